@@ -0,0 +1,14 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc for this platform");
+        let mut config = prost_build::Config::new();
+        config.protoc_executable(protoc);
+
+        println!("cargo:rerun-if-changed=proto/zkcg.proto");
+        tonic_build::configure()
+            .build_client(true)
+            .compile_protos_with_config(config, &["proto/zkcg.proto"], &["proto"])
+            .expect("failed to compile proto/zkcg.proto");
+    }
+}
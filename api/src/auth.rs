@@ -0,0 +1,280 @@
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header::AUTHORIZATION, Extensions, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use std::{
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zkcg_verifier::storage::write_private_json;
+
+use crate::rate_limit::RateLimitKey;
+use crate::tls::ClientCertIdentity;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+const TOKEN_PREFIX: &str = "zkcg";
+
+/// What a key may do. Every role can observe, `Submitter` and `Worker`
+/// grant separate capabilities on top of that, and `Admin` grants all of
+/// them; see [`Role::allows`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Read-only access to protocol state.
+    Observer,
+    /// May submit proofs and request proving.
+    Submitter,
+    /// May take proving jobs, witnesses included, as a remote prover.
+    Worker,
+    /// May manage keys and webhooks.
+    Admin,
+}
+
+impl Role {
+    /// Whether this role may use what `required` grants.
+    ///
+    /// Every role can observe and admins can do anything. Submitting and
+    /// proving are separate capabilities: a worker key cannot submit or
+    /// queue jobs, and a submitter key cannot take them.
+    pub fn allows(self, required: Role) -> bool {
+        self == Role::Admin || required == Role::Observer || self == required
+    }
+}
+
+/// A stored key. Only a salted hash of the secret is kept.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    pub id: String,
+    pub role: Role,
+    pub label: String,
+    pub created_at: u64,
+    pub revoked: bool,
+    salt: String,
+    secret_hash: String,
+}
+
+/// Authenticated caller, attached to the request by [`require_role`].
+#[derive(Debug, Clone)]
+pub struct Identity {
+    pub key_id: String,
+    pub role: Role,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeyFile {
+    keys: Vec<ApiKeyRecord>,
+}
+
+/// Local keystore of API keys, persisted as JSON.
+///
+/// Tokens have the form `zkcg_<id>_<secret>`; the id is used for lookup and
+/// the secret is checked against `sha256(salt || secret)`.
+#[derive(Clone)]
+pub struct KeyStore {
+    file: Arc<Mutex<KeyFile>>,
+    path: Option<PathBuf>,
+}
+
+impl KeyStore {
+    /// Open (or create) a keystore backed by `path`. `None` keeps it in memory.
+    pub fn open(path: Option<PathBuf>) -> io::Result<Self> {
+        let file = match &path {
+            Some(p) if p.exists() => {
+                let raw = fs::read(p)?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            _ => KeyFile::default(),
+        };
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            path,
+        })
+    }
+
+    /// Create a key and return its record plus the only copy of the token.
+    pub fn create(&self, role: Role, label: String) -> io::Result<(ApiKeyRecord, String)> {
+        let id = hex::encode(random_bytes::<8>());
+        let secret = hex::encode(random_bytes::<32>());
+        let salt = hex::encode(random_bytes::<16>());
+
+        let record = ApiKeyRecord {
+            secret_hash: hash_secret(&salt, &secret),
+            id: id.clone(),
+            role,
+            label,
+            created_at: now_secs(),
+            revoked: false,
+            salt,
+        };
+
+        let mut file = self.file.lock().unwrap();
+        file.keys.push(record.clone());
+        self.persist(&file)?;
+
+        Ok((record, format!("{}_{}_{}", TOKEN_PREFIX, id, secret)))
+    }
+
+    /// Revoke a key. Returns `false` if no such key exists.
+    pub fn revoke(&self, id: &str) -> io::Result<bool> {
+        let mut file = self.file.lock().unwrap();
+        let Some(record) = file.keys.iter_mut().find(|k| k.id == id) else {
+            return Ok(false);
+        };
+        record.revoked = true;
+        self.persist(&file)?;
+        Ok(true)
+    }
+
+    /// Create an admin key and write its token to `path`, readable by the
+    /// node's user only; returns the key id. Logs and stdout are collected
+    /// too widely for a long-lived admin credential.
+    ///
+    /// The key is revoked again if the file cannot be written, so a restart
+    /// tries afresh rather than finding an admin nobody holds the token of.
+    pub fn bootstrap_admin(&self, path: &Path) -> io::Result<String> {
+        let (record, token) = self.create(Role::Admin, "bootstrap".to_string())?;
+        let file = serde_json::json!({ "key_id": record.id, "token": token });
+        if let Err(e) = write_private_json(path, &file) {
+            self.revoke(&record.id)?;
+            return Err(e);
+        }
+        Ok(record.id)
+    }
+
+    pub fn list(&self) -> Vec<ApiKeyRecord> {
+        self.file.lock().unwrap().keys.clone()
+    }
+
+    pub fn has_active_admin(&self) -> bool {
+        self.file
+            .lock()
+            .unwrap()
+            .keys
+            .iter()
+            .any(|k| k.role == Role::Admin && !k.revoked)
+    }
+
+    /// Resolve a presented token to an identity, if it names an active key.
+    pub fn authenticate(&self, token: &str) -> Option<Identity> {
+        let rest = token.strip_prefix(TOKEN_PREFIX)?.strip_prefix('_')?;
+        let (id, secret) = rest.split_once('_')?;
+
+        let file = self.file.lock().unwrap();
+        let record = file.keys.iter().find(|k| k.id == id && !k.revoked)?;
+
+        let presented = hash_secret(&record.salt, secret);
+        if !constant_time_eq(presented.as_bytes(), record.secret_hash.as_bytes()) {
+            return None;
+        }
+
+        Some(Identity {
+            key_id: record.id.clone(),
+            role: record.role,
+        })
+    }
+
+    fn persist(&self, file: &KeyFile) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        write_private_json(path, file)
+    }
+}
+
+/// Middleware rejecting requests whose key does not grant `required`.
+///
+/// An API key takes precedence; without one, a client certificate listed in
+/// the TLS config is accepted instead.
+///
+/// Use with `middleware::from_fn_with_state(Role::Submitter, require_role)`
+/// and an `Extension<KeyStore>` layered outside it.
+pub async fn require_role(
+    State(required): State<Role>,
+    Extension(keys): Extension<KeyStore>,
+    mut req: Request<Body>,
+    next: Next,
+) -> Response {
+    let identity = match identify(&keys, req.headers(), req.extensions()) {
+        Ok(identity) => identity,
+        Err(reason) => return (StatusCode::UNAUTHORIZED, reason).into_response(),
+    };
+
+    if !identity.role.allows(required) {
+        return (StatusCode::FORBIDDEN, "insufficient role").into_response();
+    }
+
+    req.extensions_mut()
+        .insert(RateLimitKey(format!("key:{}", identity.key_id)));
+    req.extensions_mut().insert(identity);
+
+    next.run(req).await
+}
+
+/// Identity behind a request: its API key, else a listed client certificate.
+///
+/// The error is a short reason suitable for a 401 body.
+pub fn identify(
+    keys: &KeyStore,
+    headers: &HeaderMap,
+    extensions: &Extensions,
+) -> Result<Identity, &'static str> {
+    match presented_token(headers) {
+        Some(token) => keys.authenticate(&token).ok_or("invalid api key"),
+        None => match extensions.get::<ClientCertIdentity>() {
+            Some(ClientCertIdentity(identity)) => Ok(identity.clone()),
+            None => Err("missing api key"),
+        },
+    }
+}
+
+/// Token from `x-api-key`, or else `Authorization: Bearer`.
+fn presented_token(headers: &HeaderMap) -> Option<String> {
+    if let Some(v) = headers.get(API_KEY_HEADER) {
+        return v.to_str().ok().map(str::to_string);
+    }
+
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::to_string)
+}
+
+fn hash_secret(salt: &str, secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(secret.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut buf = [0u8; N];
+    OsRng.fill_bytes(&mut buf);
+    buf
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,30 @@
+use sha2::{Digest, Sha256};
+use zkcg_common::types::{Commitment, Hash};
+
+/// Domain tag prefixed to every commitment made by `/v1/prove`.
+pub const STATE_COMMITMENT_DOMAIN: &[u8] = b"zkcg/state/v1";
+
+/// Commitment the node's prover puts in the submissions it returns.
+///
+/// `sha256(domain || old_state_root || nonce (u64 BE) || threshold (u64 BE) ||
+/// sha256(proof))`
+///
+/// It chains each root to its predecessor and to the proof that advanced it,
+/// and is computed from public data only, so it reveals nothing about the
+/// prover's private inputs and anyone can recompute it. It is this node's
+/// convention, not a protocol rule: the engine applies whatever commitment a
+/// submission carries.
+pub fn next_state_commitment(
+    old_state_root: &Hash,
+    nonce: u64,
+    threshold: u64,
+    proof_bytes: &[u8],
+) -> Commitment {
+    let mut hasher = Sha256::new();
+    hasher.update(STATE_COMMITMENT_DOMAIN);
+    hasher.update(old_state_root);
+    hasher.update(nonce.to_be_bytes());
+    hasher.update(threshold.to_be_bytes());
+    hasher.update(Sha256::digest(proof_bytes));
+    Commitment(hasher.finalize().into())
+}
@@ -0,0 +1,598 @@
+use clap::{Parser, ValueEnum};
+use serde::Deserialize;
+use std::{
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    time::Duration,
+};
+use zkcg_common::{state::ProtocolState, types::Hash};
+use zkcg_verifier::backend::{self, BackendSpec, ProofBackend};
+use zkcg_verifier::mempool::MempoolConfig;
+
+use crate::auth::Role;
+use crate::jobs::JobLimits;
+use crate::rate_limit::parse_limit;
+
+/// Command-line flags. Anything set here overrides the config file.
+#[derive(Debug, Default, Parser)]
+#[command(name = "zkcg-node", about = "ZKCG verifier node")]
+pub struct Cli {
+    /// TOML config file; built-in defaults apply when omitted
+    #[arg(long, short, env = "ZKCG_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Validate the configuration, print the effective settings and exit
+    #[arg(long)]
+    pub check_config: bool,
+
+    /// Port to listen on (keeps the configured bind address)
+    #[arg(long, env = "PORT")]
+    pub port: Option<u16>,
+
+    /// Proof backend to verify with
+    #[arg(long, value_enum)]
+    pub backend: Option<BackendKind>,
+
+    /// File the accepted protocol state is persisted to
+    #[arg(long)]
+    pub state_path: Option<PathBuf>,
+
+    /// Enable the `/v1` protocol routes
+    #[arg(long, env = "ZKCG_ENABLE_PROTOCOL")]
+    pub enable_protocol: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    Zkvm,
+    Halo2,
+    /// Accepts every proof. Only in builds with the `dev-backends` feature.
+    #[cfg(any(test, feature = "dev-backends"))]
+    Stub,
+}
+
+impl From<BackendKind> for backend::BackendKind {
+    fn from(kind: BackendKind) -> Self {
+        match kind {
+            BackendKind::Zkvm => Self::Zkvm,
+            BackendKind::Halo2 => Self::Halo2,
+            #[cfg(any(test, feature = "dev-backends"))]
+            BackendKind::Stub => Self::Stub,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackendConfig {
+    pub kind: BackendKind,
+    /// Hex-encoded zkVM guest image id; the one built into this binary when unset
+    pub zkvm_image_id: Option<String>,
+    /// Halo2 params file; the bundled artifacts are used when unset
+    pub halo2_params: Option<PathBuf>,
+    /// Serialized Halo2 verifying key; derived from the params when unset
+    pub halo2_vk: Option<PathBuf>,
+    /// Overrides the backend's own proof size cap, in bytes
+    pub max_proof_bytes: Option<usize>,
+    /// Known-good proof made offline for this backend's keys or image
+    /// (`zkcg verify` format), checked before the node reports ready
+    pub self_test: Option<PathBuf>,
+    /// When false, a node without a self-test fixture reports ready without
+    /// checking anything. Development only
+    pub require_self_test: bool,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            kind: BackendKind::Zkvm,
+            zkvm_image_id: None,
+            halo2_params: None,
+            halo2_vk: None,
+            max_proof_bytes: None,
+            self_test: None,
+            require_self_test: true,
+        }
+    }
+}
+
+/// Bounds on the queue of submissions whose nonce is ahead of the state.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MempoolLimitConfig {
+    /// Queued submissions across all clients
+    pub max_entries: usize,
+    /// Queued submissions any one client may hold
+    pub max_per_client: usize,
+    /// How far past the next nonce a submission may be queued
+    pub max_lookahead: u64,
+    /// Queued submissions older than this are dropped
+    pub ttl_secs: u64,
+}
+
+impl Default for MempoolLimitConfig {
+    fn default() -> Self {
+        let defaults = MempoolConfig::default();
+        Self {
+            max_entries: defaults.max_entries,
+            max_per_client: defaults.max_per_client,
+            max_lookahead: defaults.max_lookahead,
+            ttl_secs: defaults.ttl.as_secs(),
+        }
+    }
+}
+
+impl MempoolLimitConfig {
+    pub fn mempool_config(&self) -> MempoolConfig {
+        MempoolConfig {
+            max_entries: self.max_entries,
+            max_per_client: self.max_per_client,
+            max_lookahead: self.max_lookahead,
+            ttl: Duration::from_secs(self.ttl_secs),
+        }
+    }
+
+    fn entries(&self) -> [(&'static str, u64); 4] {
+        [
+            ("max_entries", self.max_entries as u64),
+            ("max_per_client", self.max_per_client as u64),
+            ("max_lookahead", self.max_lookahead),
+            ("ttl_secs", self.ttl_secs),
+        ]
+    }
+}
+
+/// Genesis state, used only when no persisted state exists yet.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenesisConfig {
+    /// Hex-encoded 32-byte channel id; all zeroes when unset
+    pub channel: Option<String>,
+    /// Hex-encoded 32-byte root; all zeroes when unset
+    pub state_root: Option<String>,
+    pub nonce: u64,
+    pub epoch: u64,
+    /// Hex-encoded ed25519 keys of allowlisted provers
+    pub provers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StorageConfig {
+    /// Accepted state file; in-memory only when unset
+    pub state: Option<PathBuf>,
+    pub keystore: PathBuf,
+    /// Where the token of the admin key created on first start is written
+    /// (mode `0600`); it is never printed
+    pub bootstrap_token: PathBuf,
+    pub webhook_outbox: PathBuf,
+    /// Acceptance receipts and idempotency keys
+    pub receipts: PathBuf,
+    /// Append-only log of accepted transitions (JSONL), for `zkcg reverify`;
+    /// not kept when unset
+    pub transitions: Option<PathBuf>,
+    /// Proving jobs queued through `/v1/prove`, with their witnesses until they finish
+    pub jobs: PathBuf,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            state: None,
+            keystore: PathBuf::from("keystore.json"),
+            bootstrap_token: PathBuf::from("bootstrap-admin.json"),
+            webhook_outbox: PathBuf::from("webhook-outbox.json"),
+            receipts: PathBuf::from("receipts.json"),
+            transitions: None,
+            jobs: PathBuf::from("jobs.json"),
+        }
+    }
+}
+
+/// Per-route limits as `<requests>/<window secs>`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub demo_prove: String,
+    pub demo_verify: String,
+    pub submit: String,
+    pub prove: String,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            demo_prove: "5/60".to_string(),
+            demo_verify: "30/60".to_string(),
+            submit: "60/60".to_string(),
+            prove: "5/60".to_string(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn entries(&self) -> [(&'static str, &str); 4] {
+        [
+            ("demo_prove", &self.demo_prove),
+            ("demo_verify", &self.demo_verify),
+            ("submit", &self.submit),
+            ("prove", &self.prove),
+        ]
+    }
+}
+
+/// Per-route request body caps, in bytes.
+///
+/// Bodies over the cap are refused with `413` before they are buffered.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BodyLimitConfig {
+    pub demo_prove: usize,
+    /// Derived from the backend's proof size cap when unset
+    pub demo_verify: Option<usize>,
+    /// Derived from the backend's proof size cap when unset
+    pub submit: Option<usize>,
+    pub prove: usize,
+    /// Every `/v1/admin` and `/v1/webhooks` route
+    pub admin: usize,
+}
+
+impl Default for BodyLimitConfig {
+    fn default() -> Self {
+        Self {
+            demo_prove: 4 << 10,
+            demo_verify: None,
+            submit: None,
+            prove: 4 << 10,
+            admin: 64 << 10,
+        }
+    }
+}
+
+impl BodyLimitConfig {
+    /// Room for the JSON around a base64 proof.
+    const ENVELOPE: usize = 16 << 10;
+
+    /// Cap for a route carrying one base64 proof of at most `max_proof` bytes.
+    pub fn for_proof(configured: Option<usize>, max_proof: usize) -> usize {
+        configured.unwrap_or_else(|| max_proof.div_ceil(3) * 4 + Self::ENVELOPE)
+    }
+
+    fn entries(&self) -> [(&'static str, Option<usize>); 5] {
+        [
+            ("demo_prove", Some(self.demo_prove)),
+            ("demo_verify", self.demo_verify),
+            ("submit", self.submit),
+            ("prove", Some(self.prove)),
+            ("admin", Some(self.admin)),
+        ]
+    }
+}
+
+/// Route groups that are mounted at all.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RouteConfig {
+    pub demo: bool,
+    pub protocol: bool,
+    pub metrics: bool,
+    /// `zkcg.v1.Verifier` gRPC service on the same listener
+    pub grpc: bool,
+}
+
+impl Default for RouteConfig {
+    fn default() -> Self {
+        Self {
+            demo: true,
+            protocol: false,
+            metrics: true,
+            grpc: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShutdownConfig {
+    /// How long in-flight submissions may run after SIGTERM
+    pub drain_timeout_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self {
+            drain_timeout_secs: 30,
+        }
+    }
+}
+
+impl ShutdownConfig {
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_secs)
+    }
+}
+
+/// Native TLS termination. Absent means plain HTTP.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM certificate chain, leaf first
+    pub cert: PathBuf,
+    /// PEM private key (PKCS#8, PKCS#1 or SEC1)
+    pub key: PathBuf,
+    /// PEM bundle of CAs trusted to issue client certificates; enables mTLS
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+    /// Refuse handshakes without a client certificate
+    #[serde(default)]
+    pub require_client_cert: bool,
+    /// Client certificates accepted in place of an API key
+    #[serde(default)]
+    pub clients: Vec<TlsClient>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsClient {
+    pub name: String,
+    /// Hex SHA-256 of the DER certificate
+    pub fingerprint: String,
+    pub role: Role,
+}
+
+/// Everything `main` needs to start a node.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NodeConfig {
+    pub listen: SocketAddr,
+    pub backend: BackendConfig,
+    pub genesis: GenesisConfig,
+    pub storage: StorageConfig,
+    pub rate_limits: RateLimitConfig,
+    pub body_limits: BodyLimitConfig,
+    pub mempool: MempoolLimitConfig,
+    pub routes: RouteConfig,
+    pub shutdown: ShutdownConfig,
+    pub jobs: JobLimits,
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([0, 0, 0, 0], 8080)),
+            backend: BackendConfig::default(),
+            genesis: GenesisConfig::default(),
+            storage: StorageConfig::default(),
+            rate_limits: RateLimitConfig::default(),
+            body_limits: BodyLimitConfig::default(),
+            mempool: MempoolLimitConfig::default(),
+            routes: RouteConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            jobs: JobLimits::default(),
+            tls: None,
+        }
+    }
+}
+
+impl NodeConfig {
+    pub fn parse(toml_src: &str) -> Result<Self, String> {
+        toml::from_str(toml_src).map_err(|e| format!("invalid config: {}", e))
+    }
+
+    /// Config file (or defaults) with command-line overrides applied.
+    pub fn from_cli(cli: &Cli) -> Result<Self, String> {
+        let mut config = match &cli.config {
+            Some(path) => {
+                let raw = fs::read_to_string(path)
+                    .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+                Self::parse(&raw)?
+            }
+            None => Self::default(),
+        };
+
+        if let Some(port) = cli.port {
+            config.listen.set_port(port);
+        }
+        if let Some(kind) = cli.backend {
+            config.backend.kind = kind;
+        }
+        if let Some(path) = &cli.state_path {
+            config.storage.state = Some(path.clone());
+        }
+        if cli.enable_protocol {
+            config.routes.protocol = true;
+        }
+
+        Ok(config)
+    }
+
+    /// Every problem with this config, so `--check-config` can report them all at once.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut problems = Vec::new();
+
+        if let Err(e) = self.genesis_state() {
+            problems.push(e);
+        }
+
+        for (name, spec) in self.rate_limits.entries() {
+            if parse_limit(spec).is_none() {
+                problems.push(format!(
+                    "rate_limits.{}: expected `<requests>/<window secs>`, got {:?}",
+                    name, spec
+                ));
+            }
+        }
+
+        for (name, limit) in self.body_limits.entries() {
+            if limit == Some(0) {
+                problems.push(format!("body_limits.{}: must be greater than zero", name));
+            }
+        }
+        if self.backend.max_proof_bytes == Some(0) {
+            problems.push("backend.max_proof_bytes: must be greater than zero".into());
+        }
+        for (name, limit) in self.mempool.entries() {
+            if limit == 0 {
+                problems.push(format!("mempool.{}: must be greater than zero", name));
+            }
+        }
+        if self.jobs.max_concurrent == 0 {
+            problems.push("jobs.max_concurrent: must be greater than zero".into());
+        }
+        if self.jobs.max_queued == 0 {
+            problems.push("jobs.max_queued: must be greater than zero".into());
+        }
+        if self.jobs.worker_timeout_secs == 0 {
+            problems.push("jobs.worker_timeout_secs: must be greater than zero".into());
+        }
+
+        match self.backend.kind {
+            BackendKind::Zkvm if !cfg!(feature = "zk-vm") => {
+                problems.push("backend.kind = \"zkvm\" but built without the zk-vm feature".into());
+            }
+            BackendKind::Halo2 if !cfg!(feature = "zk-halo2") => {
+                problems.push("backend.kind = \"halo2\" but built without the zk-halo2 feature".into());
+            }
+            _ => {}
+        }
+        if self.routes.grpc && !cfg!(feature = "grpc") {
+            problems.push("routes.grpc = true but built without the grpc feature".into());
+        }
+
+        if let Some(id) = &self.backend.zkvm_image_id {
+            if self.backend.kind != BackendKind::Zkvm {
+                problems.push("backend.zkvm_image_id is only used by the zkvm backend".into());
+            } else if decode_hash(id).is_none() {
+                problems.push(format!("backend.zkvm_image_id: expected 32 hex bytes, got {:?}", id));
+            }
+        }
+        for (name, path) in [
+            ("backend.halo2_params", &self.backend.halo2_params),
+            ("backend.halo2_vk", &self.backend.halo2_vk),
+        ] {
+            let Some(path) = path else { continue };
+            if self.backend.kind != BackendKind::Halo2 {
+                problems.push(format!("{} is only used by the halo2 backend", name));
+            } else if !path.is_file() {
+                problems.push(format!("{}: {} not found", name, path.display()));
+            }
+        }
+        if self.backend.halo2_vk.is_some() && self.backend.halo2_params.is_none() {
+            problems.push("backend.halo2_vk needs backend.halo2_params".into());
+        }
+
+        if let Some(path) = self.backend.self_test.as_ref().filter(|p| !p.is_file()) {
+            problems.push(format!("backend.self_test: {} not found", path.display()));
+        }
+
+        for (name, path) in [
+            ("storage.state", self.storage.state.as_ref()),
+            ("storage.keystore", Some(&self.storage.keystore)),
+            ("storage.bootstrap_token", Some(&self.storage.bootstrap_token)),
+            ("storage.webhook_outbox", Some(&self.storage.webhook_outbox)),
+            ("storage.receipts", Some(&self.storage.receipts)),
+            ("storage.transitions", self.storage.transitions.as_ref()),
+            ("storage.jobs", Some(&self.storage.jobs)),
+        ] {
+            let Some(path) = path else { continue };
+            let missing_dir = path
+                .parent()
+                .filter(|d| !d.as_os_str().is_empty() && !d.is_dir());
+            if let Some(dir) = missing_dir {
+                problems.push(format!("{}: directory {} does not exist", name, dir.display()));
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            validate_tls(tls, &mut problems);
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
+    pub fn genesis_state(&self) -> Result<ProtocolState, String> {
+        let g = &self.genesis;
+        let state_root = match &g.state_root {
+            Some(hex_root) => decode_hash(hex_root)
+                .ok_or_else(|| format!("genesis.state_root: expected 32 hex bytes, got {:?}", hex_root))?,
+            None => [0u8; 32],
+        };
+        let channel = match &g.channel {
+            Some(hex_channel) => decode_hash(hex_channel)
+                .ok_or_else(|| format!("genesis.channel: expected 32 hex bytes, got {:?}", hex_channel))?,
+            None => [0u8; 32],
+        };
+        let provers = g
+            .provers
+            .iter()
+            .map(|p| decode_hash(p).ok_or_else(|| format!("genesis.provers: invalid key {:?}", p)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(ProtocolState {
+            channel,
+            state_root,
+            nonce: g.nonce,
+            epoch: g.epoch,
+            provers,
+        })
+    }
+
+    /// Instantiate the configured proof backend.
+    pub fn build_backend(&self) -> Result<Box<dyn ProofBackend>, String> {
+        let zkvm_image_id = match &self.backend.zkvm_image_id {
+            Some(id) => Some(
+                decode_hash(id).ok_or_else(|| format!("backend.zkvm_image_id: invalid image id {:?}", id))?,
+            ),
+            None => None,
+        };
+        BackendSpec {
+            kind: self.backend.kind.into(),
+            zkvm_image_id,
+            halo2_params: self.backend.halo2_params.clone(),
+            halo2_vk: self.backend.halo2_vk.clone(),
+            max_proof_size: self.backend.max_proof_bytes,
+        }
+        .build()
+    }
+}
+
+fn validate_tls(tls: &TlsConfig, problems: &mut Vec<String>) {
+    for (name, path) in [
+        ("tls.cert", Some(&tls.cert)),
+        ("tls.key", Some(&tls.key)),
+        ("tls.client_ca", tls.client_ca.as_ref()),
+    ] {
+        if let Some(path) = path.filter(|p| !p.is_file()) {
+            problems.push(format!("{}: {} not found", name, path.display()));
+        }
+    }
+
+    if tls.client_ca.is_none() {
+        if tls.require_client_cert {
+            problems.push("tls.require_client_cert needs tls.client_ca".into());
+        }
+        if !tls.clients.is_empty() {
+            problems.push("tls.clients needs tls.client_ca".into());
+        }
+    }
+
+    for client in &tls.clients {
+        if decode_hash(&client.fingerprint).is_none() {
+            problems.push(format!(
+                "tls.clients {:?}: fingerprint must be 32 hex bytes",
+                client.name
+            ));
+        }
+    }
+}
+
+fn decode_hash(s: &str) -> Option<Hash> {
+    hex::decode(s.trim_start_matches("0x")).ok()?.try_into().ok()
+}
@@ -0,0 +1,296 @@
+// tonic's `Status` is large, and every RPC returns it by value
+#![allow(clippy::result_large_err)]
+
+use axum::Router;
+use std::{pin::Pin, sync::Arc};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, WatchStream},
+    Stream, StreamExt,
+};
+use tonic::{metadata::MetadataValue, service::Routes, Code, Request, Response, Status};
+
+use zkcg_common::{errors::ProtocolError, state::ProtocolState, types::Commitment, wire};
+use zkcg_verifier::engine::{check_proof, PublicInputs};
+use zkcg_verifier::pipeline::Submission;
+use zkcg_verifier::signature::ProverSignature;
+
+use crate::auth::{identify, Identity, KeyStore, Role};
+use crate::handler::{process_submission, AppState, SubmissionStatus, SubmitError};
+use crate::metrics::metrics;
+use crate::receipts::{valid_key, AcceptanceRecord, IDEMPOTENCY_KEY_HEADER};
+use crate::rate_limit::{RateLimitKey, RateLimiter};
+use crate::shutdown::Shutdown;
+
+pub mod pb {
+    tonic::include_proto!("zkcg.v1");
+}
+
+use pb::verifier_server::{Verifier, VerifierServer};
+
+/// Metadata key carrying the SPEC §9 code of a rejected request; the REST
+/// routes send the same header.
+pub const ERROR_CODE_METADATA: &str = wire::ERROR_CODE_HEADER;
+
+/// The `zkcg.v1.Verifier` service, backed by the same state as the REST routes.
+#[derive(Clone)]
+pub struct GrpcService {
+    state: AppState,
+    keys: KeyStore,
+    shutdown: Shutdown,
+    submit_limiter: Arc<RateLimiter>,
+}
+
+impl GrpcService {
+    /// `submit_limiter` is shared with `/v1/submit-proof`, so a key has one
+    /// quota whichever transport it uses.
+    pub fn new(
+        state: AppState,
+        keys: KeyStore,
+        shutdown: Shutdown,
+        submit_limiter: Arc<RateLimiter>,
+    ) -> Self {
+        Self {
+            state,
+            keys,
+            shutdown,
+            submit_limiter,
+        }
+    }
+
+    /// Routes for merging into the axum app, so gRPC shares its listener,
+    /// TLS settings and middleware. Larger request messages are refused
+    /// before they are decoded.
+    pub fn into_router(self, max_message_size: usize) -> Router {
+        let server = VerifierServer::new(self).max_decoding_message_size(max_message_size);
+        Routes::new(server).into_axum_router()
+    }
+
+    /// Same rules as [`crate::auth::require_role`]: API key first, then a
+    /// listed client certificate.
+    fn authorize<T>(&self, req: &Request<T>, required: Role) -> Result<Identity, Status> {
+        let headers = req.metadata().clone().into_headers();
+        let identity = identify(&self.keys, &headers, req.extensions())
+            .map_err(Status::unauthenticated)?;
+
+        if !identity.role.allows(required) {
+            return Err(Status::permission_denied("insufficient role"));
+        }
+        Ok(identity)
+    }
+}
+
+type TransitionStream = Pin<Box<dyn Stream<Item = Result<pb::Transition, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl Verifier for GrpcService {
+    async fn submit_proof(
+        &self,
+        req: Request<pb::SubmitProofRequest>,
+    ) -> Result<Response<pb::SubmitProofResponse>, Status> {
+        let identity = self.authorize(&req, Role::Submitter)?;
+
+        let decision = self
+            .submit_limiter
+            .check(&RateLimitKey(format!("key:{}", identity.key_id)));
+        if !decision.allowed {
+            metrics().record_rate_limited("/zkcg.v1.Verifier/SubmitProof");
+            return Err(Status::resource_exhausted("rate limit exceeded"));
+        }
+
+        let Some(_in_flight) = self.shutdown.track() else {
+            return Err(Status::unavailable("node is shutting down"));
+        };
+
+        let idempotency_key = match req.metadata().get(IDEMPOTENCY_KEY_HEADER) {
+            Some(value) => match value.to_str() {
+                Ok(key) if valid_key(key) => Some(key.to_string()),
+                _ => return Err(Status::invalid_argument("invalid idempotency-key metadata")),
+            },
+            None => None,
+        };
+
+        let req = req.into_inner();
+        tracing::info!(
+            client = %identity.key_id,
+            nonce = req.public_inputs.as_ref().map(|p| p.nonce),
+            signed = req.signature.is_some(),
+            "proof submitted over grpc"
+        );
+        let submission = decode_submission(req).map_err(|err| {
+            metrics().record_rejection(&err);
+            rejection(err)
+        })?;
+
+        let key = idempotency_key.as_deref();
+        match process_submission(&self.state, &identity.key_id, submission, key).await {
+            Ok(submitted) => {
+                let status = match submitted.status {
+                    SubmissionStatus::Accepted => pb::SubmissionStatus::Accepted,
+                    SubmissionStatus::Queued => pb::SubmissionStatus::Queued,
+                };
+                Ok(Response::new(pb::SubmitProofResponse {
+                    status: status.into(),
+                    receipt: submitted.receipt.map(encode_receipt),
+                    replayed: submitted.replayed,
+                }))
+            }
+            Err(SubmitError::Rejected(err)) => Err(rejection(err)),
+            Err(SubmitError::IdempotencyConflict) => Err(Status::already_exists(
+                "idempotency key already used for a different submission",
+            )),
+            Err(SubmitError::Internal) => Err(Status::internal("verification task failed")),
+            Err(SubmitError::Persistence) => Err(Status::internal("failed to persist state")),
+        }
+    }
+
+    async fn get_state(
+        &self,
+        req: Request<pb::GetStateRequest>,
+    ) -> Result<Response<pb::ProtocolState>, Status> {
+        self.authorize(&req, Role::Observer)?;
+
+        let engine = self.state.engine.lock().unwrap();
+        Ok(Response::new(encode_state(engine.state())))
+    }
+
+    type StreamTransitionsStream = TransitionStream;
+
+    async fn stream_transitions(
+        &self,
+        req: Request<pb::StreamTransitionsRequest>,
+    ) -> Result<Response<Self::StreamTransitionsStream>, Status> {
+        let identity = self.authorize(&req, Role::Observer)?;
+        tracing::debug!(client = %identity.key_id, "transition stream opened");
+
+        let transitions = BroadcastStream::new(self.state.transitions.subscribe()).map(|item| {
+            Some(match item {
+                Ok(t) => Ok(pb::Transition {
+                    before: Some(encode_state(&t.before)),
+                    after: Some(encode_state(&t.after)),
+                }),
+                // the subscriber fell behind and transitions were dropped; the
+                // client has to re-read the state before trusting the stream again
+                Err(BroadcastStreamRecvError::Lagged(missed)) => Err(Status::data_loss(format!(
+                    "stream lagged; {} transitions dropped",
+                    missed
+                ))),
+            })
+        });
+        // the stream never ends on its own, and an open one would hold the
+        // connection, and so the drain, until the deadline
+        let draining = WatchStream::new(self.shutdown.subscribe())
+            .filter(|draining| *draining)
+            .map(|_| None);
+        let stream = transitions.merge(draining).map_while(|item| item);
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn verify(
+        &self,
+        req: Request<pb::VerifyRequest>,
+    ) -> Result<Response<pb::VerifyResponse>, Status> {
+        self.authorize(&req, Role::Observer)?;
+
+        let req = req.into_inner();
+        let inputs = req
+            .public_inputs
+            .ok_or(ProtocolError::InvalidFormat)
+            .and_then(decode_inputs)
+            .map_err(rejection)?;
+
+        let backend = self.state.engine.lock().unwrap().backend();
+        let span = tracing::Span::current();
+        let result = tokio::task::spawn_blocking(move || {
+            let _guard = span.enter();
+            check_proof(backend.as_ref(), &req.proof, &inputs)
+        })
+        .await
+        .map_err(|_| Status::internal("verification task failed"))?;
+
+        let response = match result {
+            Ok(_) => pb::VerifyResponse {
+                valid: true,
+                error: pb::ErrorCode::Unspecified.into(),
+            },
+            Err(err) => pb::VerifyResponse {
+                valid: false,
+                error: error_code(&err).into(),
+            },
+        };
+        Ok(Response::new(response))
+    }
+}
+
+/// gRPC status for a protocol error, mirroring the REST status mapping.
+pub fn rejection(err: ProtocolError) -> Status {
+    use ProtocolError::*;
+
+    let code = match err {
+        InvalidFormat | InvalidProof | CommitmentMismatch | PolicyViolation => Code::InvalidArgument,
+        InvalidNonce | StateMismatch => Code::FailedPrecondition,
+        UnauthorizedProver => Code::PermissionDenied,
+    };
+    let mut status = Status::new(code, err.to_string());
+    status
+        .metadata_mut()
+        .insert(ERROR_CODE_METADATA, MetadataValue::from_static(err.code()));
+    status
+}
+
+pub fn error_code(err: &ProtocolError) -> pb::ErrorCode {
+    pb::ErrorCode::from_str_name(err.code()).unwrap_or(pb::ErrorCode::Unspecified)
+}
+
+pub fn encode_state(state: &ProtocolState) -> pb::ProtocolState {
+    pb::ProtocolState {
+        state_root: state.state_root.to_vec(),
+        nonce: state.nonce,
+        epoch: state.epoch,
+        provers: state.provers.iter().map(|p| p.to_vec()).collect(),
+        channel: state.channel.to_vec(),
+    }
+}
+
+fn encode_receipt(record: AcceptanceRecord) -> pb::AcceptanceRecord {
+    pb::AcceptanceRecord {
+        // ids are always written by `ReceiptStore` as hex
+        submission_id: hex::decode(&record.submission_id).unwrap_or_default(),
+        nonce: record.nonce,
+        epoch: record.epoch,
+        state_root: record.state_root.to_vec(),
+        accepted_at_ms: record.accepted_at,
+    }
+}
+
+fn decode_submission(req: pb::SubmitProofRequest) -> Result<Submission, ProtocolError> {
+    let public_inputs = decode_inputs(req.public_inputs.ok_or(ProtocolError::InvalidFormat)?)?;
+    let signature = req
+        .signature
+        .map(|s| -> Result<_, ProtocolError> {
+            Ok(ProverSignature {
+                public_key: decode_array(&s.public_key)?,
+                signature: decode_array(&s.signature)?,
+            })
+        })
+        .transpose()?;
+
+    Ok(Submission {
+        proof_bytes: req.proof,
+        public_inputs,
+        commitment: Commitment(decode_array(&req.new_state_commitment)?),
+        signature,
+    })
+}
+
+fn decode_inputs(inputs: pb::PublicInputs) -> Result<PublicInputs, ProtocolError> {
+    Ok(PublicInputs {
+        threshold: inputs.threshold,
+        old_state_root: decode_array::<32>(&inputs.old_state_root)?,
+        nonce: inputs.nonce,
+    })
+}
+
+fn decode_array<const N: usize>(bytes: &[u8]) -> Result<[u8; N], ProtocolError> {
+    bytes.try_into().map_err(|_| ProtocolError::InvalidFormat)
+}
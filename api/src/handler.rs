@@ -1,214 +1,1259 @@
-use axum::{
-    extract::State,
-    http::StatusCode,
-    Json,
-};
-use zkcg_verifier::engine::{PublicInputs, VerifierEngine};
-use zkcg_common::{
-    errors::ProtocolError,
-    types::Commitment,
-};
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
-use crate::models::{SubmitProofRequest, SubmitProofResponse, ProveRequest, ProveResponse, ProvePublicInputs};
-use crate::models::{
-    DemoProveRequest,
-    DemoProveResponse,
-    DemoVerifyRequest,
-    DemoVerifyResponse,
-};
-
-use std::sync::{Arc, Mutex};
-use zkcg_verifier::backend::ProofBackend;
-#[cfg(feature = "zk-vm")]
-use zkcg_zkvm_host::{prove as zkvm_prove, ZkVmProverError};
-use zkcg_verifier::backend_zkvm::ZkVmBackend;
-use axum::Extension;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub engine: Arc<Mutex<VerifierEngine>>
-}
-
-pub async fn submit_proof(
-    Extension(state): Extension<AppState>,
-    Json(req): Json<SubmitProofRequest>,
-) -> Result<Json<SubmitProofResponse>, (StatusCode, String)> {
-    println!("================ ZKCG =================");
-    println!("📥 Received /v1/submit-proof request");
-    println!("• threshold   : {}", req.public_inputs.threshold);
-    println!("• nonce       : {}", req.public_inputs.nonce);
-    println!("• commitment  : {:?}", req.new_state_commitment);
-    let mut engine = state.engine.lock().unwrap();
-    let proof_bytes = STANDARD
-    .decode(&req.proof)
-    .map_err(|_| (StatusCode::BAD_REQUEST, "invalid base64 proof".to_string()))?;
-    let inputs = PublicInputs {
-        threshold: req.public_inputs.threshold,
-        old_state_root: req.public_inputs.old_state_root,
-        nonce: req.public_inputs.nonce,
-    };
-
-    let commitment = Commitment(req.new_state_commitment);
-
-    engine
-        .process_transition(&proof_bytes, inputs, commitment)
-        .map_err(map_error)?;
-    println!("✅ Proof accepted");
-    Ok(Json(SubmitProofResponse {
-        status: "accepted".to_string(),
-    }))
-}
-
-fn map_error(err: ProtocolError) -> (StatusCode, String) {
-    use ProtocolError::*;
-
-    match err {
-        InvalidFormat => (StatusCode::BAD_REQUEST, err.to_string()),
-        InvalidNonce => (StatusCode::CONFLICT, err.to_string()),
-        StateMismatch => (StatusCode::CONFLICT, err.to_string()),
-        PolicyViolation => (StatusCode::UNPROCESSABLE_ENTITY, err.to_string()),
-        InvalidProof => (StatusCode::BAD_REQUEST, err.to_string()),
-        CommitmentMismatch => (StatusCode::BAD_REQUEST, err.to_string()),
-    }
-}
-
-fn map_prover_error(err: ZkVmProverError) -> (StatusCode, String) {
-    match err {
-        ZkVmProverError::PolicyViolation => (
-            StatusCode::UNPROCESSABLE_ENTITY,
-            "policy violation".to_string(),
-        ),
-
-        ZkVmProverError::ExecutionFailed => (
-            StatusCode::BAD_REQUEST,
-            "zkvm execution failed".to_string(),
-        ),
-
-    }
-}
-
-
-#[cfg(feature = "zk-vm")]
-pub async fn prove(
-    Extension(_state): Extension<AppState>, // backend NOT needed here
-    Json(req): Json<ProveRequest>,
-) -> Result<Json<ProveResponse>, (StatusCode, String)> {
-
-    // DEV / DEMO SAFETY
-    if std::env::var("ZKCG_ENABLE_PROVER").is_err() {
-        return Err((StatusCode::FORBIDDEN, "prover disabled".into()));
-    }
-
-    println!("🧪 zkVM prover request received");
-    println!("• secret_value: {}", req.secret_value);
-    println!("• threshold   : {}", req.threshold);
-
-    // ---- IMPORTANT ----
-    // For demo purposes we always prove against GENESIS
-    let old_state_root = [0u8; 32];
-    let nonce = 1;
-
-    let proof = zkvm_prove(
-        req.secret_value,
-        req.threshold,
-        old_state_root,
-        nonce,
-    ).map_err(|e| map_prover_error(e))?;
-
-    Ok(Json(ProveResponse {
-        proof: STANDARD.encode(&proof),
-        public_inputs: ProvePublicInputs {
-            threshold: req.threshold,
-        },
-        commitment: {
-            let mut c = [0u8; 32];
-            c[0] = (req.secret_value % 256) as u8;
-            c
-        },
-    }))
-}
-
-#[cfg(feature = "zk-vm")]
-pub fn demo_prove(
-    score: u64,
-    threshold: u64,
-) -> Result<Vec<u8>, ProtocolError> {
-    // demo safety limits
-    if score > 100 || threshold > 100 {
-        return Err(ProtocolError::PolicyViolation);
-    }
-
-    // Demo = always prove against GENESIS
-    let old_state_root = [0u8; 32];
-    let nonce = 1;
-
-    let proof = zkvm_prove(
-        score,
-        threshold,
-        old_state_root,
-        nonce,
-    ).map_err(|_| ProtocolError::InvalidProof)?;
-
-    Ok(proof)
-}
-
-
-/// Demo-only proof verification (stateless)
-
-#[cfg(feature = "zk-vm")]
-pub fn demo_verify(
-    proof_b64: &str,
-    threshold: u64,
-) -> Result<bool, ProtocolError> {
-    let proof_bytes = STANDARD
-        .decode(proof_b64)
-        .map_err(|_| ProtocolError::InvalidFormat)?;
-
-    let public_inputs = PublicInputs {
-        threshold,
-        old_state_root: [0u8; 32],
-        nonce: 1,
-    };
-
-    let backend = ZkVmBackend;
-
-     match backend.verify(&proof_bytes, &public_inputs) {
-        Ok(()) => Ok(true),
-        Err(_) => Ok(false),
-    }
-}
-
-
-pub async fn demo_prove_handler(
-    Json(req): Json<DemoProveRequest>,
-) -> Result<Json<DemoProveResponse>, (StatusCode, String)> {
-
-    println!("🧪 Demo prover request");
-    println!("• secret_value: {}", req.score);
-    println!("• threshold   : {}", req.threshold);
-
-    let proof = demo_prove(req.score, req.threshold)
-        .map_err(map_error)?;
-
-    Ok(Json(DemoProveResponse {
-        proof: STANDARD.encode(&proof),
-        proof_size_bytes: proof.len(),
-        note: "Demo-only stateless proof",
-    }))
-}
-
-pub async fn demo_verify_handler(
-    Json(req): Json<DemoVerifyRequest>,
-) -> Result<Json<DemoVerifyResponse>, (StatusCode, String)> {
-
-    println!("🧪 Demo verify request");
-    println!("• threshold: {}", req.threshold);
-
-    let verified = demo_verify(&req.proof, req.threshold)
-        .map_err(map_error)?;
-
-    Ok(Json(DemoVerifyResponse {
-        verified,
-    }))
-}
+use axum::{
+    extract::{rejection::JsonRejection, Path, State},
+    http::{header::LOCATION, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use zkcg_verifier::engine::{ProverRemoval, PublicInputs, VerifierEngine};
+use zkcg_common::{
+    errors::ProtocolError,
+    types::Hash,
+    wire::{decode_hex_array, decode_proof, encode_proof, ERROR_CODE_HEADER, UNKNOWN_WORKER_CODE},
+};
+use crate::commitment::next_state_commitment;
+use crate::models::{SubmitProofRequest, SubmitProofResponse, ProveRequest, PublicInputsDto};
+use crate::models::{
+    DemoProveRequest,
+    DemoProveResponse,
+    DemoVerifyRequest,
+    DemoVerifyResponse,
+    RegisterWebhookRequest,
+    RegisterWebhookResponse,
+    CreateApiKeyRequest,
+    CreateApiKeyResponse,
+    ApiKeyInfo,
+    RegisterProverRequest,
+    ProverListResponse,
+    StateResponse,
+    SubmissionStatusResponse,
+    RegisterWorkerRequest,
+    RegisterWorkerResponse,
+    WorkerJob,
+    CompleteJobRequest,
+};
+use crate::auth::{Identity, KeyStore};
+use crate::jobs::{Job, JobError, JobQueue};
+use crate::metrics::metrics;
+use crate::receipts::{valid_key, AcceptanceRecord, ReceiptStore, IDEMPOTENCY_KEY_HEADER};
+use crate::webhook::{Delivery, WebhookDispatcher};
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use zkcg_verifier::mempool::{Admission, AppliedTransition, DroppedSubmission};
+use zkcg_verifier::pipeline::Submission;
+use tokio::sync::broadcast;
+use zkcg_verifier::storage::StateStore;
+use crate::history::TransitionLog;
+use zkcg_verifier::backend::ProofBackend;
+#[cfg(feature = "zk-vm")]
+use zkcg_zkvm_host::{prove as zkvm_prove, ZkVmProverError};
+use zkcg_verifier::backend_zkvm::ZkVmBackend;
+use axum::Extension;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub engine: Arc<Mutex<VerifierEngine>>,
+    pub webhooks: WebhookDispatcher,
+    pub store: StateStore,
+    /// Every applied transition, for streaming subscribers.
+    pub transitions: broadcast::Sender<AppliedTransition>,
+    pub receipts: ReceiptStore,
+    /// Accepted submissions, for re-verification against another backend.
+    pub history: TransitionLog,
+    /// Proving requests from `/v1/prove`.
+    pub jobs: JobQueue,
+}
+
+impl AppState {
+    /// Stage the engine's state in the store; call with the engine lock held
+    /// so saves land in the same order as the transitions.
+    fn save_state(&self, engine: &VerifierEngine) {
+        self.store.save(engine.state().clone());
+    }
+
+    /// Write everything saved so far to disk, on the blocking pool. Call
+    /// once the engine lock is released, and acknowledge nothing that
+    /// depends on the new state unless it succeeds.
+    ///
+    /// Receipts go first: a crash before the state lands leaves receipts
+    /// for states that were never saved, which [`ReceiptStore::forget_after`]
+    /// discards on startup, whereas the other order would leave an applied
+    /// transition whose retry finds no receipt.
+    async fn flush_state(&self) -> std::io::Result<()> {
+        let (receipts, store) = (self.receipts.clone(), self.store.clone());
+        let flushed = tokio::task::spawn_blocking(move || {
+            receipts.flush()?;
+            store.flush()
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = &flushed {
+            tracing::error!(error = %e, "failed to persist protocol state");
+        }
+        flushed
+    }
+}
+
+/// Result of a submission that passed every check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    Accepted,
+    Queued,
+}
+
+impl SubmissionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SubmissionStatus::Accepted => "accepted",
+            SubmissionStatus::Queued => "queued",
+        }
+    }
+}
+
+/// Outcome of [`process_submission`].
+#[derive(Debug, Clone)]
+pub struct Submitted {
+    pub status: SubmissionStatus,
+    /// Set once accepted.
+    pub receipt: Option<AcceptanceRecord>,
+    /// The submission had been seen before and was not processed again.
+    pub replayed: bool,
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Rejected(ProtocolError),
+    /// The idempotency key was already used for a different submission.
+    IdempotencyConflict,
+    /// The verification task died; says nothing about the proof.
+    Internal,
+    /// The transition was applied but could not be made durable, so it may
+    /// not survive a restart. Resubmitting answers with its fate.
+    Persistence,
+}
+
+impl SubmitError {
+    /// Count the rejection and wrap it.
+    fn rejected(err: ProtocolError) -> Self {
+        metrics().record_rejection(&err);
+        SubmitError::Rejected(err)
+    }
+}
+
+/// Check, verify and admit one submission on behalf of `client`.
+///
+/// Shared by every transport (REST, gRPC) so they see identical semantics,
+/// metrics, persistence and notifications.
+///
+/// A submission whose (proof, public inputs, commitment) was already accepted
+/// or queued is answered from the receipt store instead of being verified
+/// again, so a client retrying after a timeout learns what happened to its
+/// first attempt. An `idempotency_key` is bound to the first submission it
+/// is used with; it is reserved before verification and handed back if the
+/// submission is not admitted.
+pub async fn process_submission(
+    state: &AppState,
+    client: &str,
+    submission: Submission,
+    idempotency_key: Option<&str>,
+) -> Result<Submitted, SubmitError> {
+    let id = submission.id();
+    let reserved = match idempotency_key {
+        Some(key) => state
+            .receipts
+            .reserve_key(client, key, &id)
+            .map_err(|_| SubmitError::IdempotencyConflict)?,
+        None => false,
+    };
+
+    let result = submit_once(state, client, submission, &id).await;
+    if let (Err(_), Some(key), true) = (&result, idempotency_key, reserved) {
+        state.receipts.release_key(client, key, &id);
+    }
+    result
+}
+
+/// [`process_submission`] once its idempotency key is reserved.
+async fn submit_once(
+    state: &AppState,
+    client: &str,
+    submission: Submission,
+    id: &Hash,
+) -> Result<Submitted, SubmitError> {
+    if let Some(replay) = replayed(state, id) {
+        tracing::info!(submission_id = %hex::encode(id), "resubmission answered from receipts");
+        return Ok(replay);
+    }
+
+    let nonce = submission.public_inputs.nonce;
+    let submitted = match admit_submission(state, client, submission, id).await {
+        Ok(submitted) => submitted,
+        // an identical submission may have been accepted while this one was in flight
+        Err(e @ SubmitError::Rejected(ProtocolError::InvalidNonce | ProtocolError::StateMismatch)) => {
+            return replayed(state, id).ok_or(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if submitted.status == SubmissionStatus::Queued {
+        state.receipts.mark_queued(client, nonce, id);
+    }
+    Ok(submitted)
+}
+
+/// Earlier outcome of submission `id`, if it was accepted or is still queued.
+fn replayed(state: &AppState, id: &Hash) -> Option<Submitted> {
+    if let Some(receipt) = state.receipts.get(id) {
+        return Some(Submitted {
+            status: SubmissionStatus::Accepted,
+            receipt: Some(receipt),
+            replayed: true,
+        });
+    }
+    state.receipts.is_queued(id).then_some(Submitted {
+        status: SubmissionStatus::Queued,
+        receipt: None,
+        replayed: true,
+    })
+}
+
+/// Log, count and record queued submissions the mempool dropped, so their
+/// clients can learn what happened from `GET /v1/submissions/{id}`.
+fn report_dropped(state: &AppState, dropped: Vec<DroppedSubmission>) {
+    for entry in dropped {
+        let reason = entry.reason.as_str();
+        metrics().record_mempool_drop(reason);
+        state.receipts.dropped(&entry.client, entry.nonce, &entry.submission_id, reason);
+        tracing::warn!(
+            client = %entry.client,
+            nonce = entry.nonce,
+            reason,
+            submission_id = %hex::encode(entry.submission_id),
+            "queued submission dropped"
+        );
+    }
+}
+
+async fn admit_submission(
+    state: &AppState,
+    client: &str,
+    submission: Submission,
+    id: &Hash,
+) -> Result<Submitted, SubmitError> {
+    // the proof moves into the verification task; the log needs it afterwards
+    let mut logged = state.history.is_enabled().then(|| submission.clone());
+    let Submission {
+        proof_bytes,
+        public_inputs: inputs,
+        commitment,
+        signature,
+    } = submission;
+    metrics().observe_proof_size(proof_bytes.len());
+
+    // Cheap state check under the lock; stale submissions never reach the prover backend.
+    let verifier = {
+        let engine = state.engine.lock().unwrap();
+        engine.precheck(client, &inputs).map_err(SubmitError::rejected)?;
+        engine
+            .authorize(signature.as_ref().map(|s| &s.public_key))
+            .map_err(SubmitError::rejected)?;
+        engine.verifier()
+    };
+    let backend_name = verifier.backend().name();
+
+    // Verification can take seconds, so it runs on the blocking pool with no lock held.
+    let admitted_commitment = commitment.clone();
+    let span = tracing::Span::current();
+    let verified = tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let started = Instant::now();
+        let result = match &signature {
+            Some(sig) => verifier.verify_signed(&proof_bytes, inputs, &commitment, sig),
+            None => verifier.verify(&proof_bytes, inputs),
+        };
+        metrics().observe_verify(verifier.backend().name(), started.elapsed());
+        result
+    })
+    .await
+    .map_err(|_| SubmitError::Internal)?
+    .map_err(SubmitError::rejected)?;
+
+    let mut receipt = None;
+    let (admission, dropped) = {
+        let mut engine = state.engine.lock().unwrap();
+        let admission = engine.admit(client, *id, verified, admitted_commitment);
+        // admitting purges the mempool, whether or not this submission gets in
+        let dropped = engine.take_dropped();
+        match &admission {
+            Err(_) => {}
+            Ok(Admission::Accepted(applied)) => {
+                state.save_state(&engine);
+                // still under the lock, so the log and the receipts see
+                // transitions in order and are flushed with the state
+                for transition in applied {
+                    let own = transition.submission_id == *id;
+                    let record = state.receipts.accepted(&transition.client, &transition.after, &transition.submission_id);
+                    if own {
+                        receipt = Some(record);
+                    }
+                    let submission = logged.as_ref().filter(|_| own);
+                    state.history.record(transition, submission, backend_name);
+                }
+            }
+            Ok(Admission::Queued) => {
+                if let Some(submission) = logged.take() {
+                    state.history.hold(client, submission);
+                }
+            }
+        }
+        metrics().set_state(engine.state());
+        (admission, dropped)
+    };
+    report_dropped(state, dropped);
+    let admission = admission.map_err(SubmitError::rejected)?;
+
+    let applied = match admission {
+        Admission::Queued => {
+            metrics().record_outcome("queued");
+            tracing::info!("proof queued until its predecessor is accepted");
+            return Ok(Submitted {
+                status: SubmissionStatus::Queued,
+                receipt: None,
+                replayed: false,
+            });
+        }
+        Admission::Accepted(applied) => applied,
+    };
+    tracing::info!(applied = applied.len(), "proof accepted");
+    let flushed = state.flush_state().await;
+
+    // the transitions are final in memory at this point, durable or not; a
+    // failed enqueue must not undo them
+    for transition in applied {
+        metrics().record_outcome("accepted");
+        state.webhooks.notify_transition(&transition.before, &transition.after);
+        // no subscribers is fine
+        let _ = state.transitions.send(transition);
+    }
+    flushed.map_err(|_| SubmitError::Persistence)?;
+    Ok(Submitted {
+        status: SubmissionStatus::Accepted,
+        receipt,
+        replayed: false,
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/submit-proof",
+    tag = "protocol",
+    request_body = SubmitProofRequest,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen retry key (1-255 visible ASCII characters), bound to the first submission sent with it"),
+    ),
+    responses(
+        (status = 200, description = "Proof accepted or queued, or a replay of an earlier acceptance", body = SubmitProofResponse),
+        (status = 400, description = "Malformed or oversized submission, invalid proof or commitment mismatch", body = String),
+        (status = 401, description = "Missing or invalid API key", body = String),
+        (status = 403, description = "Insufficient role or prover not allowlisted", body = String),
+        (status = 409, description = "Stale nonce or state root", body = String),
+        (status = 413, description = "Body over `body_limits.submit`", body = String),
+        (status = 422, description = "Policy violation, or idempotency key reused for a different submission", body = String),
+        (status = 429, description = "Rate limit exceeded", body = String),
+        (status = 503, description = "Node is shutting down", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn submit_proof(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    headers: HeaderMap,
+    payload: Result<Json<SubmitProofRequest>, JsonRejection>,
+) -> Result<Json<SubmitProofResponse>, ApiError> {
+    let req = json_body(payload)
+        .inspect_err(|_| metrics().record_rejection(&ProtocolError::InvalidFormat))?;
+    let idempotency_key = idempotency_key(&headers)?;
+    tracing::info!(
+        client = %identity.key_id,
+        threshold = req.public_inputs.threshold,
+        nonce = req.public_inputs.nonce,
+        signed = req.signature.is_some(),
+        "proof submitted"
+    );
+    let max_proof = state.engine.lock().unwrap().backend().max_proof_size();
+    let submission = Submission::from_wire(&req, max_proof).map_err(reject)?;
+
+    // Submissions are attributed to the api key for mempool quotas.
+    let submitted = process_submission(&state, &identity.key_id, submission, idempotency_key)
+        .await
+        .map_err(|e| match e {
+            SubmitError::Rejected(err) => map_error(err),
+            SubmitError::IdempotencyConflict => ApiError::from((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "idempotency key already used for a different submission".to_string(),
+            )),
+            SubmitError::Internal => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "verification task failed".to_string()).into()
+            }
+            SubmitError::Persistence => {
+                (StatusCode::INTERNAL_SERVER_ERROR, "failed to persist state".to_string()).into()
+            }
+        })?;
+
+    Ok(Json(SubmitProofResponse {
+        status: submitted.status.as_str().to_string(),
+        receipt: submitted.receipt,
+        replayed: submitted.replayed,
+    }))
+}
+
+/// The optional `Idempotency-Key` header: 1-255 visible ASCII characters.
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<&str>, (StatusCode, String)> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str() {
+        Ok(key) if valid_key(key) => Ok(Some(key)),
+        _ => Err((StatusCode::BAD_REQUEST, "invalid Idempotency-Key header".to_string())),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/state",
+    tag = "protocol",
+    responses(
+        (status = 200, description = "Latest accepted state", body = StateResponse),
+        (status = 401, description = "Missing or invalid API key", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn get_state(
+    Extension(state): Extension<AppState>,
+) -> Json<StateResponse> {
+    let engine = state.engine.lock().unwrap();
+    let current = engine.state();
+    Json(StateResponse {
+        channel: hex::encode(current.channel),
+        state_root: current.state_root,
+        nonce: current.nonce,
+        epoch: current.epoch,
+        provers: current.provers.iter().map(hex::encode).collect(),
+    })
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/webhooks",
+    tag = "admin",
+    request_body = RegisterWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook registered", body = RegisterWebhookResponse),
+        (status = 400, description = "URL is not http(s)", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn register_webhook(
+    Extension(state): Extension<AppState>,
+    Json(req): Json<RegisterWebhookRequest>,
+) -> Result<Json<RegisterWebhookResponse>, (StatusCode, String)> {
+    if !(req.url.starts_with("http://") || req.url.starts_with("https://")) {
+        return Err((StatusCode::BAD_REQUEST, "webhook url must be http(s)".into()));
+    }
+
+    let webhook = state
+        .webhooks
+        .register(req.url, req.secret)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    Ok(Json(RegisterWebhookResponse { id: webhook.id }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/webhooks/{id}",
+    tag = "admin",
+    params(("id" = u64, Path, description = "Webhook id")),
+    responses(
+        (status = 204, description = "Webhook removed"),
+        (status = 404, description = "No such webhook", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn delete_webhook(
+    Extension(state): Extension<AppState>,
+    Path(id): Path<u64>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let removed = state
+        .webhooks
+        .unregister(id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if removed {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "unknown webhook".into()))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/webhooks/dead-letters",
+    tag = "admin",
+    responses((status = 200, description = "Deliveries that exhausted their retries", body = Vec<Delivery>)),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn webhook_dead_letters(
+    Extension(state): Extension<AppState>,
+) -> Json<Vec<Delivery>> {
+    Json(state.webhooks.dead_letters())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/keys",
+    tag = "admin",
+    request_body = CreateApiKeyRequest,
+    responses((status = 200, description = "Key created; the token is shown only once", body = CreateApiKeyResponse)),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn create_api_key(
+    Extension(keys): Extension<KeyStore>,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<Json<CreateApiKeyResponse>, (StatusCode, String)> {
+    let (record, token) = keys
+        .create(req.role, req.label)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    tracing::info!(key_id = %record.id, role = ?record.role, "api key created");
+    Ok(Json(CreateApiKeyResponse {
+        id: record.id,
+        role: record.role,
+        token,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/keys",
+    tag = "admin",
+    responses((status = 200, description = "Every key, without secrets", body = Vec<ApiKeyInfo>)),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn list_api_keys(
+    Extension(keys): Extension<KeyStore>,
+) -> Json<Vec<ApiKeyInfo>> {
+    Json(
+        keys.list()
+            .into_iter()
+            .map(|k| ApiKeyInfo {
+                id: k.id,
+                role: k.role,
+                label: k.label,
+                created_at: k.created_at,
+                revoked: k.revoked,
+            })
+            .collect(),
+    )
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/keys/{id}",
+    tag = "admin",
+    params(("id" = String, Path, description = "Key id")),
+    responses(
+        (status = 204, description = "Key revoked"),
+        (status = 404, description = "No such key", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn revoke_api_key(
+    Extension(keys): Extension<KeyStore>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let revoked = keys
+        .revoke(&id)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    if revoked {
+        tracing::info!(key_id = %id, "api key revoked");
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err((StatusCode::NOT_FOUND, "unknown api key".into()))
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/admin/provers",
+    tag = "admin",
+    request_body = RegisterProverRequest,
+    responses(
+        (status = 201, description = "Prover allowlisted"),
+        (status = 200, description = "Prover was already allowlisted"),
+        (status = 400, description = "Key is not 32 hex bytes", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn register_prover(
+    Extension(state): Extension<AppState>,
+    Json(req): Json<RegisterProverRequest>,
+) -> Result<StatusCode, ApiError> {
+    let key = decode_hex_array(&req.public_key).map_err(map_error)?;
+
+    let added = {
+        let mut engine = state.engine.lock().unwrap();
+        let added = engine.register_prover(key);
+        if added {
+            state.save_state(&engine);
+        }
+        added
+    };
+    if !added {
+        return Ok(StatusCode::OK);
+    }
+    state.flush_state().await.map_err(persist_failed)?;
+    tracing::info!(public_key = %req.public_key, "prover added to allowlist");
+    Ok(StatusCode::CREATED)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/admin/provers/{public_key}",
+    tag = "admin",
+    params(("public_key" = String, Path, description = "Hex ed25519 public key")),
+    responses(
+        (status = 204, description = "Prover removed"),
+        (status = 404, description = "Prover not allowlisted", body = String),
+        (status = 409, description = "Last allowlisted prover; removing it would open submission", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn remove_prover(
+    Extension(state): Extension<AppState>,
+    Path(public_key): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let key = decode_hex_array(&public_key).map_err(map_error)?;
+
+    let removal = {
+        let mut engine = state.engine.lock().unwrap();
+        let removal = engine.remove_prover(&key);
+        if removal == ProverRemoval::Removed {
+            state.save_state(&engine);
+        }
+        removal
+    };
+    match removal {
+        ProverRemoval::Removed => {}
+        ProverRemoval::NotListed => {
+            return Err((StatusCode::NOT_FOUND, "unknown prover".to_string()).into())
+        }
+        ProverRemoval::LastProver => {
+            return Err((
+                StatusCode::CONFLICT,
+                "cannot remove the last prover; an empty allowlist admits any prover".to_string(),
+            )
+                .into())
+        }
+    }
+    state.flush_state().await.map_err(persist_failed)?;
+    tracing::info!(public_key = %public_key, "prover removed from allowlist");
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/admin/provers",
+    tag = "admin",
+    responses((status = 200, description = "Allowlisted provers; empty allows any", body = ProverListResponse)),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn list_provers(
+    Extension(state): Extension<AppState>,
+) -> Json<ProverListResponse> {
+    let engine = state.engine.lock().unwrap();
+    Json(ProverListResponse {
+        provers: engine.state().provers.iter().map(hex::encode).collect(),
+    })
+}
+
+/// Error answer of a route that can reject with a protocol error: a status
+/// and plain-text message, with the SPEC §9 code of a rejection in the
+/// [`ERROR_CODE_HEADER`] so clients need not parse the message.
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    message: String,
+    code: Option<&'static str>,
+}
+
+impl ApiError {
+    /// Append `: detail` to the message.
+    fn with_detail(mut self, detail: impl std::fmt::Display) -> Self {
+        self.message = format!("{}: {}", self.message, detail);
+        self
+    }
+}
+
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        Self {
+            status,
+            message,
+            code: None,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let mut response = (self.status, self.message).into_response();
+        if let Some(code) = self.code {
+            response
+                .headers_mut()
+                .insert(ERROR_CODE_HEADER, HeaderValue::from_static(code));
+        }
+        response
+    }
+}
+
+/// [`map_error`] for protocol submissions, counting the rejection.
+fn reject(err: ProtocolError) -> ApiError {
+    metrics().record_rejection(&err);
+    map_error(err)
+}
+
+fn map_error(err: ProtocolError) -> ApiError {
+    use ProtocolError::*;
+
+    let status = match err {
+        InvalidFormat => StatusCode::BAD_REQUEST,
+        InvalidNonce => StatusCode::CONFLICT,
+        StateMismatch => StatusCode::CONFLICT,
+        PolicyViolation => StatusCode::UNPROCESSABLE_ENTITY,
+        InvalidProof => StatusCode::BAD_REQUEST,
+        CommitmentMismatch => StatusCode::BAD_REQUEST,
+        UnauthorizedProver => StatusCode::FORBIDDEN,
+    };
+    ApiError {
+        status,
+        message: err.to_string(),
+        code: Some(err.code()),
+    }
+}
+
+/// Body of a JSON route, with malformed or oversized bodies turned into
+/// plain-text errors like every other rejection.
+fn json_body<T>(payload: Result<Json<T>, JsonRejection>) -> Result<T, ApiError> {
+    match payload {
+        Ok(Json(body)) => Ok(body),
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            Err((StatusCode::PAYLOAD_TOO_LARGE, rejection.body_text()).into())
+        }
+        Err(rejection) => Err(map_error(ProtocolError::InvalidFormat).with_detail(rejection.body_text())),
+    }
+}
+
+fn persist_failed(_: std::io::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, "failed to persist state".to_string())
+}
+
+fn map_prover_error(err: ZkVmProverError) -> (StatusCode, String) {
+    match err {
+        ZkVmProverError::PolicyViolation => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "policy violation".to_string(),
+        ),
+
+        ZkVmProverError::ExecutionFailed => (
+            StatusCode::BAD_REQUEST,
+            "zkvm execution failed".to_string(),
+        ),
+
+    }
+}
+
+
+#[cfg(feature = "zk-vm")]
+#[utoipa::path(
+    post,
+    path = "/v1/prove",
+    tag = "protocol",
+    request_body = ProveRequest,
+    responses(
+        (status = 202, description = "Proving job queued; poll `Location` for the result", body = Job),
+        (status = 403, description = "Proving disabled on this node", body = String),
+        (status = 429, description = "Rate limit exceeded", body = String),
+        (status = 503, description = "Proving queue full", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn prove(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Json(req): Json<ProveRequest>,
+) -> Result<(StatusCode, [(HeaderName, String); 1], Json<Job>), ApiError> {
+
+    // DEV / DEMO SAFETY
+    if std::env::var("ZKCG_ENABLE_PROVER").is_err() {
+        return Err((StatusCode::FORBIDDEN, "prover disabled".to_string()).into());
+    }
+
+    let job = state.jobs.submit(&identity.key_id, req).map_err(map_job_error)?;
+
+    // never log the secret witness
+    tracing::info!(client = %identity.key_id, job = %job.id, threshold = job.threshold, "proving job queued");
+
+    let location = format!("/v1/jobs/{}", job.id);
+    Ok((StatusCode::ACCEPTED, [(LOCATION, location)], Json(job)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/submissions/{id}",
+    tag = "protocol",
+    params(("id" = String, Path, description = "Hex submission id (SPEC §5.3)")),
+    responses(
+        (status = 200, description = "Accepted, still queued, or dropped from the mempool", body = SubmissionStatusResponse),
+        (status = 400, description = "Malformed submission id", body = String),
+        (status = 404, description = "Unknown submission, or its record has been pruned", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn get_submission(
+    Extension(state): Extension<AppState>,
+    Path(id): Path<String>,
+) -> Result<Json<SubmissionStatusResponse>, (StatusCode, String)> {
+    let id: Hash = hex::decode(&id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or((StatusCode::BAD_REQUEST, "invalid submission id".to_string()))?;
+
+    let (status, receipt, reason) = if let Some(receipt) = state.receipts.get(&id) {
+        (SubmissionStatus::Accepted.as_str(), Some(receipt), None)
+    } else if state.receipts.is_queued(&id) {
+        (SubmissionStatus::Queued.as_str(), None, None)
+    } else if let Some(reason) = state.receipts.drop_reason(&id) {
+        ("dropped", None, Some(reason.to_string()))
+    } else {
+        return Err((StatusCode::NOT_FOUND, "unknown submission".to_string()));
+    };
+
+    Ok(Json(SubmissionStatusResponse {
+        status: status.to_string(),
+        receipt,
+        reason,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/v1/jobs/{id}",
+    tag = "protocol",
+    params(("id" = String, Path, description = "Job id from `/v1/prove`")),
+    responses(
+        (status = 200, description = "Job status; `result` holds the submission once it succeeded", body = Job),
+        (status = 404, description = "No such job for this key", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn get_job(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, ApiError> {
+    state
+        .jobs
+        .get(&identity.key_id, &id)
+        .map(Json)
+        .ok_or_else(|| map_job_error(JobError::NotFound))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/v1/jobs/{id}",
+    tag = "protocol",
+    params(("id" = String, Path, description = "Job id from `/v1/prove`")),
+    responses(
+        (status = 200, description = "Job cancelled", body = Job),
+        (status = 404, description = "No such job for this key", body = String),
+        (status = 409, description = "Job already finished", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn cancel_job(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, ApiError> {
+    let job = state.jobs.cancel(&identity.key_id, &id).map_err(map_job_error)?;
+    tracing::info!(client = %identity.key_id, job = %job.id, "proving job cancelled");
+    Ok(Json(job))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/workers",
+    tag = "workers",
+    request_body = RegisterWorkerRequest,
+    responses(
+        (status = 201, description = "Worker registered", body = RegisterWorkerResponse),
+        (status = 422, description = "Worker cannot prove for this node's backend", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn register_worker(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    payload: Result<Json<RegisterWorkerRequest>, JsonRejection>,
+) -> Result<(StatusCode, Json<RegisterWorkerResponse>), ApiError> {
+    let req = json_body(payload)?;
+    let backend = state.engine.lock().unwrap().backend().name().to_string();
+    if !req.backends.contains(&backend) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("this node verifies {} proofs", backend),
+        )
+            .into());
+    }
+
+    let worker_id = state.jobs.register_worker(&identity.key_id, &req.name);
+    tracing::info!(client = %identity.key_id, worker = %worker_id, name = %req.name, "prover worker registered");
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RegisterWorkerResponse {
+            worker_id,
+            backend,
+            heartbeat_interval_secs: (state.jobs.worker_timeout().as_secs() / 3).max(1),
+        }),
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/heartbeat",
+    tag = "workers",
+    params(("id" = String, Path, description = "Worker id from registration")),
+    responses(
+        (status = 204, description = "Worker and its jobs kept alive"),
+        (status = 404, description = "Unknown or timed-out worker, or one registered by another key; register again", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn worker_heartbeat(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Path(worker_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    state.jobs.heartbeat(&identity.key_id, &worker_id).map_err(map_job_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/claim",
+    tag = "workers",
+    params(("id" = String, Path, description = "Worker id from registration")),
+    responses(
+        (status = 200, description = "Oldest queued job, to be proved against the given state", body = WorkerJob),
+        (status = 204, description = "Nothing queued"),
+        (status = 404, description = "Unknown or timed-out worker, or one registered by another key; register again", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn claim_job(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Path(worker_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let (backend, old_state_root, nonce) = {
+        let engine = state.engine.lock().unwrap();
+        (engine.backend().name().to_string(), engine.state().state_root, engine.state().nonce + 1)
+    };
+
+    let claimed = state
+        .jobs
+        .claim_for(&identity.key_id, &worker_id, old_state_root, nonce)
+        .map_err(map_job_error)?;
+    let Some((job_id, req)) = claimed else {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    };
+
+    Ok(Json(WorkerJob {
+        job_id,
+        backend,
+        secret_value: req.secret_value,
+        threshold: req.threshold,
+        old_state_root,
+        nonce,
+    })
+    .into_response())
+}
+
+#[utoipa::path(
+    post,
+    path = "/v1/workers/{id}/jobs/{job_id}",
+    tag = "workers",
+    request_body = CompleteJobRequest,
+    params(
+        ("id" = String, Path, description = "Worker id from registration"),
+        ("job_id" = String, Path, description = "Job id from the claim"),
+    ),
+    responses(
+        (status = 204, description = "Outcome recorded"),
+        (status = 400, description = "Malformed report, or a proof that does not verify (the job fails)", body = String),
+        (status = 404, description = "Unknown or timed-out worker, or one registered by another key; register again", body = String),
+        (status = 409, description = "Job not assigned to this worker, e.g. reassigned after a timeout", body = String),
+    ),
+    security(("api_key" = []), ("bearer" = [])),
+)]
+pub async fn complete_job(
+    Extension(state): Extension<AppState>,
+    Extension(identity): Extension<Identity>,
+    Path((worker_id, job_id)): Path<(String, String)>,
+    payload: Result<Json<CompleteJobRequest>, JsonRejection>,
+) -> Result<StatusCode, ApiError> {
+    let req = json_body(payload)?;
+    let assignment = state
+        .jobs
+        .assignment(&identity.key_id, &worker_id, &job_id)
+        .map_err(map_job_error)?;
+
+    let result = match (req.proof, req.error) {
+        (Some(proof), None) => {
+            let backend = state.engine.lock().unwrap().backend();
+            let proof_bytes = decode_proof(&proof, backend.max_proof_size()).map_err(map_error)?;
+            let inputs = PublicInputs {
+                threshold: assignment.threshold,
+                old_state_root: assignment.old_state_root,
+                nonce: assignment.nonce,
+            };
+
+            // workers are not trusted to return a proof that verifies
+            let span = tracing::Span::current();
+            let (proof_bytes, verified) = tokio::task::spawn_blocking(move || {
+                span.in_scope(|| {
+                    let verified = backend.verify(&proof_bytes, &inputs);
+                    (proof_bytes, verified)
+                })
+            })
+            .await
+            .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "verification task failed".to_string()))?;
+
+            if let Err(err) = verified {
+                tracing::warn!(job = %job_id, worker = %worker_id, "worker returned a proof that does not verify");
+                state
+                    .jobs
+                    .complete_for(
+                        &identity.key_id,
+                        &worker_id,
+                        &job_id,
+                        Err("worker returned an invalid proof".into()),
+                    )
+                    .map_err(map_job_error)?;
+                return Err(map_error(err));
+            }
+            Ok(proved_submission(
+                assignment.threshold,
+                assignment.old_state_root,
+                assignment.nonce,
+                &proof_bytes,
+            ))
+        }
+        (None, Some(error)) => Err(error),
+        _ => {
+            return Err(map_error(ProtocolError::InvalidFormat)
+                .with_detail("expected exactly one of `proof` and `error`"));
+        }
+    };
+
+    state
+        .jobs
+        .complete_for(&identity.key_id, &worker_id, &job_id, result)
+        .map_err(map_job_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// An unknown worker is marked with [`UNKNOWN_WORKER_CODE`] so workers can
+/// tell it from an unknown job without parsing the message.
+fn map_job_error(err: JobError) -> ApiError {
+    let (status, message) = match err {
+        JobError::QueueFull => (StatusCode::SERVICE_UNAVAILABLE, "proving queue full"),
+        JobError::NotFound => (StatusCode::NOT_FOUND, "unknown job"),
+        JobError::Finished => (StatusCode::CONFLICT, "job already finished"),
+        JobError::UnknownWorker => {
+            return ApiError {
+                status: StatusCode::NOT_FOUND,
+                message: "unknown worker".into(),
+                code: Some(UNKNOWN_WORKER_CODE),
+            }
+        }
+        JobError::NotAssigned => (StatusCode::CONFLICT, "job not assigned to this worker"),
+    };
+    (status, message.to_string()).into()
+}
+
+/// Prove `req` on top of the engine's current state and package the result
+/// as a submission for the next nonce.
+///
+/// Run by the job queue when the job starts. If another transition is
+/// accepted first, submitting the result fails with a stale nonce and the
+/// proof has to be requested again.
+#[cfg(feature = "zk-vm")]
+pub async fn prove_next(
+    state: &AppState,
+    req: ProveRequest,
+) -> Result<SubmitProofRequest, (StatusCode, String)> {
+    let (old_state_root, nonce) = {
+        let engine = state.engine.lock().unwrap();
+        (engine.state().state_root, engine.state().nonce + 1)
+    };
+    let ProveRequest { secret_value, threshold } = req;
+
+    // proving takes far longer than verifying; keep it off the async workers
+    let span = tracing::Span::current();
+    let proof = tokio::task::spawn_blocking(move || {
+        span.in_scope(|| zkvm_prove(secret_value, threshold, old_state_root, nonce))
+    })
+    .await
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "proving task failed".to_string()))?
+    .map_err(map_prover_error)?;
+
+    tracing::info!(nonce, proof_len = proof.len(), "proof generated");
+    Ok(proved_submission(threshold, old_state_root, nonce, &proof))
+}
+
+/// Ready-to-submit request for `proof`, committing to the state it produces.
+fn proved_submission(threshold: u64, old_state_root: Hash, nonce: u64, proof: &[u8]) -> SubmitProofRequest {
+    let commitment = next_state_commitment(&old_state_root, nonce, threshold, proof);
+    SubmitProofRequest {
+        proof: encode_proof(proof),
+        public_inputs: PublicInputsDto {
+            threshold,
+            old_state_root,
+            nonce,
+        },
+        new_state_commitment: commitment.0,
+        signature: None,
+    }
+}
+
+#[cfg(feature = "zk-vm")]
+pub fn demo_prove(
+    score: u64,
+    threshold: u64,
+) -> Result<Vec<u8>, ProtocolError> {
+    // demo safety limits
+    if score > 100 || threshold > 100 {
+        return Err(ProtocolError::PolicyViolation);
+    }
+
+    // Demo = always prove against GENESIS
+    let old_state_root = [0u8; 32];
+    let nonce = 1;
+
+    let proof = zkvm_prove(
+        score,
+        threshold,
+        old_state_root,
+        nonce,
+    ).map_err(|_| ProtocolError::InvalidProof)?;
+
+    Ok(proof)
+}
+
+
+/// Demo-only proof verification (stateless)
+
+#[cfg(feature = "zk-vm")]
+pub fn demo_verify(
+    proof_b64: &str,
+    threshold: u64,
+) -> Result<bool, ProtocolError> {
+    let backend = ZkVmBackend;
+    let proof_bytes = decode_proof(proof_b64, backend.max_proof_size())?;
+
+    let public_inputs = PublicInputs {
+        threshold,
+        old_state_root: [0u8; 32],
+        nonce: 1,
+    };
+
+     match backend.verify(&proof_bytes, &public_inputs) {
+        Ok(()) => Ok(true),
+        Err(_) => Ok(false),
+    }
+}
+
+
+#[utoipa::path(
+    post,
+    path = "/demo/prove",
+    tag = "demo",
+    request_body = DemoProveRequest,
+    responses(
+        (status = 200, description = "Stateless demo proof, returned once proving finishes", body = DemoProveResponse),
+        (status = 413, description = "Body over `body_limits.demo_prove`", body = String),
+        (status = 422, description = "Score or threshold above 100", body = String),
+        (status = 429, description = "Rate limit exceeded", body = String),
+    ),
+)]
+/// Proves synchronously, unlike `/v1/prove`: the demo is unauthenticated
+/// and stateless, so there is no client to own a job and nothing to poll it
+/// with, and its callers expect the proof in the response. The proof runs
+/// on the blocking pool, and the route's rate limit bounds how many run.
+pub async fn demo_prove_handler(
+    payload: Result<Json<DemoProveRequest>, JsonRejection>,
+) -> Result<Json<DemoProveResponse>, ApiError> {
+    let req = json_body(payload)?;
+
+    tracing::info!(threshold = req.threshold, "demo prove request");
+
+    let span = tracing::Span::current();
+    let proof = tokio::task::spawn_blocking(move || span.in_scope(|| demo_prove(req.score, req.threshold)))
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "proving task failed".to_string()))?
+        .map_err(map_error)?;
+
+    Ok(Json(DemoProveResponse {
+        proof: encode_proof(&proof),
+        proof_size_bytes: proof.len(),
+        note: "Demo-only stateless proof",
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/demo/verify",
+    tag = "demo",
+    request_body = DemoVerifyRequest,
+    responses(
+        (status = 200, description = "Whether the proof verifies", body = DemoVerifyResponse),
+        (status = 400, description = "Proof is not valid base64 or over the backend's size cap", body = String),
+        (status = 413, description = "Body over `body_limits.demo_verify`", body = String),
+        (status = 429, description = "Rate limit exceeded", body = String),
+    ),
+)]
+pub async fn demo_verify_handler(
+    payload: Result<Json<DemoVerifyRequest>, JsonRejection>,
+) -> Result<Json<DemoVerifyResponse>, ApiError> {
+    let req = json_body(payload)?;
+
+    tracing::info!(threshold = req.threshold, "demo verify request");
+
+    let span = tracing::Span::current();
+    let verified = tokio::task::spawn_blocking(move || span.in_scope(|| demo_verify(&req.proof, req.threshold)))
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "verification task failed".to_string()))?
+        .map_err(map_error)?;
+
+    Ok(Json(DemoVerifyResponse {
+        verified,
+    }))
+}
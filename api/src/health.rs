@@ -0,0 +1,100 @@
+use axum::{http::StatusCode, Extension, Json};
+use std::sync::{Arc, RwLock};
+use zkcg_verifier::selftest::SelfTestReport;
+
+use crate::models::{BackendSelfTest, HealthResponse, ReadinessResponse};
+
+/// Self-test results gating `/readyz`. Not ready until results are recorded,
+/// and a backend that checked nothing is not ready either.
+#[derive(Clone, Default)]
+pub struct Readiness {
+    reports: Arc<RwLock<Option<Vec<SelfTestReport>>>>,
+    allow_skipped: bool,
+}
+
+impl Readiness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also ready when a backend checked nothing, for nodes configured
+    /// with `backend.require_self_test = false`.
+    pub fn allowing_skipped() -> Self {
+        Self {
+            allow_skipped: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn record(&self, reports: Vec<SelfTestReport>) {
+        *self.reports.write().unwrap() = Some(reports);
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.reports
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|r| r.iter().all(|report| self.accepts(report)))
+    }
+
+    fn accepts(&self, report: &SelfTestReport) -> bool {
+        report.passed() || (self.allow_skipped && report.skipped())
+    }
+
+    fn snapshot(&self) -> ReadinessResponse {
+        let reports = self.reports.read().unwrap();
+        let backends: Vec<BackendSelfTest> = reports
+            .iter()
+            .flatten()
+            .map(|r| BackendSelfTest {
+                backend: r.backend.to_string(),
+                checked: r.checked,
+                passed: r.passed(),
+                skipped: r.skipped(),
+                failures: r.failures.clone(),
+            })
+            .collect();
+
+        ReadinessResponse {
+            ready: reports.as_ref().is_some_and(|r| r.iter().all(|report| self.accepts(report))),
+            backends,
+        }
+    }
+}
+
+/// Liveness: the process is up and serving requests.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "ops",
+    responses((status = 200, description = "Process is serving", body = HealthResponse)),
+)]
+pub async fn healthz() -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+    })
+}
+
+/// Readiness: every configured backend passed its startup self-test, which
+/// needs a known-good proof made offline.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "ops",
+    responses(
+        (status = 200, description = "Backend self-test passed", body = ReadinessResponse),
+        (status = 503, description = "Self-test failed or not yet run", body = ReadinessResponse),
+    ),
+)]
+pub async fn readyz(
+    Extension(readiness): Extension<Readiness>,
+) -> (StatusCode, Json<ReadinessResponse>) {
+    let body = readiness.snapshot();
+    let status = if body.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(body))
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use zkcg_common::wire::LoggedTransition;
+use zkcg_verifier::mempool::AppliedTransition;
+use zkcg_verifier::pipeline::Submission;
+
+use crate::metrics::metrics;
+
+enum Message {
+    /// Line for the transition to `nonce`; `None` when its submission is unknown
+    Append { nonce: u64, line: Option<Vec<u8>> },
+    /// Answered once everything before it is written and synced
+    Sync(mpsc::Sender<()>),
+}
+
+struct Inner {
+    sender: mpsc::Sender<Message>,
+    /// Queued submissions by (client, nonce), logged once the mempool
+    /// releases them. Not persisted, like the mempool itself.
+    pending: Mutex<HashMap<(String, u64), Submission>>,
+}
+
+/// Append-only JSONL log of every accepted transition, in the order they
+/// were applied, so history can be re-verified later with `zkcg reverify`.
+///
+/// Lines are written and synced to disk by a dedicated thread, never on the
+/// async runtime. Each transition advances the state nonce by one, so the
+/// nonce doubles as the log's sequence number: a transition that cannot be
+/// written, or reaches the writer out of order, is logged as an error and
+/// counted in `zkcg_transition_log_gaps_total`.
+///
+/// Opened without a path the log is disabled and every call is a no-op.
+#[derive(Clone)]
+pub struct TransitionLog {
+    inner: Option<Arc<Inner>>,
+}
+
+impl TransitionLog {
+    /// Open `path` for appending, creating it if needed, and start its writer.
+    /// `nonce` is the state nonce the log continues from. `None` disables the
+    /// log.
+    pub fn open(path: Option<PathBuf>, nonce: u64) -> io::Result<Self> {
+        let inner = match path {
+            Some(path) => {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                let (sender, receiver) = mpsc::channel();
+                thread::Builder::new()
+                    .name("transition-log".into())
+                    .spawn(move || write_loop(file, nonce, receiver))?;
+                Some(Arc::new(Inner {
+                    sender,
+                    pending: Mutex::new(HashMap::new()),
+                }))
+            }
+            None => None,
+        };
+        Ok(Self { inner })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Keep a queued submission until [`TransitionLog::record`] sees it applied.
+    pub fn hold(&self, client: &str, submission: Submission) {
+        if let Some(inner) = &self.inner {
+            let nonce = submission.public_inputs.nonce;
+            inner
+                .pending
+                .lock()
+                .unwrap()
+                .insert((client.to_string(), nonce), submission);
+        }
+    }
+
+    /// Queue an applied transition for appending.
+    ///
+    /// Call it with the engine lock still held, so transitions reach the
+    /// writer in the order they were applied. `own` is the submission being
+    /// processed; transitions released from the mempool are matched to their
+    /// held submission instead. A transition with neither, e.g. queued before
+    /// a restart, is a gap.
+    pub fn record(&self, transition: &AppliedTransition, own: Option<&Submission>, backend: &str) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+
+        let after = &transition.after;
+        let held = {
+            let mut pending = inner.pending.lock().unwrap();
+            let held = pending.remove(&(transition.client.clone(), after.nonce));
+            // anything at or below the new nonce can no longer be applied
+            pending.retain(|(_, nonce), _| *nonce > after.nonce);
+            held
+        };
+
+        let line = own.or(held.as_ref()).map(|submission| {
+            let logged = LoggedTransition {
+                submission_id: hex::encode(submission.id()),
+                backend: backend.to_string(),
+                channel: after.channel,
+                epoch: after.epoch,
+                submission: submission.to_wire(),
+                accepted_at: now_ms(),
+            };
+            let mut line = serde_json::to_vec(&logged).expect("logged transitions serialize");
+            line.push(b'\n');
+            line
+        });
+        let message = Message::Append {
+            nonce: after.nonce,
+            line,
+        };
+        if inner.sender.send(message).is_err() {
+            tracing::error!(nonce = after.nonce, "transition log writer has stopped");
+            metrics().record_log_gap(1);
+        }
+    }
+
+    /// Block until everything recorded so far is on disk.
+    pub fn sync(&self) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        let (done, wait) = mpsc::channel();
+        if inner.sender.send(Message::Sync(done)).is_ok() {
+            let _ = wait.recv();
+        }
+    }
+}
+
+/// Append lines as they arrive, syncing once per burst.
+fn write_loop(mut file: File, mut last: u64, receiver: mpsc::Receiver<Message>) {
+    while let Ok(first) = receiver.recv() {
+        let mut waiting = Vec::new();
+        let mut written = false;
+
+        for message in std::iter::once(first).chain(receiver.try_iter()) {
+            match message {
+                Message::Append { nonce, line } => {
+                    if nonce != last + 1 {
+                        tracing::error!(expected = last + 1, nonce, "transition log is out of sequence");
+                        metrics().record_log_gap(nonce.saturating_sub(last + 1).max(1));
+                    }
+                    last = last.max(nonce);
+
+                    let Some(line) = line else {
+                        tracing::error!(nonce, "applied transition missing from the transition log");
+                        metrics().record_log_gap(1);
+                        continue;
+                    };
+                    match file.write_all(&line) {
+                        Ok(()) => written = true,
+                        Err(e) => {
+                            tracing::error!(nonce, error = %e, "failed to append to transition log");
+                            metrics().record_log_gap(1);
+                        }
+                    }
+                }
+                Message::Sync(done) => waiting.push(done),
+            }
+        }
+
+        let synced = if written { file.sync_data() } else { Ok(()) };
+        if let Err(e) = synced {
+            tracing::error!(error = %e, "failed to sync transition log");
+        }
+        for done in waiting {
+            let _ = done.send(());
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,615 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fs,
+    future::Future,
+    io,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::Notify;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use zkcg_common::types::Hash;
+use zkcg_verifier::storage::write_json;
+
+use crate::models::{ProveRequest, SubmitProofRequest};
+
+/// Bounds on the proving queue.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JobLimits {
+    /// Proofs this node generates at the same time
+    pub max_concurrent: usize,
+    /// Jobs waiting to start; `/v1/prove` answers `503` beyond this
+    pub max_queued: usize,
+    /// Finished jobs kept for polling, oldest dropped first
+    pub retain: usize,
+    /// Prove on this node; with `false` only remote workers do
+    pub local: bool,
+    /// Silence after which a worker is dropped and its jobs queued again
+    pub worker_timeout_secs: u64,
+}
+
+impl Default for JobLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrent: 1,
+            max_queued: 64,
+            retain: 1024,
+            local: true,
+            worker_timeout_secs: 30,
+        }
+    }
+}
+
+impl JobLimits {
+    pub fn worker_timeout(&self) -> Duration {
+        Duration::from_secs(self.worker_timeout_secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// A proving job, as returned to the client that created it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub threshold: u64,
+    /// Unix time (ms)
+    pub created_at: u64,
+    /// Unix time (ms) the current proving attempt started
+    pub started_at: Option<u64>,
+    /// Unix time (ms)
+    pub finished_at: Option<u64>,
+    /// Ready-to-submit request, once succeeded
+    pub result: Option<SubmitProofRequest>,
+    /// Why proving failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JobRecord {
+    #[serde(flatten)]
+    job: Job,
+    /// API key the job belongs to
+    client: String,
+    /// Private witness, never written to disk; dropped as soon as the job finishes
+    #[serde(skip)]
+    secret_value: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JobFile {
+    /// Oldest first
+    jobs: Vec<JobRecord>,
+}
+
+#[derive(Default)]
+struct Inner {
+    jobs: HashMap<String, JobRecord>,
+    /// Creation order, which is also the order queued jobs start in
+    order: VecDeque<String>,
+    /// Jobs with a proof being generated here, including ones cancelled meanwhile
+    proving: HashSet<String>,
+    /// Registered remote workers, by id. Not persisted: workers register
+    /// again when the node forgets them.
+    workers: HashMap<String, Worker>,
+    /// Jobs handed to remote workers, by job id
+    assignments: HashMap<String, Assignment>,
+    /// Bumped on every change to `jobs`
+    generation: u64,
+}
+
+struct Worker {
+    /// API key that registered the worker; only it may act as the worker
+    owner: String,
+    name: String,
+    last_seen: Instant,
+}
+
+/// A job handed to a remote worker, with the state it is proved against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assignment {
+    pub worker_id: String,
+    pub threshold: u64,
+    pub old_state_root: Hash,
+    pub nonce: u64,
+}
+
+/// Error of jobs that were unfinished when the node stopped.
+pub const WITNESS_LOST: &str = "node restarted before the job finished; submit it again";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum JobError {
+    QueueFull,
+    NotFound,
+    /// Cancelling a job that already finished.
+    Finished,
+    /// The worker never registered, was registered by another key, or
+    /// timed out and was dropped.
+    UnknownWorker,
+    /// The job is not (or no longer) assigned to the reporting worker.
+    NotAssigned,
+}
+
+/// Proving jobs behind `/v1/prove`, persisted as JSON so clients can still
+/// poll them after a restart.
+///
+/// Jobs are proved on this node by [`JobQueue::run`] or claimed by remote
+/// workers; either way a proof is generated against the state current when
+/// the job starts. Jobs of a worker that stops heartbeating are queued
+/// again. Witnesses are kept in memory only, so jobs left unfinished when
+/// the node stopped fail on open and have to be submitted again.
+///
+/// Changes are made in memory and written by [`JobQueue::persist_loop`],
+/// off the request path, or by a blocking [`JobQueue::flush`].
+#[derive(Clone)]
+pub struct JobQueue {
+    inner: Arc<Mutex<Inner>>,
+    path: Option<PathBuf>,
+    limits: JobLimits,
+    wake: Arc<Notify>,
+    dirty: Arc<Notify>,
+    /// Generation last written to `path`; held for the whole write so
+    /// flushes never overtake each other
+    written: Arc<Mutex<u64>>,
+}
+
+impl JobQueue {
+    /// Open (or create) a queue backed by `path`. `None` keeps it in memory.
+    pub fn open(path: Option<PathBuf>, limits: JobLimits) -> io::Result<Self> {
+        let existed = path.as_ref().is_some_and(|p| p.exists());
+        let file: JobFile = match &path {
+            Some(p) if p.exists() => {
+                let raw = fs::read(p)?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            _ => JobFile::default(),
+        };
+
+        let mut inner = Inner::default();
+        for mut record in file.jobs {
+            if !record.job.status.is_finished() {
+                tracing::warn!(job = %record.job.id, "failing job whose witness was lost in a restart");
+                record.job.error = Some(WITNESS_LOST.into());
+                record.finish(JobStatus::Failed);
+            }
+            inner.order.push_back(record.job.id.clone());
+            inner.jobs.insert(record.job.id.clone(), record);
+        }
+        // records the failures, and drops witnesses older nodes wrote
+        inner.generation = u64::from(existed);
+
+        let queue = Self {
+            inner: Arc::new(Mutex::new(inner)),
+            path,
+            limits,
+            wake: Arc::new(Notify::new()),
+            dirty: Arc::new(Notify::new()),
+            written: Arc::new(Mutex::new(0)),
+        };
+        queue.flush()?;
+        Ok(queue)
+    }
+
+    /// Queue `req` on behalf of `client`.
+    pub fn submit(&self, client: &str, req: ProveRequest) -> Result<Job, JobError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.count(JobStatus::Queued) >= self.limits.max_queued {
+            return Err(JobError::QueueFull);
+        }
+
+        let job = Job {
+            id: Uuid::new_v4().to_string(),
+            status: JobStatus::Queued,
+            threshold: req.threshold,
+            created_at: now_ms(),
+            started_at: None,
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        inner.order.push_back(job.id.clone());
+        inner.jobs.insert(
+            job.id.clone(),
+            JobRecord {
+                job: job.clone(),
+                client: client.to_string(),
+                secret_value: Some(req.secret_value),
+            },
+        );
+        self.save(&mut inner);
+        drop(inner);
+
+        self.wake.notify_one();
+        Ok(job)
+    }
+
+    /// `client`'s job `id`; other clients' jobs are not visible.
+    pub fn get(&self, client: &str, id: &str) -> Option<Job> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .jobs
+            .get(id)
+            .filter(|r| r.client == client)
+            .map(|r| r.job.clone())
+    }
+
+    /// Cancel `client`'s job `id`.
+    ///
+    /// A proof already being generated runs to completion but its result is
+    /// discarded, and it keeps its concurrency slot until then.
+    pub fn cancel(&self, client: &str, id: &str) -> Result<Job, JobError> {
+        let mut inner = self.inner.lock().unwrap();
+        let record = inner
+            .jobs
+            .get_mut(id)
+            .filter(|r| r.client == client)
+            .ok_or(JobError::NotFound)?;
+        if record.job.status.is_finished() {
+            return Err(JobError::Finished);
+        }
+
+        record.finish(JobStatus::Cancelled);
+        let job = record.job.clone();
+        inner.evict(self.limits.retain);
+        self.save(&mut inner);
+        Ok(job)
+    }
+
+    /// Start the oldest queued job if a local proving slot is free.
+    fn claim(&self) -> Option<(String, ProveRequest)> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.proving.len() >= self.limits.max_concurrent {
+            return None;
+        }
+
+        let (id, req) = inner.start_next()?;
+        inner.proving.insert(id.clone());
+        self.save(&mut inner);
+        Some((id, req))
+    }
+
+    /// Record the outcome of locally proved job `id` and free its slot.
+    fn complete(&self, id: &str, result: Result<SubmitProofRequest, String>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.proving.remove(id);
+        if inner.finish_running(id, result) {
+            inner.evict(self.limits.retain);
+            self.save(&mut inner);
+        }
+    }
+
+    /// Start queued jobs as slots free up, proving each with `prove`.
+    pub async fn run<P, Fut>(self, prove: P)
+    where
+        P: Fn(ProveRequest) -> Fut + Clone + Send + 'static,
+        Fut: Future<Output = Result<SubmitProofRequest, String>> + Send + 'static,
+    {
+        loop {
+            while let Some((id, req)) = self.claim() {
+                tracing::info!(job = %id, threshold = req.threshold, "proving job started");
+                let queue = self.clone();
+                let prove = prove.clone();
+                tokio::spawn(async move {
+                    let result = prove(req).await;
+                    queue.complete(&id, result);
+                    queue.wake.notify_one();
+                });
+            }
+            self.wake.notified().await;
+        }
+    }
+
+    pub fn worker_timeout(&self) -> Duration {
+        self.limits.worker_timeout()
+    }
+
+    /// Register a remote worker on behalf of `owner`; returns its id.
+    ///
+    /// Every later call for the worker must come from `owner`; to any other
+    /// key the worker does not exist.
+    pub fn register_worker(&self, owner: &str, name: &str) -> String {
+        let id = Uuid::new_v4().to_string();
+        self.inner.lock().unwrap().workers.insert(
+            id.clone(),
+            Worker {
+                owner: owner.to_string(),
+                name: name.to_string(),
+                last_seen: Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// Note that `worker_id` is still alive, keeping its jobs assigned to it.
+    pub fn heartbeat(&self, owner: &str, worker_id: &str) -> Result<(), JobError> {
+        self.inner.lock().unwrap().touch(owner, worker_id)
+    }
+
+    /// Hand the oldest queued job to `worker_id`, to be proved against
+    /// `old_state_root` and `nonce`. `None` when nothing is queued.
+    pub fn claim_for(
+        &self,
+        owner: &str,
+        worker_id: &str,
+        old_state_root: Hash,
+        nonce: u64,
+    ) -> Result<Option<(String, ProveRequest)>, JobError> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(owner, worker_id)?;
+
+        let Some((id, req)) = inner.start_next() else {
+            return Ok(None);
+        };
+        inner.assignments.insert(
+            id.clone(),
+            Assignment {
+                worker_id: worker_id.to_string(),
+                threshold: req.threshold,
+                old_state_root,
+                nonce,
+            },
+        );
+        self.save(&mut inner);
+
+        tracing::info!(job = %id, worker = %worker_id, nonce, "proving job assigned");
+        Ok(Some((id, req)))
+    }
+
+    /// What job `id` was assigned to `worker_id` with.
+    pub fn assignment(&self, owner: &str, worker_id: &str, id: &str) -> Result<Assignment, JobError> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(owner, worker_id)?;
+        inner
+            .assignments
+            .get(id)
+            .filter(|a| a.worker_id == worker_id)
+            .cloned()
+            .ok_or(JobError::NotAssigned)
+    }
+
+    /// Record the outcome `worker_id` reported for job `id`.
+    ///
+    /// A job cancelled in the meantime stays cancelled; a worker whose job
+    /// was handed to another after it timed out gets [`JobError::NotAssigned`].
+    pub fn complete_for(
+        &self,
+        owner: &str,
+        worker_id: &str,
+        id: &str,
+        result: Result<SubmitProofRequest, String>,
+    ) -> Result<(), JobError> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.touch(owner, worker_id)?;
+        if inner.assignments.get(id).is_none_or(|a| a.worker_id != worker_id) {
+            return Err(JobError::NotAssigned);
+        }
+
+        inner.assignments.remove(id);
+        if inner.finish_running(id, result) {
+            inner.evict(self.limits.retain);
+            self.save(&mut inner);
+        }
+        Ok(())
+    }
+
+    /// Drop workers silent for longer than the worker timeout as of `now`
+    /// and queue their unfinished jobs again.
+    pub fn expire_workers(&self, now: Instant) {
+        let timeout = self.limits.worker_timeout();
+        let mut inner = self.inner.lock().unwrap();
+
+        let expired: Vec<String> = inner
+            .workers
+            .iter()
+            .filter(|(_, w)| now.saturating_duration_since(w.last_seen) > timeout)
+            .map(|(id, _)| id.clone())
+            .collect();
+        if expired.is_empty() {
+            return;
+        }
+
+        let mut requeued = 0;
+        for worker_id in &expired {
+            if let Some(worker) = inner.workers.remove(worker_id) {
+                tracing::warn!(worker = %worker_id, name = %worker.name, "prover worker timed out");
+            }
+        }
+        let Inner { jobs, assignments, .. } = &mut *inner;
+        assignments.retain(|id, a| {
+            if !expired.contains(&a.worker_id) {
+                return true;
+            }
+            if let Some(record) = jobs.get_mut(id).filter(|r| r.job.status == JobStatus::Running) {
+                tracing::info!(job = %id, worker = %a.worker_id, "requeueing job of timed-out worker");
+                record.job.status = JobStatus::Queued;
+                record.job.started_at = None;
+                requeued += 1;
+            }
+            false
+        });
+
+        if requeued > 0 {
+            self.save(&mut inner);
+            drop(inner);
+            self.wake.notify_one();
+        }
+    }
+
+    /// Periodically expire silent workers.
+    pub async fn reap_workers(self) {
+        let period = (self.limits.worker_timeout() / 2).max(Duration::from_secs(1));
+        loop {
+            tokio::time::sleep(period).await;
+            self.expire_workers(Instant::now());
+        }
+    }
+
+    /// Mark the job table changed, for [`JobQueue::persist_loop`] to write.
+    fn save(&self, inner: &mut Inner) {
+        inner.generation += 1;
+        self.dirty.notify_one();
+    }
+
+    /// Write the job table whenever it changed, on the blocking pool. A
+    /// failed write is logged rather than returned: the table in memory is
+    /// still authoritative until a restart. Never returns.
+    pub async fn persist_loop(self) {
+        loop {
+            self.dirty.notified().await;
+            let this = self.clone();
+            let flushed = tokio::task::spawn_blocking(move || this.flush())
+                .await
+                .unwrap_or_else(|e| Err(io::Error::other(e)));
+            if let Err(e) = flushed {
+                tracing::warn!(error = %e, "failed to persist proving jobs");
+            }
+        }
+    }
+
+    /// Write the job table to disk, unless a concurrent flush already has.
+    /// Blocks; a no-op for in-memory queues.
+    pub fn flush(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut written = self.written.lock().unwrap();
+        let (file, generation) = {
+            let inner = self.inner.lock().unwrap();
+            if *written >= inner.generation {
+                return Ok(());
+            }
+            let file = JobFile {
+                jobs: inner
+                    .order
+                    .iter()
+                    .filter_map(|id| inner.jobs.get(id).cloned())
+                    .collect(),
+            };
+            (file, inner.generation)
+        };
+
+        write_json(path, &file)?;
+        *written = generation;
+        Ok(())
+    }
+}
+
+impl JobRecord {
+    fn finish(&mut self, status: JobStatus) {
+        self.job.status = status;
+        self.job.finished_at = Some(now_ms());
+        self.secret_value = None;
+    }
+}
+
+impl Inner {
+    /// Mark the oldest queued job running and return what to prove.
+    fn start_next(&mut self) -> Option<(String, ProveRequest)> {
+        let (id, secret_value) = loop {
+            let id = self
+                .order
+                .iter()
+                .find(|id| self.jobs.get(*id).is_some_and(|r| r.job.status == JobStatus::Queued))?
+                .clone();
+            let record = self.jobs.get_mut(&id)?;
+            match record.secret_value {
+                Some(secret_value) => break (id, secret_value),
+                // jobs without a witness fail on open; this is only a safeguard
+                None => {
+                    record.job.error = Some("witness missing".into());
+                    record.finish(JobStatus::Failed);
+                }
+            }
+        };
+
+        let record = self.jobs.get_mut(&id)?;
+        record.job.status = JobStatus::Running;
+        record.job.started_at = Some(now_ms());
+        let threshold = record.job.threshold;
+        Some((id, ProveRequest { secret_value, threshold }))
+    }
+
+    /// Finish job `id` with `result` if it is still running; a job cancelled
+    /// or requeued meanwhile is left alone.
+    fn finish_running(&mut self, id: &str, result: Result<SubmitProofRequest, String>) -> bool {
+        let Some(record) = self.jobs.get_mut(id).filter(|r| r.job.status == JobStatus::Running) else {
+            return false;
+        };
+
+        match result {
+            Ok(request) => {
+                tracing::info!(job = %id, nonce = request.public_inputs.nonce, "proving job succeeded");
+                record.job.result = Some(request);
+                record.finish(JobStatus::Succeeded);
+            }
+            Err(err) => {
+                tracing::warn!(job = %id, error = %err, "proving job failed");
+                record.job.error = Some(err);
+                record.finish(JobStatus::Failed);
+            }
+        }
+        true
+    }
+
+    fn touch(&mut self, owner: &str, worker_id: &str) -> Result<(), JobError> {
+        let worker = self
+            .workers
+            .get_mut(worker_id)
+            .filter(|w| w.owner == owner)
+            .ok_or(JobError::UnknownWorker)?;
+        worker.last_seen = Instant::now();
+        Ok(())
+    }
+
+    fn count(&self, status: JobStatus) -> usize {
+        self.jobs.values().filter(|r| r.job.status == status).count()
+    }
+
+    /// Drop the oldest finished jobs beyond `retain`.
+    fn evict(&mut self, retain: usize) {
+        let mut excess = self
+            .jobs
+            .values()
+            .filter(|r| r.job.status.is_finished())
+            .count()
+            .saturating_sub(retain);
+        let jobs = &mut self.jobs;
+        self.order.retain(|id| {
+            let finished = jobs.get(id).is_some_and(|r| r.job.status.is_finished());
+            if excess > 0 && finished {
+                excess -= 1;
+                jobs.remove(id);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
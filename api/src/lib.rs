@@ -1,4 +1,74 @@
 // ✅ KEEP THESE — they are required for the whole API
 pub mod handler;
 pub mod models;
+pub mod webhook;
+pub mod rate_limit;
+pub mod auth;
+pub mod metrics;
+pub mod telemetry;
+pub mod config;
+pub mod health;
+pub mod shutdown;
+pub mod tls;
+pub mod openapi;
+pub mod routes;
+pub mod receipts;
+pub mod jobs;
+pub mod commitment;
+pub mod history;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 
+#[cfg(test)]
+mod test_support;
+
+#[cfg(test)]
+mod tests_webhook;
+
+#[cfg(test)]
+mod tests_rate_limit;
+
+#[cfg(test)]
+mod tests_auth;
+
+#[cfg(test)]
+mod tests_metrics;
+
+#[cfg(test)]
+mod tests_telemetry;
+
+#[cfg(test)]
+mod tests_config;
+
+#[cfg(test)]
+mod tests_health;
+
+#[cfg(test)]
+mod tests_shutdown;
+
+#[cfg(test)]
+mod tests_tls;
+
+#[cfg(test)]
+mod tests_openapi;
+
+#[cfg(all(test, feature = "grpc"))]
+mod tests_grpc;
+
+#[cfg(test)]
+mod tests_limits;
+
+#[cfg(test)]
+mod tests_receipts;
+
+#[cfg(all(test, feature = "zk-vm"))]
+mod tests_prove;
+
+#[cfg(test)]
+mod tests_jobs;
+
+#[cfg(test)]
+mod tests_workers;
+
+#[cfg(test)]
+mod tests_history;
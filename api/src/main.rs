@@ -1,118 +1,320 @@
-use axum::{
-    routing::post,
-    middleware,
-    Router,
-    Extension,
-};
-use std::{
-    env,
-    net::SocketAddr,
-    sync::{Arc, Mutex},
-    time::Duration,
-};
-use tokio::net::TcpListener;
-
-use axum::{
-    body::Body,
-    http::Request,
-    middleware::Next,
-    response::Response,
-};
-
-use zkcg_verifier::engine::VerifierEngine;
-use zkcg_common::state::ProtocolState;
-use zkcg_verifier::backend_zkvm::ZkVmBackend;
-
-use api::handler::{
-    submit_proof,
-    prove,
-    demo_prove_handler,
-    demo_verify_handler,
-    AppState,
-};
-
-mod rate_limit;
-use rate_limit::RateLimiter;
-
-// 👇 simple request logger middleware
-async fn log_requests(
-    req: Request<Body>,
-    next: Next,
-) -> Response {
-    println!(
-        "[REQUEST] {} {}",
-        req.method(),
-        req.uri().path()
-    );
-
-    let res = next.run(req).await;
-
-    println!(
-        "[RESPONSE] status={}",
-        res.status()
-    );
-
-    res
-}
-
-#[tokio::main]
-async fn main() {
-    let port: u16 = env::var("PORT")
-        .unwrap_or_else(|_| "8080".to_string())
-        .parse()
-        .unwrap();
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-
-    println!("[BOOT] starting ZKCG API");
-
-    let engine = VerifierEngine::new(
-        ProtocolState::genesis(),
-        Box::new(ZkVmBackend),
-    );
-
-    let app_state = AppState {
-        engine: Arc::new(Mutex::new(engine)),
-    };
-
-    let prove_limiter = Arc::new(RateLimiter::new(5, Duration::from_secs(60)));
-    let verify_limiter = Arc::new(RateLimiter::new(30, Duration::from_secs(60)));
-
-    let demo_routes = Router::new()
-        .route(
-            "/demo/prove",
-            post(demo_prove_handler)
-                .route_layer(middleware::from_fn(RateLimiter::middleware))
-                .route_layer(Extension(prove_limiter)),
-        )
-        .route(
-            "/demo/verify",
-            post(demo_verify_handler)
-                .route_layer(middleware::from_fn(RateLimiter::middleware))
-                .route_layer(Extension(verify_limiter)),
-        );
-
-    let mut app = Router::new()
-        .merge(demo_routes)
-        .layer(Extension(app_state));
-
-    if env::var("ZKCG_ENABLE_PROTOCOL").is_ok() {
-        println!("[CONFIG] protocol endpoints ENABLED");
-        app = app
-            .route("/v1/submit-proof", post(submit_proof))
-            .route("/v1/prove", post(prove));
-    } else {
-        println!("[CONFIG] protocol endpoints DISABLED");
-    }
-
-    // 👇 global request logging
-    let app = app.layer(middleware::from_fn(log_requests));
-
-    println!("[LISTENING] {}", addr);
-
-    let listener = TcpListener::bind(addr).await.unwrap();
-
-    axum::serve(listener, app)
-        .await
-        .unwrap();
-}
+use axum::{middleware, Extension};
+use clap::Parser;
+use std::{
+    env,
+    net::SocketAddr,
+    future::IntoFuture,
+    process::ExitCode,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tokio::{net::TcpListener, sync::broadcast};
+
+use zkcg_verifier::engine::VerifierEngine;
+use zkcg_verifier::storage::StateStore;
+use api::history::TransitionLog;
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::selftest::{self, Fixture, SelfTestReport};
+
+use api::handler::{prove_next, AppState};
+use api::auth::KeyStore;
+use api::config::{Cli, NodeConfig};
+use api::health::Readiness;
+use api::jobs::JobQueue;
+use api::receipts::{ReceiptStore, DEFAULT_RETAIN};
+use api::shutdown::{self, Shutdown};
+use api::tls::TlsServer;
+use api::metrics::metrics;
+use api::routes::{http_routes, RouteLimiters};
+use api::webhook::{RetryPolicy, WebhookDispatcher};
+
+use api::telemetry::{self, LogFormat};
+
+/// Applied transitions buffered per stream subscriber before it lags.
+const TRANSITION_BUFFER: usize = 256;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let log_format = env::var("ZKCG_LOG_FORMAT")
+        .ok()
+        .and_then(|f| LogFormat::parse(&f))
+        .unwrap_or(LogFormat::Pretty);
+    telemetry::init(log_format, "info");
+
+    let config = match NodeConfig::from_cli(&cli) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(problems) = config.validate() {
+        for problem in &problems {
+            eprintln!("config error: {}", problem);
+        }
+        return ExitCode::FAILURE;
+    }
+
+    let backend: Arc<dyn ProofBackend> = match config.build_backend() {
+        Ok(backend) => backend.into(),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fixture = match config.backend.self_test.as_deref().map(Fixture::read).transpose() {
+        Ok(fixture) => fixture,
+        Err(e) => {
+            eprintln!("cannot read backend.self_test: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let unchecked = fixture.is_none() && !config.backend.require_self_test;
+
+    // known-answer checks run before the listener is bound, so no traffic
+    // reaches a backend with a mis-loaded key or the wrong image id
+    let self_test = if unchecked {
+        SelfTestReport {
+            backend: backend.name(),
+            checked: 0,
+            failures: Vec::new(),
+        }
+    } else {
+        let backend = backend.clone();
+        tokio::task::spawn_blocking(move || selftest::run(backend.as_ref(), fixture.as_ref()))
+            .await
+            .expect("self-test task panicked")
+    };
+    if unchecked {
+        tracing::warn!(backend = self_test.backend, "backend.require_self_test is off; nothing was checked");
+    } else if self_test.passed() {
+        tracing::info!(backend = self_test.backend, checked = self_test.checked, "backend self-test passed");
+    } else {
+        tracing::error!(backend = self_test.backend, failures = ?self_test.failures, "backend self-test failed");
+        if config.backend.self_test.is_none() {
+            tracing::error!("no known-good proof to check; set backend.self_test to a fixture made for this release");
+        }
+    }
+
+    let tls = match config.tls.as_ref().map(TlsServer::from_config).transpose() {
+        Ok(tls) => tls,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if cli.check_config {
+        println!("{:#?}", config);
+        if !self_test.passed() && !unchecked {
+            for failure in &self_test.failures {
+                eprintln!("self-test failure ({}): {}", self_test.backend, failure);
+            }
+            return ExitCode::FAILURE;
+        }
+        println!("config OK");
+        return ExitCode::SUCCESS;
+    }
+
+    let readiness = if unchecked {
+        Readiness::allowing_skipped()
+    } else {
+        Readiness::new()
+    };
+    readiness.record(vec![self_test]);
+
+    let addr = config.listen;
+
+    tracing::info!(backend = ?config.backend.kind, "starting ZKCG API");
+
+    let max_proof = backend.max_proof_size();
+    let genesis = config.genesis_state().expect("genesis is validated at startup");
+    let store = StateStore::open(config.storage.state.clone(), genesis)
+        .expect("failed to open state store");
+
+    let engine = VerifierEngine::with_shared_backend(store.load(), backend)
+        .with_mempool(config.mempool.mempool_config());
+
+    let webhooks = WebhookDispatcher::open(
+        Some(config.storage.webhook_outbox.clone()),
+        RetryPolicy::default(),
+    )
+    .expect("failed to open webhook outbox");
+    tokio::spawn(webhooks.clone().run());
+
+    let receipts = ReceiptStore::open(Some(config.storage.receipts.clone()), DEFAULT_RETAIN)
+        .expect("failed to open receipt store");
+    let forgotten = receipts.forget_after(engine.state().nonce);
+    if forgotten > 0 {
+        tracing::warn!(forgotten, "discarded receipts for transitions the saved state does not include");
+    }
+
+    let history = TransitionLog::open(config.storage.transitions.clone(), engine.state().nonce)
+        .expect("failed to open transition log");
+
+    let jobs = JobQueue::open(Some(config.storage.jobs.clone()), config.jobs.clone())
+        .expect("failed to open job queue");
+
+    let shutdown = Shutdown::new();
+
+    let app_state = AppState {
+        engine: Arc::new(Mutex::new(engine)),
+        webhooks,
+        store,
+        transitions: broadcast::channel(TRANSITION_BUFFER).0,
+        receipts,
+        history,
+        jobs,
+    };
+
+    if config.jobs.local {
+        tokio::spawn(app_state.jobs.clone().run({
+            let state = app_state.clone();
+            move |req| {
+                let state = state.clone();
+                async move { prove_next(&state, req).await.map_err(|(_, message)| message) }
+            }
+        }));
+    } else {
+        tracing::info!("local proving disabled; jobs wait for remote workers");
+    }
+    tokio::spawn(app_state.jobs.clone().reap_workers());
+    tokio::spawn(app_state.jobs.clone().persist_loop());
+
+    let keys = KeyStore::open(Some(config.storage.keystore.clone()))
+        .expect("failed to open keystore");
+
+    if !keys.has_active_admin() {
+        let path = &config.storage.bootstrap_token;
+        match keys.bootstrap_admin(path) {
+            Ok(key_id) => tracing::warn!(
+                %key_id,
+                path = %path.display(),
+                "no admin key found; created bootstrap key, its token is in the file"
+            ),
+            Err(e) => {
+                eprintln!("cannot create bootstrap admin key at {}: {}", path.display(), e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let limiters = RouteLimiters::from_config(&config.rate_limits);
+
+    metrics().set_state(app_state.engine.lock().unwrap().state());
+
+    let app = http_routes(&config, max_proof, &limiters).into_router();
+
+    #[cfg(feature = "grpc")]
+    let app = if config.routes.grpc {
+        tracing::info!("grpc service enabled");
+        let service = api::grpc::GrpcService::new(
+            app_state.clone(),
+            keys.clone(),
+            shutdown.clone(),
+            limiters.submit.clone(),
+        );
+        let submit_body = api::config::BodyLimitConfig::for_proof(config.body_limits.submit, max_proof);
+        app.merge(service.into_router(submit_body))
+    } else {
+        app
+    };
+
+    // applied after every route is registered so protocol routes see it too
+    let app = app
+        .layer(Extension(app_state.clone()))
+        .layer(Extension(keys))
+        .layer(Extension(readiness))
+        .layer(Extension(shutdown.clone()));
+
+    // request span + x-request-id on every route
+    let app = app.layer(middleware::from_fn(telemetry::trace_requests));
+
+    tracing::info!(%addr, tls = tls.is_some(), "listening");
+
+    let listener = TcpListener::bind(addr).await.unwrap();
+
+    let mut server = match tls {
+        Some(tls) => {
+            let shutdown = shutdown.clone();
+            tokio::spawn(async move {
+                tls.serve(listener, app, shutdown).await;
+                Ok(())
+            })
+        }
+        None => tokio::spawn(
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(shutdown.clone().triggered())
+                .into_future(),
+        ),
+    };
+
+    tokio::select! {
+        _ = shutdown::signal() => {}
+        res = &mut server => {
+            // the server only returns on its own if it failed
+            tracing::error!(result = ?res, "server stopped unexpectedly");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    // stop taking submissions, then give in-flight ones until the deadline
+    let grace = config.shutdown.drain_timeout();
+    let deadline = Instant::now() + grace;
+    tracing::info!(in_flight = shutdown.in_flight(), grace_secs = grace.as_secs(), "shutdown requested; draining");
+    shutdown.trigger();
+
+    if !shutdown.drain(grace).await {
+        tracing::warn!(in_flight = shutdown.in_flight(), "drain deadline passed; abandoning in-flight submissions");
+    }
+    if tokio::time::timeout_at(deadline.into(), &mut server).await.is_err() {
+        tracing::warn!("connections still open at the deadline; closing them");
+        server.abort();
+    }
+
+    // an apply that raced the deadline finishes before this lock is granted
+    let (final_state, queued) = {
+        let engine = app_state.engine.lock().unwrap();
+        app_state.store.save(engine.state().clone());
+        (engine.state().clone(), engine.mempool().map_or(0, |m| m.len()))
+    };
+    let (store, history, receipts, webhooks, jobs) = (
+        app_state.store.clone(),
+        app_state.history.clone(),
+        app_state.receipts.clone(),
+        app_state.webhooks.clone(),
+        app_state.jobs.clone(),
+    );
+    let flushed = tokio::task::spawn_blocking(move || {
+        history.sync();
+        if let Err(e) = receipts.flush() {
+            tracing::warn!(error = %e, "failed to persist receipts on shutdown");
+        }
+        if let Err(e) = webhooks.flush() {
+            tracing::warn!(error = %e, "failed to persist webhook outbox on shutdown");
+        }
+        if let Err(e) = jobs.flush() {
+            tracing::warn!(error = %e, "failed to persist proving jobs on shutdown");
+        }
+        store.flush()
+    })
+    .await
+    .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+    match flushed {
+        Ok(()) => tracing::info!(
+            nonce = final_state.nonce,
+            epoch = final_state.epoch,
+            dropped_queued = queued,
+            "state flushed; shutdown complete"
+        ),
+        Err(e) => {
+            tracing::error!(error = %e, nonce = final_state.nonce, "failed to flush state on shutdown");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
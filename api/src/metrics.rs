@@ -0,0 +1,146 @@
+use axum::http::{header::CONTENT_TYPE, StatusCode};
+use axum::response::{IntoResponse, Response};
+use prometheus::{
+    exponential_buckets, Encoder, Histogram, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use std::{sync::OnceLock, time::Duration};
+use zkcg_common::{errors::ProtocolError, state::ProtocolState};
+
+/// Node-wide Prometheus metrics, served at `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    transitions: IntCounterVec,
+    verify_seconds: HistogramVec,
+    proof_bytes: Histogram,
+    rate_limited: IntCounterVec,
+    nonce: IntGauge,
+    epoch: IntGauge,
+    log_gaps: IntCounter,
+    mempool_dropped: IntCounterVec,
+}
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Process-wide metrics, registered on first use.
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(|| Metrics::new().expect("metric definitions are valid"))
+}
+
+impl Metrics {
+    /// A fresh registry; the node uses the one behind [`metrics`].
+    pub(crate) fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("zkcg".to_string()), None)?;
+
+        let transitions = IntCounterVec::new(
+            Opts::new("transitions_total", "Submitted transitions by outcome"),
+            &["result"],
+        )?;
+        let verify_seconds = HistogramVec::new(
+            HistogramOpts::new("verify_duration_seconds", "Proof verification latency")
+                .buckets(exponential_buckets(0.0001, 4.0, 10)?),
+            &["backend"],
+        )?;
+        let proof_bytes = Histogram::with_opts(
+            HistogramOpts::new("proof_size_bytes", "Decoded proof size")
+                .buckets(exponential_buckets(64.0, 4.0, 10)?),
+        )?;
+        let rate_limited = IntCounterVec::new(
+            Opts::new("rate_limited_total", "Requests rejected by the rate limiter"),
+            &["route"],
+        )?;
+        let nonce = IntGauge::new("state_nonce", "Current protocol nonce")?;
+        let epoch = IntGauge::new("state_epoch", "Current protocol epoch")?;
+        let log_gaps = IntCounter::new(
+            "transition_log_gaps_total",
+            "Applied transitions missing from the transition log",
+        )?;
+        let mempool_dropped = IntCounterVec::new(
+            Opts::new("mempool_dropped_total", "Queued submissions dropped without being applied"),
+            &["reason"],
+        )?;
+
+        registry.register(Box::new(transitions.clone()))?;
+        registry.register(Box::new(verify_seconds.clone()))?;
+        registry.register(Box::new(proof_bytes.clone()))?;
+        registry.register(Box::new(rate_limited.clone()))?;
+        registry.register(Box::new(nonce.clone()))?;
+        registry.register(Box::new(epoch.clone()))?;
+        registry.register(Box::new(log_gaps.clone()))?;
+        registry.register(Box::new(mempool_dropped.clone()))?;
+
+        Ok(Self {
+            registry,
+            transitions,
+            verify_seconds,
+            proof_bytes,
+            rate_limited,
+            nonce,
+            epoch,
+            log_gaps,
+            mempool_dropped,
+        })
+    }
+
+    /// Count an accepted (or queued) transition, labelled `accepted`/`queued`.
+    pub fn record_outcome(&self, outcome: &str) {
+        self.transitions.with_label_values(&[outcome]).inc();
+    }
+
+    /// Count a rejected transition, labelled by its SPEC error code.
+    pub fn record_rejection(&self, err: &ProtocolError) {
+        self.transitions.with_label_values(&[err.code()]).inc();
+    }
+
+    pub fn observe_verify(&self, backend: &str, elapsed: Duration) {
+        self.verify_seconds
+            .with_label_values(&[backend])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn observe_proof_size(&self, bytes: usize) {
+        self.proof_bytes.observe(bytes as f64);
+    }
+
+    pub fn record_rate_limited(&self, route: &str) {
+        self.rate_limited.with_label_values(&[route]).inc();
+    }
+
+    pub fn set_state(&self, state: &ProtocolState) {
+        self.nonce.set(state.nonce as i64);
+        self.epoch.set(state.epoch as i64);
+    }
+
+    pub fn record_log_gap(&self, missing: u64) {
+        self.log_gaps.inc_by(missing);
+    }
+
+    /// Count a queued submission dropped for `reason` (`expired`, `superseded`, `stale`).
+    pub fn record_mempool_drop(&self, reason: &str) {
+        self.mempool_dropped.with_label_values(&[reason]).inc();
+    }
+
+    /// Text exposition of every registered metric.
+    pub fn render(&self) -> String {
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .expect("text encoding into a Vec cannot fail");
+        String::from_utf8(buf).expect("prometheus text format is utf-8")
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "ops",
+    responses((status = 200, description = "Prometheus text exposition", body = String, content_type = "text/plain")),
+)]
+pub async fn metrics_handler() -> Response {
+    (
+        StatusCode::OK,
+        [(CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics().render(),
+    )
+        .into_response()
+}
@@ -1,65 +1,191 @@
-use serde::{Deserialize, Serialize};
-use zkcg_common::types::Hash;
-
-#[derive(Debug, Deserialize)]
-pub struct SubmitProofRequest {
-    pub proof: String,
-    pub public_inputs: PublicInputsDto,
-    pub new_state_commitment: Hash,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct PublicInputsDto {
-    pub threshold: u64,
-    pub old_state_root: Hash,
-    pub nonce: u64,
-}
-
-#[derive(Debug, Serialize)]
-pub struct SubmitProofResponse {
-    pub status: String,
-}
-
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProveRequest {
-    // demo inputs (can evolve later)
-    pub secret_value: u64,
-    pub threshold: u64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProveResponse {
-    pub proof: String,                 // base64
-    pub public_inputs: ProvePublicInputs,
-    pub commitment: [u8; 32],
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ProvePublicInputs {
-    pub threshold: u64,
-}
-
-#[derive(Deserialize)]
-pub struct DemoProveRequest {
-    pub score: u64,
-    pub threshold: u64,
-}
-
-#[derive(Serialize)]
-pub struct DemoProveResponse {
-    pub proof: String, // base64
-    pub proof_size_bytes: usize,
-    pub note: &'static str,
-}
-
-#[derive(Deserialize)]
-pub struct DemoVerifyRequest {
-    pub proof: String, // base64
-    pub threshold: u64,
-}
-
-#[derive(Serialize)]
-pub struct DemoVerifyResponse {
-    pub verified: bool,
-}
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use zkcg_common::types::Hash;
+use crate::auth::Role;
+use crate::receipts::AcceptanceRecord;
+
+pub use zkcg_common::wire::{PublicInputsDto, SubmissionSignatureDto, SubmitProofRequest};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmitProofResponse {
+    /// `accepted`, or `queued` until the preceding nonce is accepted
+    #[schema(example = "accepted")]
+    pub status: String,
+    /// Present once accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<AcceptanceRecord>,
+    /// True when this answers a resubmission of an earlier submission
+    pub replayed: bool,
+}
+
+/// What became of a submission, from `GET /v1/submissions/{id}`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmissionStatusResponse {
+    /// `accepted`, `queued`, or `dropped` when it left the mempool without being applied
+    #[schema(example = "dropped")]
+    pub status: String,
+    /// Present once accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub receipt: Option<AcceptanceRecord>,
+    /// Why a dropped submission was dropped: `expired`, `superseded` or `stale`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Latest accepted protocol state (SPEC §4).
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StateResponse {
+    /// Hex id of the channel; prover signatures cover it
+    pub channel: String,
+    #[schema(schema_with = crate::openapi::hash_schema)]
+    pub state_root: Hash,
+    /// The next submission must carry `nonce + 1`
+    pub nonce: u64,
+    pub epoch: u64,
+    /// Hex ed25519 public keys of allowlisted provers; empty allows any
+    pub provers: Vec<String>,
+}
+
+/// Private witness for `/v1/prove`; the proof is bound to the node's current state.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProveRequest {
+    // demo inputs (can evolve later)
+    pub secret_value: u64,
+    pub threshold: u64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DemoProveRequest {
+    pub score: u64,
+    pub threshold: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DemoProveResponse {
+    /// Base64-encoded proof
+    pub proof: String,
+    pub proof_size_bytes: usize,
+    pub note: &'static str,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct DemoVerifyRequest {
+    /// Base64-encoded proof
+    pub proof: String,
+    pub threshold: u64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DemoVerifyResponse {
+    pub verified: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    /// Shared secret used to HMAC-sign every delivery.
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegisterWebhookResponse {
+    pub id: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateApiKeyRequest {
+    pub role: Role,
+    #[serde(default)]
+    pub label: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CreateApiKeyResponse {
+    pub id: String,
+    pub role: Role,
+    /// Shown once; only a hash is kept server-side.
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApiKeyInfo {
+    pub id: String,
+    pub role: Role,
+    pub label: String,
+    pub created_at: u64,
+    pub revoked: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterProverRequest {
+    /// Hex ed25519 public key, 32 bytes
+    pub public_key: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProverListResponse {
+    /// Hex ed25519 public keys
+    pub provers: Vec<String>,
+}
+
+/// Registration of a remote prover worker.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterWorkerRequest {
+    /// Shown in the node's logs
+    pub name: String,
+    /// Backends the worker can prove for, e.g. `zkvm` or `halo2`
+    pub backends: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RegisterWorkerResponse {
+    pub worker_id: String,
+    /// Backend every job from this node must be proved for
+    pub backend: String,
+    /// Heartbeat at least this often while proving; a silent worker's jobs go to another
+    pub heartbeat_interval_secs: u64,
+}
+
+/// A proving job handed to a worker, witness included.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WorkerJob {
+    pub job_id: String,
+    pub backend: String,
+    pub secret_value: u64,
+    pub threshold: u64,
+    #[schema(schema_with = crate::openapi::hash_schema)]
+    pub old_state_root: Hash,
+    pub nonce: u64,
+}
+
+/// What a worker reports for its job: exactly one of `proof` and `error`.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CompleteJobRequest {
+    /// Base64-encoded proof
+    #[serde(default)]
+    pub proof: Option<String>,
+    /// Why proving failed
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthResponse {
+    pub status: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BackendSelfTest {
+    pub backend: String,
+    pub checked: usize,
+    pub passed: bool,
+    /// Nothing was checked; only ready with `backend.require_self_test = false`
+    pub skipped: bool,
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub ready: bool,
+    pub backends: Vec<BackendSelfTest>,
+}
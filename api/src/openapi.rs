@@ -0,0 +1,115 @@
+use axum::Json;
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+pub use zkcg_common::wire::hash_schema;
+
+use crate::{auth, handler, health, jobs, metrics, models, receipts, webhook};
+
+/// OpenAPI 3 description of the HTTP API, generated from the handlers and DTOs.
+///
+/// Operations are listed here by hand; `tests_openapi.rs` checks them against
+/// every route [`crate::routes::http_routes`] serves, in both directions.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "ZKCG Verifier API"),
+    paths(
+        health::healthz,
+        health::readyz,
+        openapi_json,
+        metrics::metrics_handler,
+        handler::demo_prove_handler,
+        handler::demo_verify_handler,
+        handler::submit_proof,
+        handler::get_state,
+        handler::get_submission,
+        handler::prove,
+        handler::get_job,
+        handler::cancel_job,
+        handler::register_worker,
+        handler::worker_heartbeat,
+        handler::claim_job,
+        handler::complete_job,
+        handler::register_webhook,
+        handler::delete_webhook,
+        handler::webhook_dead_letters,
+        handler::create_api_key,
+        handler::list_api_keys,
+        handler::revoke_api_key,
+        handler::register_prover,
+        handler::remove_prover,
+        handler::list_provers,
+    ),
+    components(schemas(
+        models::SubmitProofRequest,
+        models::SubmissionSignatureDto,
+        models::PublicInputsDto,
+        models::SubmitProofResponse,
+        models::SubmissionStatusResponse,
+        receipts::AcceptanceRecord,
+        models::StateResponse,
+        models::ProveRequest,
+        jobs::Job,
+        jobs::JobStatus,
+        models::RegisterWorkerRequest,
+        models::RegisterWorkerResponse,
+        models::WorkerJob,
+        models::CompleteJobRequest,
+        models::DemoProveRequest,
+        models::DemoProveResponse,
+        models::DemoVerifyRequest,
+        models::DemoVerifyResponse,
+        models::RegisterWebhookRequest,
+        models::RegisterWebhookResponse,
+        models::CreateApiKeyRequest,
+        models::CreateApiKeyResponse,
+        models::ApiKeyInfo,
+        models::RegisterProverRequest,
+        models::ProverListResponse,
+        models::HealthResponse,
+        models::BackendSelfTest,
+        models::ReadinessResponse,
+        auth::Role,
+        webhook::Delivery,
+        webhook::WebhookEvent,
+    )),
+    modifiers(&ApiKeyAuth),
+    tags(
+        (name = "ops", description = "Health, readiness and metrics"),
+        (name = "demo", description = "Stateless demo endpoints"),
+        (name = "protocol", description = "Proof submission, proving jobs and state; needs a submitter key, or an observer key to read state"),
+        (name = "workers", description = "Remote provers taking jobs queued by `/v1/prove`; needs a worker key"),
+        (name = "admin", description = "Keys, provers and webhooks; needs an admin key"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Both ways of presenting a key accepted by [`auth::require_role`].
+struct ApiKeyAuth;
+
+impl Modify for ApiKeyAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(auth::API_KEY_HEADER))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// This document.
+#[utoipa::path(
+    get,
+    path = "/openapi.json",
+    tag = "ops",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json")),
+)]
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
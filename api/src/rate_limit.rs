@@ -1,28 +1,154 @@
 use axum::{
     body::Body,
-    http::Request,
+    extract::{ConnectInfo, MatchedPath},
+    http::{HeaderValue, Request},
     middleware::Next,
     response::Response,
     Extension,
 };
+use crate::metrics::metrics;
 use std::{
+    collections::{BTreeSet, HashMap},
+    net::SocketAddr,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
-#[derive(Clone)]
+/// Identity to rate-limit a request by, set by upstream middleware
+/// (e.g. authentication). Requests without one are keyed by peer IP.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RateLimitKey(pub String);
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    /// When the bucket is full again if left alone
+    full_at: Instant,
+}
+
+#[derive(Default)]
+struct Buckets {
+    by_key: HashMap<RateLimitKey, Bucket>,
+    /// Every key, by the time its bucket is full again; soonest first
+    by_refill: BTreeSet<(Instant, RateLimitKey)>,
+}
+
+/// Result of taking a token for one request.
+#[derive(Debug, Clone, Copy)]
+pub struct Decision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Time until the bucket is full again.
+    pub reset: Duration,
+    /// Time until the next token is available; zero when allowed.
+    pub retry_after: Duration,
+}
+
+/// Per-client token bucket limiter for one route.
+///
+/// Each key gets a bucket of `capacity` tokens refilled continuously at
+/// `capacity / window`. The key table holds at most `max_keys` buckets;
+/// when full, idle (fully refilled) buckets are dropped first, then the one
+/// closest to full. Buckets are indexed by when they refill, so making
+/// room takes logarithmic time however many keys are tracked.
 pub struct RateLimiter {
-    max: usize,
-    window: Duration,
-    hits: Arc<Mutex<Vec<Instant>>>,
+    capacity: u32,
+    refill_per_sec: f64,
+    max_keys: usize,
+    buckets: Mutex<Buckets>,
 }
 
 impl RateLimiter {
-    pub fn new(max: usize, window: Duration) -> Self {
+    pub const DEFAULT_MAX_KEYS: usize = 10_000;
+
+    /// `max` requests per `window` for every client.
+    pub fn new(max: u32, window: Duration) -> Self {
         Self {
-            max,
-            window,
-            hits: Arc::new(Mutex::new(Vec::new())),
+            capacity: max,
+            refill_per_sec: max as f64 / window.as_secs_f64(),
+            max_keys: Self::DEFAULT_MAX_KEYS,
+            buckets: Mutex::new(Buckets::default()),
+        }
+    }
+
+    pub fn with_max_keys(mut self, max_keys: usize) -> Self {
+        self.max_keys = max_keys.max(1);
+        self
+    }
+
+    pub fn tracked_keys(&self) -> usize {
+        self.buckets.lock().unwrap().by_key.len()
+    }
+
+    pub fn check(&self, key: &RateLimitKey) -> Decision {
+        self.check_at(key, Instant::now())
+    }
+
+    pub fn check_at(&self, key: &RateLimitKey, now: Instant) -> Decision {
+        let mut guard = self.buckets.lock().unwrap();
+        let Buckets { by_key, by_refill } = &mut *guard;
+        let capacity = self.capacity as f64;
+
+        if !by_key.contains_key(key) && by_key.len() >= self.max_keys {
+            Self::evict(by_key, by_refill, self.max_keys, now);
+        }
+
+        let bucket = by_key.entry(key.clone()).or_insert(Bucket {
+            tokens: capacity,
+            updated: now,
+            full_at: now,
+        });
+        by_refill.remove(&(bucket.full_at, key.clone()));
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(capacity);
+        bucket.updated = now;
+
+        let allowed = bucket.tokens >= 1.0;
+        if allowed {
+            bucket.tokens -= 1.0;
+        }
+
+        let secs_until = |tokens: f64| {
+            if tokens <= 0.0 || self.refill_per_sec <= 0.0 {
+                Duration::ZERO
+            } else {
+                Duration::from_secs_f64(tokens / self.refill_per_sec)
+            }
+        };
+
+        bucket.full_at = now + secs_until(capacity - bucket.tokens);
+        by_refill.insert((bucket.full_at, key.clone()));
+
+        Decision {
+            allowed,
+            limit: self.capacity,
+            remaining: bucket.tokens.floor() as u32,
+            reset: secs_until(capacity - bucket.tokens),
+            retry_after: if allowed {
+                Duration::ZERO
+            } else {
+                secs_until(1.0 - bucket.tokens)
+            },
+        }
+    }
+
+    fn evict(
+        by_key: &mut HashMap<RateLimitKey, Bucket>,
+        by_refill: &mut BTreeSet<(Instant, RateLimitKey)>,
+        max_keys: usize,
+        now: Instant,
+    ) {
+        // A bucket that would be full by now carries no information, nor
+        // much does the one closest to it.
+        while let Some((full_at, _)) = by_refill.first() {
+            if *full_at > now && by_key.len() < max_keys {
+                break;
+            }
+            let (_, key) = by_refill.pop_first().expect("checked non-empty");
+            by_key.remove(&key);
         }
     }
 
@@ -31,22 +157,60 @@ impl RateLimiter {
         req: Request<Body>,
         next: Next,
     ) -> Response {
-        {
-            let mut hits = limiter.hits.lock().unwrap();
-            let now = Instant::now();
+        let key = client_key(&req);
+        let decision = limiter.check(&key);
 
-            hits.retain(|t| now.duration_since(*t) < limiter.window);
+        let mut res = if decision.allowed {
+            next.run(req).await
+        } else {
+            let route = req
+                .extensions()
+                .get::<MatchedPath>()
+                .map(|p| p.as_str().to_string())
+                .unwrap_or_else(|| req.uri().path().to_string());
+            metrics().record_rate_limited(&route);
 
-            if hits.len() >= limiter.max {
-                return Response::builder()
-                    .status(429)
-                    .body("rate limit exceeded".into())
-                    .unwrap();
-            }
+            let mut res = Response::builder()
+                .status(429)
+                .body("rate limit exceeded".into())
+                .unwrap();
+            res.headers_mut()
+                .insert("retry-after", ceil_secs(decision.retry_after));
+            res
+        };
 
-            hits.push(now);
-        }
+        let headers = res.headers_mut();
+        headers.insert("x-ratelimit-limit", HeaderValue::from(decision.limit));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from(decision.remaining));
+        headers.insert("x-ratelimit-reset", ceil_secs(decision.reset));
 
-        next.run(req).await
+        res
     }
 }
+
+/// Parse a `<requests>/<window secs>` limit, e.g. `30/60`.
+pub fn parse_limit(spec: &str) -> Option<(u32, Duration)> {
+    let (max, window) = spec.trim().split_once('/')?;
+    let max: u32 = max.trim().parse().ok()?;
+    let window: u64 = window.trim().trim_end_matches('s').parse().ok()?;
+
+    if max == 0 || window == 0 {
+        return None;
+    }
+    Some((max, Duration::from_secs(window)))
+}
+
+fn client_key(req: &Request<Body>) -> RateLimitKey {
+    if let Some(key) = req.extensions().get::<RateLimitKey>() {
+        return key.clone();
+    }
+
+    match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+        Some(ConnectInfo(addr)) => RateLimitKey(format!("ip:{}", addr.ip())),
+        None => RateLimitKey("ip:unknown".to_string()),
+    }
+}
+
+fn ceil_secs(d: Duration) -> HeaderValue {
+    HeaderValue::from(d.as_secs() + u64::from(d.subsec_nanos() > 0))
+}
@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fs,
+    io,
+    mem,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use utoipa::ToSchema;
+use zkcg_common::{state::ProtocolState, types::Hash};
+use zkcg_verifier::storage::write_json;
+
+/// Acceptance records kept when no capacity is configured.
+pub const DEFAULT_RETAIN: usize = 4096;
+
+/// Header (and gRPC metadata key) carrying a client's idempotency key.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// What a client gets back for an accepted submission, and again for any
+/// resubmission of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct AcceptanceRecord {
+    /// Hex submission id: `sha256` of the canonical submission bytes (SPEC §5.3)
+    pub submission_id: String,
+    /// Nonce of the state this submission produced
+    pub nonce: u64,
+    pub epoch: u64,
+    #[schema(schema_with = crate::openapi::hash_schema)]
+    pub state_root: Hash,
+    /// Unix time (ms)
+    pub accepted_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyBinding {
+    client: String,
+    key: String,
+    submission_id: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReceiptFile {
+    /// Oldest first
+    records: Vec<AcceptanceRecord>,
+    /// Oldest first
+    keys: Vec<KeyBinding>,
+}
+
+#[derive(Default)]
+struct Inner {
+    records: HashMap<String, AcceptanceRecord>,
+    record_order: VecDeque<String>,
+    keys: HashMap<(String, String), String>,
+    key_order: VecDeque<(String, String)>,
+    /// Queued submissions as (nonce, client, id), lowest nonce first; one
+    /// client may queue competing submissions for a nonce. Not persisted,
+    /// like the mempool.
+    queued: BTreeSet<(u64, String, String)>,
+    /// Ids in `queued`, with the number of entries holding each
+    queued_ids: HashMap<String, usize>,
+    /// Queued submissions dropped from the mempool, with the reason. Not
+    /// persisted, like `queued`.
+    dropped: HashMap<String, &'static str>,
+    dropped_order: VecDeque<String>,
+    /// Bumped on every change to `records` or `keys`
+    generation: u64,
+}
+
+/// Recently accepted submissions and the idempotency keys bound to them,
+/// persisted as JSON so retries are recognised across restarts.
+///
+/// Both tables keep the newest `capacity` entries. Changes are made in
+/// memory; [`ReceiptStore::flush`] writes them out and blocks, so async
+/// callers run it on a blocking thread.
+#[derive(Clone)]
+pub struct ReceiptStore {
+    inner: Arc<Mutex<Inner>>,
+    path: Option<PathBuf>,
+    capacity: usize,
+    /// Generation last written to `path`; held for the whole write so
+    /// flushes never overtake each other
+    written: Arc<Mutex<u64>>,
+}
+
+/// [`ReceiptStore::reserve_key`] found the key bound to another submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyConflict;
+
+impl ReceiptStore {
+    /// Open (or create) a store backed by `path`. `None` keeps it in memory.
+    pub fn open(path: Option<PathBuf>, capacity: usize) -> io::Result<Self> {
+        let file: ReceiptFile = match &path {
+            Some(p) if p.exists() => {
+                let raw = fs::read(p)?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            _ => ReceiptFile::default(),
+        };
+
+        let mut inner = Inner::default();
+        for record in file.records {
+            inner.insert_record(record, capacity);
+        }
+        for binding in file.keys {
+            inner.insert_key((binding.client, binding.key), binding.submission_id, capacity);
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+            path,
+            capacity,
+            written: Arc::new(Mutex::new(0)),
+        })
+    }
+
+    pub fn get(&self, id: &Hash) -> Option<AcceptanceRecord> {
+        self.inner.lock().unwrap().records.get(&hex::encode(id)).cloned()
+    }
+
+    /// Whether `id` is waiting in the mempool.
+    pub fn is_queued(&self, id: &Hash) -> bool {
+        self.inner.lock().unwrap().queued_ids.contains_key(&hex::encode(id))
+    }
+
+    pub fn mark_queued(&self, client: &str, nonce: u64, id: &Hash) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.queue((nonce, client.to_string(), hex::encode(id)));
+    }
+
+    /// Record that submission `id`, queued by `client` for `nonce`, was
+    /// dropped from the mempool.
+    pub fn dropped(&self, client: &str, nonce: u64, id: &Hash, reason: &'static str) {
+        let mut inner = self.inner.lock().unwrap();
+        let id = hex::encode(id);
+        inner.unqueue(&(nonce, client.to_string(), id.clone()));
+        if inner.dropped.insert(id.clone(), reason).is_none() {
+            inner.dropped_order.push_back(id.clone());
+        }
+        while inner.dropped_order.len() > self.capacity {
+            if let Some(oldest) = inner.dropped_order.pop_front() {
+                inner.dropped.remove(&oldest);
+            }
+        }
+    }
+
+    /// Why `id` was dropped from the mempool, if it was.
+    pub fn drop_reason(&self, id: &Hash) -> Option<&'static str> {
+        self.inner.lock().unwrap().dropped.get(&hex::encode(id)).copied()
+    }
+
+    /// Record the acceptance of submission `id`, which produced `after`.
+    ///
+    /// The record is written by the next [`ReceiptStore::flush`].
+    pub fn accepted(&self, client: &str, after: &ProtocolState, id: &Hash) -> AcceptanceRecord {
+        let mut inner = self.inner.lock().unwrap();
+
+        let submission_id = hex::encode(id);
+        inner.unqueue(&(after.nonce, client.to_string(), submission_id.clone()));
+        // anything at or below the new nonce can no longer be applied
+        let later = inner.queued.split_off(&(after.nonce + 1, String::new(), String::new()));
+        for (_, _, id) in mem::replace(&mut inner.queued, later) {
+            inner.forget_queued(&id);
+        }
+
+        let record = AcceptanceRecord {
+            submission_id,
+            nonce: after.nonce,
+            epoch: after.epoch,
+            state_root: after.state_root,
+            accepted_at: now_ms(),
+        };
+        inner.insert_record(record.clone(), self.capacity);
+        inner.generation += 1;
+
+        record
+    }
+
+    /// Drop records of states past `nonce`, the nonce of the saved state.
+    ///
+    /// Receipts are flushed before the state, so a crash between the two
+    /// leaves records for transitions that did not survive; call this on
+    /// startup so those submissions are not answered as accepted.
+    pub fn forget_after(&self, nonce: u64) -> usize {
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.records.len();
+        inner.records.retain(|_, record| record.nonce <= nonce);
+        let forgotten = before - inner.records.len();
+        if forgotten > 0 {
+            let Inner { records, record_order, .. } = &mut *inner;
+            record_order.retain(|id| records.contains_key(id));
+            inner.generation += 1;
+        }
+        forgotten
+    }
+
+    /// Submission id an idempotency key was first used for by `client`.
+    pub fn key_lookup(&self, client: &str, key: &str) -> Option<Hash> {
+        let inner = self.inner.lock().unwrap();
+        let id = inner.keys.get(&(client.to_string(), key.to_string()))?;
+        hex::decode(id).ok()?.try_into().ok()
+    }
+
+    /// Bind `key` to `id` for `client` unless it is bound already, in one
+    /// step so concurrent requests with the same key cannot both claim it.
+    ///
+    /// Returns whether this call made the binding; a key bound to another
+    /// submission is a [`KeyConflict`]. A key is never rebound, but a
+    /// reservation can be handed back with [`ReceiptStore::release_key`].
+    pub fn reserve_key(&self, client: &str, key: &str, id: &Hash) -> Result<bool, KeyConflict> {
+        let mut inner = self.inner.lock().unwrap();
+        let slot = (client.to_string(), key.to_string());
+        let id = hex::encode(id);
+        match inner.keys.get(&slot) {
+            Some(bound) if *bound == id => Ok(false),
+            Some(_) => Err(KeyConflict),
+            None => {
+                inner.insert_key(slot, id, self.capacity);
+                inner.generation += 1;
+                Ok(true)
+            }
+        }
+    }
+
+    /// Undo a [`ReceiptStore::reserve_key`] whose submission was not
+    /// admitted, so the key can be used again. Kept if `id` was accepted or
+    /// queued meanwhile, e.g. by a concurrent retry.
+    pub fn release_key(&self, client: &str, key: &str, id: &Hash) {
+        let mut inner = self.inner.lock().unwrap();
+        let id = hex::encode(id);
+        if inner.records.contains_key(&id) || inner.queued_ids.contains_key(&id) {
+            return;
+        }
+        let slot = (client.to_string(), key.to_string());
+        if inner.keys.get(&slot) == Some(&id) {
+            inner.keys.remove(&slot);
+            inner.key_order.retain(|s| *s != slot);
+            inner.generation += 1;
+        }
+    }
+
+    /// Write records and keys to disk, unless a concurrent flush already has.
+    /// A no-op for in-memory stores.
+    pub fn flush(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut written = self.written.lock().unwrap();
+        let (file, generation) = {
+            let inner = self.inner.lock().unwrap();
+            if *written >= inner.generation {
+                return Ok(());
+            }
+            (inner.snapshot(), inner.generation)
+        };
+
+        write_json(path, &file)?;
+        *written = generation;
+        Ok(())
+    }
+}
+
+impl Inner {
+    fn snapshot(&self) -> ReceiptFile {
+        ReceiptFile {
+            records: self
+                .record_order
+                .iter()
+                .filter_map(|id| self.records.get(id).cloned())
+                .collect(),
+            keys: self
+                .key_order
+                .iter()
+                .filter_map(|slot| {
+                    Some(KeyBinding {
+                        client: slot.0.clone(),
+                        key: slot.1.clone(),
+                        submission_id: self.keys.get(slot)?.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn queue(&mut self, entry: (u64, String, String)) {
+        let id = entry.2.clone();
+        if self.queued.insert(entry) {
+            *self.queued_ids.entry(id).or_default() += 1;
+        }
+    }
+
+    fn unqueue(&mut self, entry: &(u64, String, String)) {
+        if self.queued.remove(entry) {
+            self.forget_queued(&entry.2);
+        }
+    }
+
+    /// Drop one `queued_ids` count for an entry already taken out of `queued`.
+    fn forget_queued(&mut self, id: &str) {
+        if let Some(count) = self.queued_ids.get_mut(id) {
+            *count -= 1;
+            if *count == 0 {
+                self.queued_ids.remove(id);
+            }
+        }
+    }
+
+    fn insert_record(&mut self, record: AcceptanceRecord, capacity: usize) {
+        let id = record.submission_id.clone();
+        if self.records.insert(id.clone(), record).is_none() {
+            self.record_order.push_back(id);
+        }
+        while self.record_order.len() > capacity {
+            if let Some(oldest) = self.record_order.pop_front() {
+                self.records.remove(&oldest);
+            }
+        }
+    }
+
+    fn insert_key(&mut self, slot: (String, String), id: String, capacity: usize) {
+        if self.keys.insert(slot.clone(), id).is_none() {
+            self.key_order.push_back(slot);
+        }
+        while self.key_order.len() > capacity {
+            if let Some(oldest) = self.key_order.pop_front() {
+                self.keys.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Idempotency keys are 1-255 visible ASCII characters.
+pub fn valid_key(key: &str) -> bool {
+    (1..=255).contains(&key.len()) && key.bytes().all(|b| b.is_ascii_graphic())
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
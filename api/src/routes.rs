@@ -0,0 +1,215 @@
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::Method,
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, MethodRouter, Route},
+    Extension, Router,
+};
+use std::{convert::Infallible, sync::Arc};
+use tower::{Layer, Service};
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::backend_zkvm::ZkVmBackend;
+
+use crate::auth::{require_role, Role};
+use crate::config::{BodyLimitConfig, NodeConfig, RateLimitConfig};
+use crate::handler::{
+    cancel_job, claim_job, complete_job, create_api_key, delete_webhook, demo_prove_handler,
+    demo_verify_handler, get_job, get_state, get_submission, list_api_keys, list_provers, prove, register_prover,
+    register_webhook, register_worker, remove_prover, revoke_api_key, submit_proof,
+    webhook_dead_letters, worker_heartbeat,
+};
+use crate::health::{healthz, readyz};
+use crate::metrics::metrics_handler;
+use crate::openapi::openapi_json;
+use crate::rate_limit::{parse_limit, RateLimiter};
+use crate::shutdown::Shutdown;
+
+/// HTTP routes together with the `(method, path)` of each one they serve.
+///
+/// Every route the node serves is registered through this, so the OpenAPI
+/// document can be checked against the same table the listener uses.
+#[derive(Default)]
+pub struct RouteTable {
+    router: Router,
+    served: Vec<(Method, String)>,
+}
+
+impl RouteTable {
+    /// Register `handler` for `method` on an axum `path` (`/v1/jobs/:id`).
+    pub fn route(mut self, method: Method, path: &str, handler: MethodRouter) -> Self {
+        self.served.push((method, openapi_path(path)));
+        self.router = self.router.route(path, handler);
+        self
+    }
+
+    /// Same as [`Router::route_layer`], for every route registered so far.
+    pub fn route_layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + 'static,
+        L::Service: Service<Request> + Clone + Send + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.router = self.router.route_layer(layer);
+        self
+    }
+
+    pub fn merge(mut self, other: RouteTable) -> Self {
+        self.router = self.router.merge(other.router);
+        self.served.extend(other.served);
+        self
+    }
+
+    /// `(method, path)` of every route, with paths in OpenAPI form (`/v1/jobs/{id}`).
+    pub fn served(&self) -> &[(Method, String)] {
+        &self.served
+    }
+
+    pub fn into_router(self) -> Router {
+        self.router
+    }
+}
+
+/// `/v1/jobs/:id` as OpenAPI writes it, `/v1/jobs/{id}`.
+fn openapi_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{}}}", name),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Per-route rate limiters; the submit limiter is shared with gRPC `SubmitProof`.
+pub struct RouteLimiters {
+    pub demo_prove: Arc<RateLimiter>,
+    pub demo_verify: Arc<RateLimiter>,
+    pub submit: Arc<RateLimiter>,
+    pub prove: Arc<RateLimiter>,
+}
+
+impl RouteLimiters {
+    /// Specs are checked by `NodeConfig::validate`.
+    pub fn from_config(limits: &RateLimitConfig) -> Self {
+        Self {
+            demo_prove: route_limiter("/demo/prove", &limits.demo_prove),
+            demo_verify: route_limiter("/demo/verify", &limits.demo_verify),
+            submit: route_limiter("/v1/submit-proof", &limits.submit),
+            prove: route_limiter("/v1/prove", &limits.prove),
+        }
+    }
+}
+
+fn route_limiter(route: &str, spec: &str) -> Arc<RateLimiter> {
+    let (max, window) = parse_limit(spec).expect("rate limits are validated at startup");
+
+    tracing::info!(route, max, window_secs = window.as_secs(), "rate limit configured");
+    Arc::new(RateLimiter::new(max, window))
+}
+
+/// Every HTTP route enabled by `config`, without the shared state layers.
+///
+/// `max_proof` is the node backend's proof size cap.
+pub fn http_routes(config: &NodeConfig, max_proof: usize, limiters: &RouteLimiters) -> RouteTable {
+    let bodies = &config.body_limits;
+    // the demo routes always verify with the zkVM backend
+    let demo_verify_body = BodyLimitConfig::for_proof(bodies.demo_verify, ZkVmBackend.max_proof_size());
+    let submit_body = BodyLimitConfig::for_proof(bodies.submit, max_proof);
+    tracing::info!(max_proof, submit_body, demo_verify_body, "body limits configured");
+
+    let mut app = RouteTable::default()
+        .route(Method::GET, "/healthz", get(healthz))
+        .route(Method::GET, "/readyz", get(readyz))
+        .route(Method::GET, "/openapi.json", get(openapi_json));
+    if config.routes.metrics {
+        app = app.route(Method::GET, "/metrics", get(metrics_handler));
+    }
+
+    if config.routes.demo {
+        let demo_routes = RouteTable::default()
+            .route(
+                Method::POST,
+                "/demo/prove",
+                post(demo_prove_handler)
+                    .route_layer(DefaultBodyLimit::max(bodies.demo_prove))
+                    .route_layer(middleware::from_fn(RateLimiter::middleware))
+                    .route_layer(Extension(limiters.demo_prove.clone())),
+            )
+            .route(
+                Method::POST,
+                "/demo/verify",
+                post(demo_verify_handler)
+                    .route_layer(DefaultBodyLimit::max(demo_verify_body))
+                    .route_layer(middleware::from_fn(RateLimiter::middleware))
+                    .route_layer(Extension(limiters.demo_verify.clone())),
+            );
+        app = app.merge(demo_routes);
+    }
+
+    if !config.routes.protocol {
+        tracing::info!("protocol endpoints disabled");
+        return app;
+    }
+    tracing::info!("protocol endpoints enabled");
+
+    let submitter_routes = RouteTable::default()
+        .route(
+            Method::POST,
+            "/v1/submit-proof",
+            post(submit_proof)
+                .route_layer(DefaultBodyLimit::max(submit_body))
+                .route_layer(middleware::from_fn(RateLimiter::middleware))
+                .route_layer(Extension(limiters.submit.clone())),
+        )
+        .route(
+            Method::POST,
+            "/v1/prove",
+            post(prove)
+                .route_layer(DefaultBodyLimit::max(bodies.prove))
+                .route_layer(middleware::from_fn(RateLimiter::middleware))
+                .route_layer(Extension(limiters.prove.clone())),
+        )
+        .route_layer(middleware::from_fn(Shutdown::middleware))
+        .route_layer(middleware::from_fn_with_state(Role::Submitter, require_role));
+
+    // status polling stays available while the node drains
+    let job_routes = RouteTable::default()
+        .route(Method::GET, "/v1/submissions/:id", get(get_submission))
+        .route(Method::GET, "/v1/jobs/:id", get(get_job))
+        .route(Method::DELETE, "/v1/jobs/:id", delete(cancel_job))
+        .route_layer(middleware::from_fn_with_state(Role::Submitter, require_role));
+
+    let worker_routes = RouteTable::default()
+        .route(Method::POST, "/v1/workers", post(register_worker))
+        .route(Method::POST, "/v1/workers/:id/heartbeat", post(worker_heartbeat))
+        .route(Method::POST, "/v1/workers/:id/claim", post(claim_job))
+        .route(Method::POST, "/v1/workers/:id/jobs/:job_id", post(complete_job))
+        .route_layer(DefaultBodyLimit::max(submit_body))
+        .route_layer(middleware::from_fn_with_state(Role::Worker, require_role));
+
+    let observer_routes = RouteTable::default()
+        .route(Method::GET, "/v1/state", get(get_state))
+        .route_layer(middleware::from_fn_with_state(Role::Observer, require_role));
+
+    let admin_routes = RouteTable::default()
+        .route(Method::POST, "/v1/webhooks", post(register_webhook))
+        .route(Method::DELETE, "/v1/webhooks/:id", delete(delete_webhook))
+        .route(Method::GET, "/v1/webhooks/dead-letters", get(webhook_dead_letters))
+        .route(Method::POST, "/v1/admin/keys", post(create_api_key))
+        .route(Method::GET, "/v1/admin/keys", get(list_api_keys))
+        .route(Method::DELETE, "/v1/admin/keys/:id", delete(revoke_api_key))
+        .route(Method::POST, "/v1/admin/provers", post(register_prover))
+        .route(Method::GET, "/v1/admin/provers", get(list_provers))
+        .route(Method::DELETE, "/v1/admin/provers/:public_key", delete(remove_prover))
+        .route_layer(DefaultBodyLimit::max(bodies.admin))
+        .route_layer(middleware::from_fn_with_state(Role::Admin, require_role));
+
+    app.merge(submitter_routes)
+        .merge(job_routes)
+        .merge(worker_routes)
+        .merge(observer_routes)
+        .merge(admin_routes)
+}
@@ -0,0 +1,152 @@
+use axum::{
+    body::Body,
+    extract::Extension,
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::{watch, Notify};
+
+/// Coordinates a graceful stop: once triggered, new submissions are refused
+/// while those already running are allowed to finish.
+#[derive(Clone)]
+pub struct Shutdown {
+    draining: Arc<watch::Sender<bool>>,
+    in_flight: Arc<AtomicUsize>,
+    idle: Arc<Notify>,
+}
+
+/// Marks one submission as in flight until dropped.
+pub struct InFlight {
+    shutdown: Shutdown,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if self.shutdown.in_flight.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shutdown.idle.notify_waiters();
+        }
+    }
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self {
+            draining: Arc::new(watch::channel(false).0),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            idle: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Start draining. Idempotent.
+    pub fn trigger(&self) {
+        self.draining.send_replace(true);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        *self.draining.borrow()
+    }
+
+    /// Resolves once [`Shutdown::trigger`] has been called.
+    pub async fn triggered(self) {
+        let mut rx = self.draining.subscribe();
+        // the sender lives in `self`, so this only errors if it was dropped
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+
+    /// The draining flag, for long-lived streams that end on shutdown.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.draining.subscribe()
+    }
+
+    /// Register a submission, or `None` if the node is already draining.
+    pub fn track(&self) -> Option<InFlight> {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let guard = InFlight {
+            shutdown: self.clone(),
+        };
+        // checked after incrementing so `drain` never misses a late arrival
+        if self.is_draining() {
+            None
+        } else {
+            Some(guard)
+        }
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::Acquire)
+    }
+
+    /// Wait until nothing is in flight, for at most `deadline`.
+    ///
+    /// Returns `false` if the deadline passed with submissions still running.
+    pub async fn drain(&self, deadline: Duration) -> bool {
+        tokio::time::timeout(deadline, async {
+            loop {
+                let idle = self.idle.notified();
+                if self.in_flight() == 0 {
+                    return;
+                }
+                idle.await;
+            }
+        })
+        .await
+        .is_ok()
+    }
+
+    /// Middleware refusing new work with `503` once draining, and tracking
+    /// everything it lets through.
+    pub async fn middleware(
+        Extension(shutdown): Extension<Shutdown>,
+        req: Request<Body>,
+        next: Next,
+    ) -> Response {
+        let Some(_in_flight) = shutdown.track() else {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                [("retry-after", "5")],
+                "node is shutting down",
+            )
+                .into_response();
+        };
+        next.run(req).await
+    }
+}
+
+/// Resolves on Ctrl-C, or SIGTERM on unix.
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl-C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
@@ -0,0 +1,81 @@
+use axum::{
+    body::Body,
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use tracing::Instrument;
+use tracing_subscriber::{fmt, EnvFilter};
+use uuid::Uuid;
+
+/// Header carrying the request ID, echoed back on every response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Log output format, selected with `ZKCG_LOG_FORMAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "pretty" | "text" => Some(Self::Pretty),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Install the global subscriber.
+///
+/// Levels come from `RUST_LOG` (e.g. `info,zkcg_verifier=debug`), falling back
+/// to `default_filter` when unset.
+pub fn init(format: LogFormat, default_filter: &str) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(default_filter));
+    let builder = fmt().with_env_filter(filter);
+
+    match format {
+        LogFormat::Pretty => builder.init(),
+        LogFormat::Json => builder.json().flatten_event(true).with_current_span(true).init(),
+    }
+}
+
+/// Reuse the caller's request ID if it sent a sane one, otherwise mint a fresh one.
+fn request_id(req: &Request<Body>) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= 128 && v.chars().all(|c| c.is_ascii_graphic()))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+}
+
+/// Wrap every request in a `request` span tagged with its ID.
+///
+/// Everything logged while handling the request, including engine and backend
+/// events on the blocking pool, is recorded inside this span.
+pub async fn trace_requests(req: Request<Body>, next: Next) -> Response {
+    let id = request_id(&req);
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+
+    let mut res = async move {
+        let res = next.run(req).await;
+        tracing::info!(status = res.status().as_u16(), "request completed");
+        res
+    }
+    .instrument(span)
+    .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    res
+}
@@ -0,0 +1,25 @@
+use crate::handler::AppState;
+use crate::history::TransitionLog;
+use crate::jobs::{JobLimits, JobQueue};
+use crate::receipts::{ReceiptStore, DEFAULT_RETAIN};
+use crate::webhook::{RetryPolicy, WebhookDispatcher};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use zkcg_common::state::ProtocolState;
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::engine::VerifierEngine;
+use zkcg_verifier::storage::StateStore;
+
+/// In-memory node state at genesis over `backend`.
+pub fn app_state(backend: Box<dyn ProofBackend>) -> AppState {
+    let genesis = ProtocolState::genesis();
+    AppState {
+        engine: Arc::new(Mutex::new(VerifierEngine::new(genesis.clone(), backend))),
+        webhooks: WebhookDispatcher::open(None, RetryPolicy::default()).unwrap(),
+        store: StateStore::open(None, genesis).unwrap(),
+        transitions: broadcast::channel(16).0,
+        receipts: ReceiptStore::open(None, DEFAULT_RETAIN).unwrap(),
+        history: TransitionLog::open(None, 0).unwrap(),
+        jobs: JobQueue::open(None, JobLimits::default()).unwrap(),
+    }
+}
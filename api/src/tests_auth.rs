@@ -0,0 +1,98 @@
+use crate::auth::{KeyStore, Role};
+
+#[test]
+fn created_key_authenticates_with_its_role() {
+    let keys = KeyStore::open(None).unwrap();
+    let (record, token) = keys.create(Role::Submitter, "ci".into()).unwrap();
+
+    let identity = keys.authenticate(&token).expect("fresh key authenticates");
+    assert_eq!(identity.key_id, record.id);
+    assert_eq!(identity.role, Role::Submitter);
+}
+
+#[test]
+fn tampered_or_malformed_tokens_are_rejected() {
+    let keys = KeyStore::open(None).unwrap();
+    let (_, token) = keys.create(Role::Admin, "ops".into()).unwrap();
+
+    let mut tampered = token.clone();
+    tampered.pop();
+    tampered.push('x');
+
+    assert!(keys.authenticate(&tampered).is_none());
+    assert!(keys.authenticate("").is_none());
+    assert!(keys.authenticate("zkcg_nosecret").is_none());
+    assert!(keys.authenticate(&token.replacen("zkcg", "other", 1)).is_none());
+}
+
+#[test]
+fn revoked_key_no_longer_authenticates() {
+    let keys = KeyStore::open(None).unwrap();
+    let (record, token) = keys.create(Role::Observer, "dashboard".into()).unwrap();
+
+    assert!(keys.revoke(&record.id).unwrap());
+    assert!(keys.authenticate(&token).is_none());
+    assert!(!keys.revoke("missing").unwrap());
+}
+
+#[test]
+fn secrets_are_not_stored_in_plaintext() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("keystore.json");
+    let token = {
+        let keys = KeyStore::open(Some(path.clone())).unwrap();
+        keys.create(Role::Admin, "root".into()).unwrap().1
+    };
+
+    let secret = token.rsplit('_').next().unwrap();
+    let on_disk = std::fs::read_to_string(&path).unwrap();
+    assert!(!on_disk.contains(secret));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // and the key still works after a restart
+    let reopened = KeyStore::open(Some(path.clone())).unwrap();
+    assert_eq!(reopened.authenticate(&token).unwrap().role, Role::Admin);
+    assert!(reopened.has_active_admin());
+}
+
+#[test]
+fn bootstrap_token_goes_to_a_private_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bootstrap-admin.json");
+    let keys = KeyStore::open(None).unwrap();
+
+    let key_id = keys.bootstrap_admin(&path).unwrap();
+    let file: serde_json::Value = serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    assert_eq!(file["key_id"], key_id);
+    let identity = keys.authenticate(file["token"].as_str().unwrap()).unwrap();
+    assert_eq!(identity.role, Role::Admin);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // a token nobody can read is no admin at all
+    let keys = KeyStore::open(None).unwrap();
+    assert!(keys.bootstrap_admin(&dir.path().join("missing").join("token.json")).is_err());
+    assert!(!keys.has_active_admin());
+}
+
+#[test]
+fn submitting_and_proving_are_separate_capabilities() {
+    assert!(Role::Admin.allows(Role::Submitter));
+    assert!(Role::Admin.allows(Role::Observer));
+    assert!(Role::Admin.allows(Role::Worker));
+    assert!(Role::Submitter.allows(Role::Observer));
+    assert!(Role::Worker.allows(Role::Observer));
+    assert!(!Role::Worker.allows(Role::Submitter));
+    assert!(!Role::Submitter.allows(Role::Worker));
+    assert!(!Role::Submitter.allows(Role::Admin));
+    assert!(!Role::Observer.allows(Role::Submitter));
+}
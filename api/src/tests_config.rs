@@ -0,0 +1,250 @@
+use crate::config::{BackendKind, Cli, NodeConfig};
+use std::path::PathBuf;
+
+#[test]
+fn defaults_match_previous_hard_coded_node() {
+    let config = NodeConfig::from_cli(&Cli::default()).unwrap();
+
+    assert_eq!(config.listen.port(), 8080);
+    assert_eq!(config.backend.kind, BackendKind::Zkvm);
+    assert_eq!(config.rate_limits.demo_prove, "5/60");
+    assert_eq!(config.rate_limits.demo_verify, "30/60");
+    assert!(config.routes.demo && config.routes.metrics && !config.routes.protocol);
+    assert!(config.storage.state.is_none());
+    assert_eq!(config.genesis_state().unwrap().nonce, 0);
+}
+
+#[test]
+fn file_values_are_parsed() {
+    let config = NodeConfig::parse(
+        r#"
+        listen = "127.0.0.1:9000"
+
+        [backend]
+        kind = "stub"
+
+        [genesis]
+        channel = "0303030303030303030303030303030303030303030303030303030303030303"
+        state_root = "0x0101010101010101010101010101010101010101010101010101010101010101"
+        nonce = 4
+        provers = ["0202020202020202020202020202020202020202020202020202020202020202"]
+
+        [storage]
+        state = "node-state.json"
+
+        [rate_limits]
+        submit = "120/60"
+
+        [routes]
+        protocol = true
+        demo = false
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.listen.port(), 9000);
+    assert_eq!(config.backend.kind, BackendKind::Stub);
+    assert_eq!(config.rate_limits.submit, "120/60");
+    assert_eq!(config.rate_limits.prove, "5/60");
+    assert!(config.routes.protocol && !config.routes.demo);
+    assert_eq!(config.storage.state, Some(PathBuf::from("node-state.json")));
+
+    let genesis = config.genesis_state().unwrap();
+    assert_eq!(genesis.channel, [3u8; 32]);
+    assert_eq!(genesis.state_root, [1u8; 32]);
+    assert_eq!(genesis.nonce, 4);
+    assert_eq!(genesis.provers, vec![[2u8; 32]]);
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn unknown_keys_are_rejected() {
+    assert!(NodeConfig::parse("[routes]\nadmin = true\n").is_err());
+}
+
+#[test]
+fn cli_flags_override_the_file() {
+    let cli = Cli {
+        port: Some(3000),
+        backend: Some(BackendKind::Stub),
+        state_path: Some(PathBuf::from("state.json")),
+        enable_protocol: true,
+        ..Cli::default()
+    };
+    let config = NodeConfig::from_cli(&cli).unwrap();
+
+    assert_eq!(config.listen.port(), 3000);
+    assert_eq!(config.backend.kind, BackendKind::Stub);
+    assert_eq!(config.storage.state, Some(PathBuf::from("state.json")));
+    assert!(config.routes.protocol);
+}
+
+#[test]
+fn validation_reports_every_problem() {
+    let config = NodeConfig::parse(
+        r#"
+        [genesis]
+        channel = "0303030303030303030303030303030303030303030303030303030303030303"
+        state_root = "abcd"
+
+        [rate_limits]
+        submit = "lots"
+        prove = "0/60"
+
+        [storage]
+        keystore = "/definitely/missing/dir/keystore.json"
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert_eq!(problems.len(), 4, "{:?}", problems);
+    assert!(problems.iter().any(|p| p.starts_with("genesis.state_root")));
+    assert!(problems.iter().any(|p| p.starts_with("rate_limits.submit")));
+    assert!(problems.iter().any(|p| p.starts_with("rate_limits.prove")));
+    assert!(problems.iter().any(|p| p.starts_with("storage.keystore")));
+}
+
+#[test]
+fn job_limits_must_be_positive() {
+    let config = NodeConfig::parse(
+        r#"
+        [jobs]
+        max_concurrent = 0
+        max_queued = 0
+        worker_timeout_secs = 0
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert_eq!(problems.len(), 3, "{:?}", problems);
+    assert!(problems.iter().any(|p| p.starts_with("jobs.max_concurrent")));
+    assert!(problems.iter().any(|p| p.starts_with("jobs.max_queued")));
+    assert!(problems.iter().any(|p| p.starts_with("jobs.worker_timeout_secs")));
+    assert_eq!(NodeConfig::default().jobs.max_concurrent, 1);
+}
+
+#[test]
+fn halo2_params_must_exist() {
+    let config = NodeConfig::parse(
+        r#"
+        [backend]
+        kind = "halo2"
+        halo2_params = "/definitely/missing/params.bin"
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert!(problems.iter().any(|p| p.starts_with("backend.halo2_params")));
+}
+
+#[test]
+fn backend_artifacts_are_checked_against_the_kind() {
+    let config = NodeConfig::parse(
+        r#"
+        [backend]
+        kind = "zkvm"
+        zkvm_image_id = "1234"
+        halo2_vk = "/definitely/missing/vk.bin"
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert!(problems.iter().any(|p| p.starts_with("backend.zkvm_image_id: expected 32 hex bytes")));
+    assert!(problems.iter().any(|p| p == "backend.halo2_vk is only used by the halo2 backend"));
+    assert!(problems.iter().any(|p| p == "backend.halo2_vk needs backend.halo2_params"));
+
+    let pinned = NodeConfig::parse(&format!(
+        "[backend]\nkind = \"zkvm\"\nzkvm_image_id = \"{}\"\n",
+        "ab".repeat(32)
+    ))
+    .unwrap();
+    let problems = pinned.validate().err().unwrap_or_default();
+    assert!(!problems.iter().any(|p| p.starts_with("backend.zkvm_image_id")));
+}
+
+#[test]
+fn mempool_limits_are_configurable() {
+    let config = NodeConfig::parse(
+        r#"
+        [mempool]
+        max_entries = 8
+        max_per_client = 2
+        ttl_secs = 5
+        "#,
+    )
+    .unwrap();
+
+    let mempool = config.mempool.mempool_config();
+    assert_eq!(mempool.max_entries, 8);
+    assert_eq!(mempool.max_per_client, 2);
+    assert_eq!(mempool.max_lookahead, 64);
+    assert_eq!(mempool.ttl.as_secs(), 5);
+
+    let config = NodeConfig::parse("[mempool]\nmax_lookahead = 0\nttl_secs = 0\n").unwrap();
+    let problems = config.validate().unwrap_err();
+    assert!(problems.iter().any(|p| p.starts_with("mempool.max_lookahead")));
+    assert!(problems.iter().any(|p| p.starts_with("mempool.ttl_secs")));
+}
+
+#[test]
+fn tls_client_auth_needs_a_ca() {
+    let config = NodeConfig::parse(
+        r#"
+        [tls]
+        cert = "testdata/tls/server.pem"
+        key = "testdata/tls/server.key"
+        require_client_cert = true
+
+        [[tls.clients]]
+        name = "lender"
+        fingerprint = "not-hex"
+        role = "submitter"
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert!(problems.iter().any(|p| p == "tls.require_client_cert needs tls.client_ca"));
+    assert!(problems.iter().any(|p| p == "tls.clients needs tls.client_ca"));
+    assert!(problems.iter().any(|p| p.contains("fingerprint must be 32 hex bytes")));
+}
+
+#[test]
+fn grpc_route_needs_the_feature() {
+    let config = NodeConfig::parse("[routes]\ngrpc = true\n").unwrap();
+
+    let flagged = config
+        .validate()
+        .err()
+        .is_some_and(|problems| problems.iter().any(|p| p.starts_with("routes.grpc")));
+    assert_eq!(flagged, !cfg!(feature = "grpc"));
+}
+
+#[test]
+fn zero_size_limits_are_rejected() {
+    let config = NodeConfig::parse(
+        r#"
+        [backend]
+        kind = "stub"
+        max_proof_bytes = 0
+
+        [body_limits]
+        submit = 0
+        "#,
+    )
+    .unwrap();
+
+    let problems = config.validate().unwrap_err();
+    assert!(problems.iter().any(|p| p.starts_with("body_limits.submit")));
+    assert!(problems.iter().any(|p| p.starts_with("backend.max_proof_bytes")));
+}
+
+#[test]
+fn configured_proof_cap_wraps_the_backend() {
+    let config = NodeConfig::parse("[backend]\nkind = \"stub\"\nmax_proof_bytes = 1024\n").unwrap();
+    assert_eq!(config.build_backend().unwrap().max_proof_size(), 1024);
+}
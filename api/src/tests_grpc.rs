@@ -0,0 +1,251 @@
+use crate::auth::{KeyStore, Role};
+use crate::grpc::{
+    pb::{self, verifier_client::VerifierClient},
+    GrpcService, ERROR_CODE_METADATA,
+};
+use crate::rate_limit::RateLimiter;
+use crate::shutdown::Shutdown;
+use crate::test_support::app_state;
+use std::{sync::Arc, time::Duration};
+use tokio::net::TcpListener;
+use tonic::{transport::Channel, Code, Request};
+use zkcg_verifier::backend_stub::StubBackend;
+
+struct Node {
+    client: VerifierClient<Channel>,
+    submitter: String,
+    observer: String,
+    shutdown: Shutdown,
+}
+
+async fn start() -> Node {
+    let state = app_state(Box::new(StubBackend));
+
+    let keys = KeyStore::open(None).unwrap();
+    let (_, submitter) = keys.create(Role::Submitter, "submitter".into()).unwrap();
+    let (_, observer) = keys.create(Role::Observer, "observer".into()).unwrap();
+
+    let shutdown = Shutdown::new();
+    let limiter = Arc::new(RateLimiter::new(100, Duration::from_secs(60)));
+    let app = GrpcService::new(state, keys, shutdown.clone(), limiter).into_router(64 << 10);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client = VerifierClient::connect(format!("http://{}", addr)).await.unwrap();
+    Node {
+        client,
+        submitter,
+        observer,
+        shutdown,
+    }
+}
+
+fn with_key<T>(msg: T, token: &str) -> Request<T> {
+    let mut req = Request::new(msg);
+    req.metadata_mut().insert("x-api-key", token.parse().unwrap());
+    req
+}
+
+fn submission(nonce: u64, old_root: [u8; 32], new_root: [u8; 32]) -> pb::SubmitProofRequest {
+    pb::SubmitProofRequest {
+        proof: vec![1, 2, 3],
+        public_inputs: Some(pb::PublicInputs {
+            threshold: 600,
+            old_state_root: old_root.to_vec(),
+            nonce,
+        }),
+        new_state_commitment: new_root.to_vec(),
+        signature: None,
+    }
+}
+
+#[tokio::test]
+async fn calls_need_a_key_with_the_right_role() {
+    let mut node = start().await;
+
+    let err = node.client.get_state(pb::GetStateRequest {}).await.unwrap_err();
+    assert_eq!(err.code(), Code::Unauthenticated);
+
+    let req = with_key(submission(1, [0; 32], [7; 32]), &node.observer);
+    let err = node.client.submit_proof(req).await.unwrap_err();
+    assert_eq!(err.code(), Code::PermissionDenied);
+
+    let state = node
+        .client
+        .get_state(with_key(pb::GetStateRequest {}, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(state.nonce, 0);
+    assert_eq!(state.state_root, vec![0; 32]);
+}
+
+#[tokio::test]
+async fn accepted_submission_is_streamed_and_visible() {
+    let mut node = start().await;
+
+    let mut stream = node
+        .client
+        .stream_transitions(with_key(pb::StreamTransitionsRequest {}, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let res = node
+        .client
+        .submit_proof(with_key(submission(1, [0; 32], [7; 32]), &node.submitter))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(res.status(), pb::SubmissionStatus::Accepted);
+
+    let transition = tokio::time::timeout(Duration::from_secs(5), stream.message())
+        .await
+        .expect("transition streamed")
+        .unwrap()
+        .unwrap();
+    assert_eq!(transition.before.unwrap().nonce, 0);
+    let after = transition.after.unwrap();
+    assert_eq!(after.nonce, 1);
+    assert_eq!(after.state_root, vec![7; 32]);
+
+    let state = node
+        .client
+        .get_state(with_key(pb::GetStateRequest {}, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(state.nonce, 1);
+}
+
+#[tokio::test]
+async fn rejections_carry_the_spec_error_code() {
+    let mut node = start().await;
+
+    let err = node
+        .client
+        .submit_proof(with_key(submission(1, [9; 32], [7; 32]), &node.submitter))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+    assert_eq!(
+        err.metadata().get(ERROR_CODE_METADATA).unwrap(),
+        "ERR_STATE_MISMATCH"
+    );
+
+    let mut bad = submission(1, [0; 32], [7; 32]);
+    bad.new_state_commitment = vec![7; 5];
+    let err = node
+        .client
+        .submit_proof(with_key(bad, &node.submitter))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::InvalidArgument);
+    assert_eq!(
+        err.metadata().get(ERROR_CODE_METADATA).unwrap(),
+        "ERR_INVALID_FORMAT"
+    );
+}
+
+#[tokio::test]
+async fn verify_leaves_state_untouched() {
+    let mut node = start().await;
+
+    let req = pb::VerifyRequest {
+        proof: vec![1, 2, 3],
+        public_inputs: Some(pb::PublicInputs {
+            threshold: 600,
+            old_state_root: vec![0; 32],
+            nonce: 1,
+        }),
+    };
+    let res = node
+        .client
+        .verify(with_key(req, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(res.valid);
+
+    let state = node
+        .client
+        .get_state(with_key(pb::GetStateRequest {}, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(state.nonce, 0);
+}
+
+#[tokio::test]
+async fn submissions_refused_while_draining() {
+    let mut node = start().await;
+    node.shutdown.trigger();
+
+    let err = node
+        .client
+        .submit_proof(with_key(submission(1, [0; 32], [7; 32]), &node.submitter))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::Unavailable);
+}
+
+#[tokio::test]
+async fn transition_stream_ends_when_draining_starts() {
+    let mut node = start().await;
+
+    let mut stream = node
+        .client
+        .stream_transitions(with_key(pb::StreamTransitionsRequest {}, &node.observer))
+        .await
+        .unwrap()
+        .into_inner();
+    node.shutdown.trigger();
+
+    let end = tokio::time::timeout(Duration::from_secs(5), stream.message())
+        .await
+        .expect("stream ended");
+    assert!(end.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn oversized_messages_are_refused() {
+    let mut node = start().await;
+
+    let mut big = submission(1, [0; 32], [7; 32]);
+    big.proof = vec![0; 128 << 10];
+    let err = node
+        .client
+        .submit_proof(with_key(big, &node.submitter))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Code::OutOfRange);
+}
+
+#[tokio::test]
+async fn resubmission_replays_the_receipt() {
+    let mut node = start().await;
+
+    let mut req = with_key(submission(1, [0; 32], [7; 32]), &node.submitter);
+    req.metadata_mut().insert("idempotency-key", "once".parse().unwrap());
+    let first = node.client.submit_proof(req).await.unwrap().into_inner();
+    assert!(!first.replayed);
+    let receipt = first.receipt.unwrap();
+    assert_eq!(receipt.nonce, 1);
+    assert_eq!(receipt.state_root, vec![7; 32]);
+
+    let again = node
+        .client
+        .submit_proof(with_key(submission(1, [0; 32], [7; 32]), &node.submitter))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(again.replayed);
+    assert_eq!(again.receipt, Some(receipt));
+
+    let mut req = with_key(submission(2, [7; 32], [8; 32]), &node.submitter);
+    req.metadata_mut().insert("idempotency-key", "once".parse().unwrap());
+    let err = node.client.submit_proof(req).await.unwrap_err();
+    assert_eq!(err.code(), Code::AlreadyExists);
+}
@@ -0,0 +1,99 @@
+use crate::health::{healthz, readyz, Readiness};
+use crate::models::ReadinessResponse;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use tower::ServiceExt;
+use zkcg_verifier::selftest::SelfTestReport;
+
+fn app(readiness: Readiness) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .layer(Extension(readiness))
+}
+
+async fn get_readyz(readiness: Readiness) -> (StatusCode, ReadinessResponse) {
+    let res = app(readiness)
+        .oneshot(Request::get("/readyz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap())
+}
+
+fn report(failures: Vec<String>) -> SelfTestReport {
+    SelfTestReport {
+        backend: "zkvm",
+        checked: 3,
+        failures,
+    }
+}
+
+#[tokio::test]
+async fn healthz_is_always_ok() {
+    let res = app(Readiness::new())
+        .oneshot(Request::get("/healthz").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn not_ready_before_self_test() {
+    let (status, body) = get_readyz(Readiness::new()).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!body.ready);
+    assert!(body.backends.is_empty());
+}
+
+#[tokio::test]
+async fn ready_once_self_test_passes() {
+    let readiness = Readiness::new();
+    readiness.record(vec![report(vec![])]);
+
+    let (status, body) = get_readyz(readiness).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.ready);
+    assert_eq!(body.backends[0].backend, "zkvm");
+    assert!(body.backends[0].passed);
+}
+
+#[tokio::test]
+async fn failed_self_test_is_reported() {
+    let readiness = Readiness::new();
+    readiness.record(vec![report(vec![
+        "expected image id: expected accepted, got rejected".to_string(),
+    ])]);
+
+    let (status, body) = get_readyz(readiness.clone()).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(!readiness.is_ready());
+    assert_eq!(body.backends[0].failures.len(), 1);
+}
+
+#[tokio::test]
+async fn backend_that_checked_nothing_is_not_ready() {
+    let skipped = SelfTestReport {
+        backend: "stub",
+        checked: 0,
+        failures: vec![],
+    };
+
+    let readiness = Readiness::new();
+    readiness.record(vec![skipped.clone()]);
+    let (status, body) = get_readyz(readiness).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(body.backends[0].skipped);
+
+    // only with the explicit override
+    let readiness = Readiness::allowing_skipped();
+    readiness.record(vec![skipped]);
+    let (status, body) = get_readyz(readiness).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body.backends[0].passed);
+}
@@ -0,0 +1,93 @@
+use crate::history::TransitionLog;
+use crate::metrics::metrics;
+use std::path::Path;
+use zkcg_common::{state::ProtocolState, types::Commitment, wire::LoggedTransition};
+use zkcg_verifier::{engine::PublicInputs, mempool::AppliedTransition, pipeline::Submission};
+
+fn submission(nonce: u64, proof: &[u8]) -> Submission {
+    Submission {
+        proof_bytes: proof.to_vec(),
+        public_inputs: PublicInputs {
+            threshold: 10,
+            old_state_root: [nonce as u8 - 1; 32],
+            nonce,
+        },
+        commitment: Commitment([nonce as u8; 32]),
+        signature: None,
+    }
+}
+
+fn applied(client: &str, nonce: u64) -> AppliedTransition {
+    let mut after = ProtocolState::genesis();
+    after.nonce = nonce;
+    after.state_root = [nonce as u8; 32];
+    AppliedTransition {
+        client: client.to_string(),
+        submission_id: [nonce as u8; 32],
+        before: ProtocolState::genesis(),
+        after,
+    }
+}
+
+fn read_log(path: &Path) -> Vec<LoggedTransition> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+fn gaps() -> f64 {
+    let rendered = metrics().render();
+    rendered
+        .lines()
+        .find_map(|l| l.strip_prefix("zkcg_transition_log_gaps_total "))
+        .map_or(0.0, |v| v.parse().unwrap())
+}
+
+#[test]
+fn applied_transitions_are_appended_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transitions.jsonl");
+    let log = TransitionLog::open(Some(path.clone()), 0).unwrap();
+
+    // nonce 2 is queued first and released by nonce 1
+    log.hold("b", submission(2, b"p2"));
+    log.record(&applied("a", 1), Some(&submission(1, b"p1")), "stub");
+    log.record(&applied("b", 2), None, "stub");
+    log.sync();
+
+    let logged = read_log(&path);
+    assert_eq!(logged.len(), 2);
+    assert_eq!(logged[0].submission.public_inputs.nonce, 1);
+    assert_eq!(logged[1].submission.public_inputs.nonce, 2);
+    assert_eq!(logged[1].backend, "stub");
+    assert_eq!(logged[1].submission_id, hex::encode(submission(2, b"p2").id()));
+
+    // reopening appends rather than truncating
+    let log = TransitionLog::open(Some(path.clone()), 2).unwrap();
+    log.record(&applied("a", 3), Some(&submission(3, b"p3")), "stub");
+    log.sync();
+    assert_eq!(read_log(&path).len(), 3);
+}
+
+#[test]
+fn missing_and_skipped_transitions_are_counted_as_gaps() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transitions.jsonl");
+    let log = TransitionLog::open(Some(path.clone()), 0).unwrap();
+    let before = gaps();
+
+    // no submission for nonce 1, and nonce 2 never reaches the log
+    log.record(&applied("a", 1), None, "stub");
+    log.record(&applied("a", 3), Some(&submission(3, b"p3")), "stub");
+    log.sync();
+
+    assert_eq!(read_log(&path).len(), 1);
+    assert!(gaps() - before >= 2.0);
+
+    let disabled = TransitionLog::open(None, 0).unwrap();
+    assert!(!disabled.is_enabled());
+    disabled.record(&applied("a", 1), Some(&submission(1, b"p1")), "stub");
+    disabled.sync();
+}
@@ -0,0 +1,292 @@
+use crate::auth::{Identity, Role};
+use crate::handler::{cancel_job, get_job, AppState};
+use crate::jobs::{JobError, JobLimits, JobQueue, JobStatus, WITNESS_LOST};
+use crate::models::{ProveRequest, PublicInputsDto, SubmitProofRequest};
+use crate::test_support::app_state;
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    routing::get,
+    Extension, Router,
+};
+use serde_json::Value;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Semaphore;
+use tower::ServiceExt;
+use zkcg_verifier::backend_stub::StubBackend;
+
+fn limits(max_concurrent: usize, max_queued: usize) -> JobLimits {
+    JobLimits {
+        max_concurrent,
+        max_queued,
+        ..JobLimits::default()
+    }
+}
+
+fn request(secret_value: u64) -> ProveRequest {
+    ProveRequest {
+        secret_value,
+        threshold: 10,
+    }
+}
+
+fn proved(req: &ProveRequest) -> SubmitProofRequest {
+    SubmitProofRequest {
+        proof: req.secret_value.to_string(),
+        public_inputs: PublicInputsDto {
+            threshold: req.threshold,
+            old_state_root: [0; 32],
+            nonce: 1,
+        },
+        new_state_commitment: [7; 32],
+        signature: None,
+    }
+}
+
+/// Stand-in prover that records what it was asked to prove and finishes
+/// one proof per permit added to `gate`.
+#[derive(Clone)]
+struct Prover {
+    gate: Arc<Semaphore>,
+    started: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Prover {
+    fn new() -> Self {
+        Self {
+            gate: Arc::new(Semaphore::new(0)),
+            started: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn spawn(&self, queue: &JobQueue) {
+        let prover = self.clone();
+        tokio::spawn(queue.clone().run(move |req: ProveRequest| {
+            let prover = prover.clone();
+            async move {
+                prover.started.lock().unwrap().push(req.secret_value);
+                prover.gate.acquire().await.unwrap().forget();
+                if req.secret_value == 0 {
+                    Err("policy violation".to_string())
+                } else {
+                    Ok(proved(&req))
+                }
+            }
+        }));
+    }
+}
+
+async fn wait_for(queue: &JobQueue, id: &str, status: JobStatus) {
+    for _ in 0..200 {
+        if queue.get("alice", id).unwrap().status == status {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    panic!("job {} never reached {:?}", id, status);
+}
+
+#[tokio::test]
+async fn jobs_run_in_order_within_the_concurrency_limit() {
+    let queue = JobQueue::open(None, limits(1, 8)).unwrap();
+    let first = queue.submit("alice", request(1)).unwrap();
+    let second = queue.submit("alice", request(2)).unwrap();
+    assert_eq!(first.status, JobStatus::Queued);
+
+    let prover = Prover::new();
+    prover.spawn(&queue);
+
+    wait_for(&queue, &first.id, JobStatus::Running).await;
+    assert_eq!(queue.get("alice", &second.id).unwrap().status, JobStatus::Queued);
+
+    prover.gate.add_permits(1);
+    wait_for(&queue, &first.id, JobStatus::Succeeded).await;
+    wait_for(&queue, &second.id, JobStatus::Running).await;
+    prover.gate.add_permits(1);
+    wait_for(&queue, &second.id, JobStatus::Succeeded).await;
+
+    assert_eq!(*prover.started.lock().unwrap(), vec![1, 2]);
+    let done = queue.get("alice", &first.id).unwrap();
+    assert_eq!(done.result.unwrap().proof, "1");
+    assert!(done.started_at.is_some() && done.finished_at.is_some());
+}
+
+#[tokio::test]
+async fn failed_proof_is_reported_on_the_job() {
+    let queue = JobQueue::open(None, limits(2, 8)).unwrap();
+    let job = queue.submit("alice", request(0)).unwrap();
+
+    let prover = Prover::new();
+    prover.gate.add_permits(1);
+    prover.spawn(&queue);
+
+    wait_for(&queue, &job.id, JobStatus::Failed).await;
+    let failed = queue.get("alice", &job.id).unwrap();
+    assert_eq!(failed.error.as_deref(), Some("policy violation"));
+    assert!(failed.result.is_none());
+}
+
+#[tokio::test]
+async fn cancelled_jobs_are_never_proved_or_discard_their_result() {
+    let queue = JobQueue::open(None, limits(1, 8)).unwrap();
+    let running = queue.submit("alice", request(1)).unwrap();
+    let queued = queue.submit("alice", request(2)).unwrap();
+    let last = queue.submit("alice", request(3)).unwrap();
+
+    let prover = Prover::new();
+    prover.spawn(&queue);
+    wait_for(&queue, &running.id, JobStatus::Running).await;
+
+    assert_eq!(queue.cancel("alice", &queued.id).unwrap().status, JobStatus::Cancelled);
+    assert_eq!(queue.cancel("alice", &running.id).unwrap().status, JobStatus::Cancelled);
+    assert_eq!(queue.cancel("alice", &running.id).unwrap_err(), JobError::Finished);
+
+    // the cancelled proof still holds the only slot until it finishes
+    assert_eq!(queue.get("alice", &last.id).unwrap().status, JobStatus::Queued);
+    prover.gate.add_permits(2);
+    wait_for(&queue, &last.id, JobStatus::Succeeded).await;
+
+    assert_eq!(*prover.started.lock().unwrap(), vec![1, 3]);
+    assert!(queue.get("alice", &running.id).unwrap().result.is_none());
+}
+
+#[test]
+fn queue_is_bounded_and_scoped_to_its_client() {
+    let queue = JobQueue::open(None, limits(1, 2)).unwrap();
+    let job = queue.submit("alice", request(1)).unwrap();
+    queue.submit("alice", request(2)).unwrap();
+
+    assert_eq!(queue.submit("bob", request(3)).unwrap_err(), JobError::QueueFull);
+    assert!(queue.get("bob", &job.id).is_none());
+    assert_eq!(queue.cancel("bob", &job.id).unwrap_err(), JobError::NotFound);
+
+    // a cancelled job frees its place in the queue
+    queue.cancel("alice", &job.id).unwrap();
+    assert!(queue.submit("bob", request(3)).is_ok());
+}
+
+#[tokio::test]
+async fn jobs_survive_a_restart_but_their_witnesses_do_not() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("jobs.json");
+    let queue = JobQueue::open(Some(path.clone()), limits(1, 8)).unwrap();
+    let done = queue.submit("alice", request(111)).unwrap();
+    let interrupted = queue.submit("alice", request(222)).unwrap();
+    let waiting = queue.submit("alice", request(333)).unwrap();
+
+    let prover = Prover::new();
+    prover.gate.add_permits(1);
+    prover.spawn(&queue);
+    wait_for(&queue, &done.id, JobStatus::Succeeded).await;
+    wait_for(&queue, &interrupted.id, JobStatus::Running).await;
+    queue.flush().unwrap();
+
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(!raw.contains("secret_value"));
+
+    // unfinished jobs cannot be proved without their witness
+    let reopened = JobQueue::open(Some(path.clone()), limits(1, 8)).unwrap();
+    assert_eq!(reopened.get("alice", &done.id).unwrap().result.unwrap().proof, "111");
+    for id in [&interrupted.id, &waiting.id] {
+        let job = reopened.get("alice", id).unwrap();
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some(WITNESS_LOST));
+    }
+
+    let prover = Prover::new();
+    prover.gate.add_permits(1);
+    prover.spawn(&reopened);
+    let next = reopened.submit("alice", request(444)).unwrap();
+    wait_for(&reopened, &next.id, JobStatus::Succeeded).await;
+    assert_eq!(*prover.started.lock().unwrap(), vec![444]);
+}
+
+#[tokio::test]
+async fn changes_are_written_off_the_request_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("jobs.json");
+    let queue = JobQueue::open(Some(path.clone()), limits(1, 8)).unwrap();
+
+    // submitting only marks the table changed
+    let job = queue.submit("alice", request(1)).unwrap();
+    assert!(!path.exists());
+
+    tokio::spawn(queue.clone().persist_loop());
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !std::fs::read_to_string(&path).is_ok_and(|raw| raw.contains(&job.id)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("job table written");
+}
+
+#[test]
+fn oldest_finished_jobs_are_dropped_beyond_retain() {
+    let queue = JobQueue::open(
+        None,
+        JobLimits {
+            retain: 1,
+            ..limits(1, 8)
+        },
+    )
+    .unwrap();
+    let first = queue.submit("alice", request(1)).unwrap();
+    let second = queue.submit("alice", request(2)).unwrap();
+    let pending = queue.submit("alice", request(3)).unwrap();
+
+    queue.cancel("alice", &first.id).unwrap();
+    queue.cancel("alice", &second.id).unwrap();
+
+    assert!(queue.get("alice", &first.id).is_none());
+    assert!(queue.get("alice", &second.id).is_some());
+    assert!(queue.get("alice", &pending.id).is_some());
+}
+
+fn app(state: AppState, key_id: &str) -> Router {
+    Router::new()
+        .route("/v1/jobs/:id", get(get_job).delete(cancel_job))
+        .layer(Extension(state))
+        .layer(Extension(Identity {
+            key_id: key_id.into(),
+            role: Role::Submitter,
+        }))
+}
+
+async fn call(state: &AppState, key_id: &str, method: &str, id: &str) -> (StatusCode, Value) {
+    let req = Request::builder()
+        .method(method)
+        .uri(format!("/v1/jobs/{}", id))
+        .body(Body::empty())
+        .unwrap();
+    let res = app(state.clone(), key_id).oneshot(req).await.unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+    (status, body)
+}
+
+#[tokio::test]
+async fn job_routes_poll_and_cancel_by_id() {
+    let state = app_state(Box::new(StubBackend));
+    let job = state.jobs.submit("alice", request(1)).unwrap();
+
+    let (status, body) = call(&state, "alice", "GET", &job.id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "queued");
+    assert!(body.get("secret_value").is_none());
+
+    let (status, _) = call(&state, "bob", "GET", &job.id).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = call(&state, "alice", "DELETE", &job.id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "cancelled");
+
+    let (status, _) = call(&state, "alice", "DELETE", &job.id).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
@@ -0,0 +1,106 @@
+use crate::auth::{Identity, Role};
+use crate::config::BodyLimitConfig;
+use crate::handler::{submit_proof, AppState};
+use crate::test_support::app_state;
+use axum::{
+    body::{to_bytes, Body},
+    extract::DefaultBodyLimit,
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    routing::post,
+    Extension, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tower::ServiceExt;
+use zkcg_verifier::backend::WithMaxProofSize;
+use zkcg_verifier::backend_stub::StubBackend;
+
+const MAX_PROOF: usize = 64;
+
+fn node() -> AppState {
+    app_state(Box::new(WithMaxProofSize::new(Box::new(StubBackend), MAX_PROOF)))
+}
+
+fn app(state: AppState, body_limit: usize) -> Router {
+    Router::new()
+        .route(
+            "/v1/submit-proof",
+            post(submit_proof).route_layer(DefaultBodyLimit::max(body_limit)),
+        )
+        .layer(Extension(state))
+        .layer(Extension(Identity {
+            key_id: "test".into(),
+            role: Role::Submitter,
+        }))
+}
+
+fn submission(proof: &[u8]) -> String {
+    let (old_root, new_root) = ([0u8; 32], [7u8; 32]);
+    serde_json::json!({
+        "proof": STANDARD.encode(proof),
+        "public_inputs": { "threshold": 10, "old_state_root": old_root, "nonce": 1 },
+        "new_state_commitment": new_root,
+    })
+    .to_string()
+}
+
+async fn post_json(app: Router, body: String) -> (StatusCode, String) {
+    let req = Request::post("/v1/submit-proof")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap();
+    let res = app.oneshot(req).await.unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn proof_within_limits_is_accepted() {
+    let state = node();
+    let (status, _) = post_json(app(state.clone(), 4096), submission(&[1; MAX_PROOF])).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(state.engine.lock().unwrap().state().nonce, 1);
+}
+
+#[tokio::test]
+async fn body_over_route_limit_is_413() {
+    let state = node();
+    let (status, _) = post_json(app(state.clone(), 256), submission(&[1; 1024])).await;
+
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(state.engine.lock().unwrap().state().nonce, 0);
+}
+
+#[tokio::test]
+async fn proof_over_backend_cap_is_invalid_format() {
+    let state = node();
+    let (status, body) = post_json(app(state.clone(), 4096), submission(&[1; MAX_PROOF + 1])).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, "invalid message format");
+    assert_eq!(state.engine.lock().unwrap().state().nonce, 0);
+}
+
+#[tokio::test]
+async fn malformed_inputs_are_invalid_format() {
+    let (status, body) = post_json(app(node(), 4096), "{\"proof\": 5}".into()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.starts_with("invalid message format"), "{}", body);
+
+    let bad_base64 = submission(&[1; 8]).replace("AQEBAQEBAQE=", "!!not-base64!!");
+    let (status, body) = post_json(app(node(), 4096), bad_base64).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body, "invalid message format");
+
+    let (status, _) = post_json(app(node(), 4096), submission(&[])).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[test]
+fn proof_body_limit_covers_the_base64_proof() {
+    let limit = BodyLimitConfig::for_proof(None, 3 << 20);
+    assert!(limit > (4 << 20));
+
+    assert_eq!(BodyLimitConfig::for_proof(Some(1000), 3 << 20), 1000);
+}
@@ -0,0 +1,28 @@
+use crate::metrics::Metrics;
+use std::time::Duration;
+use zkcg_common::{errors::ProtocolError, state::ProtocolState};
+
+#[test]
+fn exposition_includes_recorded_series() {
+    // a registry of its own: other tests update the global one concurrently
+    let m = Metrics::new().unwrap();
+    m.record_outcome("accepted");
+    m.record_rejection(&ProtocolError::InvalidNonce);
+    m.observe_verify("stub", Duration::from_millis(3));
+    m.observe_proof_size(1024);
+    m.record_rate_limited("/v1/submit-proof");
+    m.set_state(&ProtocolState {
+        nonce: 7,
+        epoch: 2,
+        ..ProtocolState::genesis()
+    });
+
+    let text = m.render();
+    assert!(text.contains(r#"zkcg_transitions_total{result="accepted"}"#));
+    assert!(text.contains(r#"zkcg_transitions_total{result="ERR_NONCE_INVALID"}"#));
+    assert!(text.contains(r#"zkcg_verify_duration_seconds_count{backend="stub"}"#));
+    assert!(text.contains("zkcg_proof_size_bytes_count"));
+    assert!(text.contains(r#"zkcg_rate_limited_total{route="/v1/submit-proof"}"#));
+    assert!(text.contains("zkcg_state_nonce 7"));
+    assert!(text.contains("zkcg_state_epoch 2"));
+}
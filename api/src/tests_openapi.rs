@@ -0,0 +1,142 @@
+use crate::config::NodeConfig;
+use crate::openapi::{openapi_json, ApiDoc};
+use crate::routes::{http_routes, RouteLimiters, RouteTable};
+use axum::{
+    body::{to_bytes, Body},
+    http::{Request, StatusCode},
+    routing::get,
+    Router,
+};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use tower::ServiceExt;
+use utoipa::OpenApi;
+
+fn spec() -> Value {
+    serde_json::to_value(ApiDoc::openapi()).unwrap()
+}
+
+fn collect_refs<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(r)) = map.get("$ref") {
+                out.push(r);
+            }
+            map.values().for_each(|v| collect_refs(v, out));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, out)),
+        _ => {}
+    }
+}
+
+/// Every route the node can serve, as the listener would with all of them enabled.
+fn all_routes() -> RouteTable {
+    let mut config = NodeConfig::default();
+    config.routes.demo = true;
+    config.routes.metrics = true;
+    config.routes.protocol = true;
+    http_routes(&config, 1 << 20, &RouteLimiters::from_config(&config.rate_limits))
+}
+
+#[test]
+fn documented_operations_are_the_served_routes() {
+    let spec = spec();
+    let documented: BTreeSet<(String, String)> = spec["paths"]
+        .as_object()
+        .unwrap()
+        .iter()
+        .flat_map(|(path, ops)| {
+            ops.as_object().unwrap().keys().map(move |method| (method.to_uppercase(), path.clone()))
+        })
+        .collect();
+
+    let routes = all_routes();
+    let served: BTreeSet<(String, String)> = routes
+        .served()
+        .iter()
+        .map(|(method, path)| (method.to_string(), path.clone()))
+        .collect();
+    assert_eq!(served.len(), routes.served().len(), "a route is registered twice");
+
+    let undocumented: Vec<_> = served.difference(&documented).collect();
+    assert!(undocumented.is_empty(), "served but missing from ApiDoc: {:?}", undocumented);
+    let unserved: Vec<_> = documented.difference(&served).collect();
+    assert!(unserved.is_empty(), "in ApiDoc but not served: {:?}", unserved);
+}
+
+#[tokio::test]
+async fn served_routes_answer_their_method() {
+    let routes = all_routes();
+    let served = routes.served().to_vec();
+    let app = routes.into_router();
+
+    for (method, path) in served {
+        let uri = path.replace(['{', '}'], "");
+        let res = app
+            .clone()
+            .oneshot(Request::builder().method(method.clone()).uri(&uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(
+            !matches!(res.status(), StatusCode::NOT_FOUND | StatusCode::METHOD_NOT_ALLOWED),
+            "{} {} is not routed ({})",
+            method,
+            path,
+            res.status()
+        );
+    }
+}
+
+#[test]
+fn schema_references_resolve() {
+    let spec = spec();
+    let schemas = spec["components"]["schemas"].as_object().unwrap();
+
+    let mut refs = Vec::new();
+    collect_refs(&spec, &mut refs);
+    assert!(!refs.is_empty());
+
+    for r in refs {
+        let name = r.strip_prefix("#/components/schemas/").expect("local schema ref");
+        assert!(schemas.contains_key(name), "dangling reference {}", r);
+    }
+}
+
+#[test]
+fn protocol_routes_require_a_key() {
+    let spec = spec();
+
+    let submit = &spec["paths"]["/v1/submit-proof"]["post"];
+    assert!(submit["security"].as_array().is_some_and(|s| !s.is_empty()));
+    assert!(spec["paths"]["/demo/verify"]["post"].get("security").is_none());
+
+    let schemes = &spec["components"]["securitySchemes"];
+    assert_eq!(schemes["api_key"]["name"], "x-api-key");
+    assert_eq!(schemes["bearer"]["scheme"], "bearer");
+}
+
+#[tokio::test]
+async fn served_as_json() {
+    let app = Router::new().route("/openapi.json", get(openapi_json));
+
+    let res = app
+        .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let body: Value = serde_json::from_slice(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert!(body["openapi"].as_str().unwrap().starts_with("3."));
+    assert_eq!(body, spec());
+}
+
+#[test]
+fn hashes_are_documented_as_byte_arrays() {
+    let spec = spec();
+    let root = &spec["components"]["schemas"]["PublicInputsDto"]["properties"]["old_state_root"];
+
+    // serde writes `[u8; 32]` as a JSON array, not a string
+    assert_eq!(root["type"], "array");
+    assert_eq!(root["minItems"], 32);
+    assert_eq!(root["maxItems"], 32);
+}
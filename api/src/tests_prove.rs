@@ -0,0 +1,86 @@
+use crate::commitment::next_state_commitment;
+use crate::handler::prove_next;
+use crate::jobs::JobStatus;
+use crate::models::{ProveRequest, SubmitProofRequest};
+use crate::test_support::app_state;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use zkcg_common::types::Commitment;
+use zkcg_verifier::backend_stub::StubBackend;
+use zkcg_verifier::engine::PublicInputs;
+
+#[tokio::test]
+async fn proof_is_bound_to_the_current_state() {
+    let state = app_state(Box::new(StubBackend));
+    state
+        .engine
+        .lock()
+        .unwrap()
+        .process_transition(
+            b"proof",
+            PublicInputs {
+                threshold: 10,
+                old_state_root: [0; 32],
+                nonce: 1,
+            },
+            Commitment([7; 32]),
+        )
+        .unwrap();
+
+    let request = prove_next(&state, ProveRequest { secret_value: 300, threshold: 600 })
+        .await
+        .unwrap();
+
+    assert_eq!(request.public_inputs.old_state_root, [7; 32]);
+    assert_eq!(request.public_inputs.nonce, 2);
+    assert_eq!(request.public_inputs.threshold, 600);
+
+    let proof = STANDARD.decode(&request.proof).unwrap();
+    let expected = next_state_commitment(&[7; 32], 2, 600, &proof);
+    assert_eq!(request.new_state_commitment, expected.0);
+}
+
+#[tokio::test]
+async fn response_is_a_ready_to_submit_request() {
+    let state = app_state(Box::new(StubBackend));
+
+    let request = prove_next(&state, ProveRequest { secret_value: 1, threshold: 600 })
+        .await
+        .unwrap();
+
+    let json = serde_json::to_string(&request).unwrap();
+    let parsed: SubmitProofRequest = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.public_inputs.nonce, 1);
+    assert_eq!(parsed.new_state_commitment, request.new_state_commitment);
+    assert!(!json.contains("signature"));
+}
+
+#[tokio::test]
+async fn queued_job_is_proved_against_the_state_when_it_starts() {
+    let state = app_state(Box::new(StubBackend));
+    let job = state
+        .jobs
+        .submit("alice", ProveRequest { secret_value: 300, threshold: 600 })
+        .unwrap();
+
+    let runner = state.clone();
+    tokio::spawn(state.jobs.clone().run(move |req| {
+        let state = runner.clone();
+        async move { prove_next(&state, req).await.map_err(|(_, message)| message) }
+    }));
+
+    let mut finished = None;
+    for _ in 0..200 {
+        let current = state.jobs.get("alice", &job.id).unwrap();
+        if current.status.is_finished() {
+            finished = Some(current);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+
+    let finished = finished.expect("job never finished");
+    assert_eq!(finished.status, JobStatus::Succeeded);
+    let request = finished.result.unwrap();
+    assert_eq!(request.public_inputs.nonce, 1);
+    assert_eq!(request.public_inputs.threshold, 600);
+}
@@ -0,0 +1,88 @@
+use crate::rate_limit::{parse_limit, RateLimitKey, RateLimiter};
+use std::time::{Duration, Instant};
+
+fn key(name: &str) -> RateLimitKey {
+    RateLimitKey(name.to_string())
+}
+
+#[test]
+fn burst_is_limited_per_key() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+    let now = Instant::now();
+
+    assert!(limiter.check_at(&key("a"), now).allowed);
+    assert!(limiter.check_at(&key("a"), now).allowed);
+
+    let denied = limiter.check_at(&key("a"), now);
+    assert!(!denied.allowed);
+    assert_eq!(denied.remaining, 0);
+    assert!(denied.retry_after > Duration::from_secs(29));
+
+    // another client is unaffected
+    assert!(limiter.check_at(&key("b"), now).allowed);
+}
+
+#[test]
+fn tokens_refill_over_time() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+    let now = Instant::now();
+
+    limiter.check_at(&key("a"), now);
+    limiter.check_at(&key("a"), now);
+    assert!(!limiter.check_at(&key("a"), now).allowed);
+
+    // one token every 30s
+    let later = now + Duration::from_secs(30);
+    assert!(limiter.check_at(&key("a"), later).allowed);
+    assert!(!limiter.check_at(&key("a"), later).allowed);
+}
+
+#[test]
+fn key_table_is_bounded() {
+    let limiter = RateLimiter::new(5, Duration::from_secs(60)).with_max_keys(3);
+    let now = Instant::now();
+
+    for i in 0..10 {
+        limiter.check_at(&key(&format!("client-{}", i)), now + Duration::from_millis(i));
+    }
+
+    assert_eq!(limiter.tracked_keys(), 3);
+}
+
+#[test]
+fn idle_buckets_are_evicted_before_active_ones() {
+    let limiter = RateLimiter::new(1, Duration::from_secs(60)).with_max_keys(2);
+    let now = Instant::now();
+
+    // "idle" is fully refilled by the time the table fills up
+    limiter.check_at(&key("idle"), now);
+    limiter.check_at(&key("busy"), now + Duration::from_secs(59));
+    limiter.check_at(&key("new"), now + Duration::from_secs(61));
+
+    // "busy" kept its drained bucket
+    assert!(!limiter.check_at(&key("busy"), now + Duration::from_secs(61)).allowed);
+}
+
+#[test]
+fn bucket_closest_to_full_is_evicted_when_none_is_idle() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60)).with_max_keys(2);
+    let now = Instant::now();
+
+    // "light" is full again 30s in, "heavy" only after 60s
+    limiter.check_at(&key("light"), now);
+    limiter.check_at(&key("heavy"), now);
+    limiter.check_at(&key("heavy"), now);
+    limiter.check_at(&key("new"), now + Duration::from_secs(10));
+
+    assert_eq!(limiter.tracked_keys(), 2);
+    assert!(!limiter.check_at(&key("heavy"), now + Duration::from_secs(10)).allowed);
+}
+
+#[test]
+fn limit_spec_parsing() {
+    assert_eq!(parse_limit("30/60"), Some((30, Duration::from_secs(60))));
+    assert_eq!(parse_limit(" 5 / 10s "), Some((5, Duration::from_secs(10))));
+    assert_eq!(parse_limit("0/60"), None);
+    assert_eq!(parse_limit("thirty/60"), None);
+    assert_eq!(parse_limit("30"), None);
+}
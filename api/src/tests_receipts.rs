@@ -0,0 +1,425 @@
+use crate::auth::{Identity, Role};
+use crate::handler::{get_state, get_submission, submit_proof, AppState};
+use crate::history::TransitionLog;
+use crate::receipts::{KeyConflict, ReceiptStore};
+use crate::test_support::app_state;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    routing::{get, post},
+    Extension, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::Value;
+use tower::ServiceExt;
+use zkcg_common::{state::ProtocolState, wire::LoggedTransition};
+use zkcg_verifier::backend_stub::StubBackend;
+use zkcg_verifier::engine::VerifierEngine;
+use zkcg_verifier::mempool::MempoolConfig;
+use zkcg_verifier::pipeline::Submission;
+use zkcg_verifier::storage::StateStore;
+
+fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/submit-proof", post(submit_proof))
+        .route("/v1/state", get(get_state))
+        .route("/v1/submissions/:id", get(get_submission))
+        .layer(Extension(state))
+        .layer(Extension(Identity {
+            key_id: "test".into(),
+            role: Role::Submitter,
+        }))
+}
+
+fn submission(nonce: u64, old_root: [u8; 32], new_root: [u8; 32]) -> String {
+    serde_json::json!({
+        "proof": STANDARD.encode([1, 2, 3]),
+        "public_inputs": { "threshold": 10, "old_state_root": old_root, "nonce": nonce },
+        "new_state_commitment": new_root,
+    })
+    .to_string()
+}
+
+async fn submit(state: &AppState, body: String, key: Option<&str>) -> (StatusCode, Value) {
+    let mut req = Request::post("/v1/submit-proof").header(CONTENT_TYPE, "application/json");
+    if let Some(key) = key {
+        req = req.header("Idempotency-Key", key);
+    }
+    let res = app(state.clone())
+        .oneshot(req.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+    (status, body)
+}
+
+fn submission_id(body: &str) -> String {
+    let wire = serde_json::from_str(body).unwrap();
+    hex::encode(Submission::from_wire(&wire, usize::MAX).unwrap().id())
+}
+
+async fn submission_status(state: &AppState, id: &str) -> (StatusCode, Value) {
+    let res = app(state.clone())
+        .oneshot(Request::get(format!("/v1/submissions/{}", id)).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn state_at(nonce: u64, root: u8) -> ProtocolState {
+    let mut state = ProtocolState::genesis();
+    state.nonce = nonce;
+    state.state_root = [root; 32];
+    state
+}
+
+#[tokio::test]
+async fn resubmission_replays_the_original_receipt() {
+    let state = app_state(Box::new(StubBackend));
+
+    let (status, first) = submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(first["status"], "accepted");
+    assert_eq!(first["replayed"], false);
+    assert_eq!(first["receipt"]["nonce"], 1);
+
+    // a retry after the state moved on would otherwise be a stale nonce
+    let (status, again) = submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again["replayed"], true);
+    assert_eq!(again["receipt"], first["receipt"]);
+    assert_eq!(state.engine.lock().unwrap().state().nonce, 1);
+}
+
+#[tokio::test]
+async fn queued_resubmission_is_not_verified_twice() {
+    let state = app_state(Box::new(StubBackend));
+    {
+        let mut engine = state.engine.lock().unwrap();
+        let genesis = engine.state().clone();
+        *engine = VerifierEngine::new(genesis, Box::new(StubBackend))
+            .with_mempool(MempoolConfig::default());
+    }
+
+    let (_, first) = submit(&state, submission(2, [7; 32], [8; 32]), None).await;
+    assert_eq!(first["status"], "queued");
+    assert!(first.get("receipt").is_none());
+
+    let (status, again) = submit(&state, submission(2, [7; 32], [8; 32]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again["status"], "queued");
+    assert_eq!(again["replayed"], true);
+
+    // once released, the queued submission has a receipt of its own
+    submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    let (_, released) = submit(&state, submission(2, [7; 32], [8; 32]), None).await;
+    assert_eq!(released["status"], "accepted");
+    assert_eq!(released["receipt"]["nonce"], 2);
+}
+
+#[tokio::test]
+async fn dropped_queued_submission_reports_why() {
+    let state = app_state(Box::new(StubBackend));
+    {
+        let mut engine = state.engine.lock().unwrap();
+        let genesis = engine.state().clone();
+        *engine = VerifierEngine::new(genesis, Box::new(StubBackend))
+            .with_mempool(MempoolConfig::default());
+    }
+
+    // queued against a root nonce 1 will not produce
+    let forked = submission(2, [9; 32], [8; 32]);
+    let forked_id = submission_id(&forked);
+    let (_, queued) = submit(&state, forked, None).await;
+    assert_eq!(queued["status"], "queued");
+    let (status, body) = submission_status(&state, &forked_id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "queued");
+
+    let first = submission(1, [0; 32], [7; 32]);
+    let first_id = submission_id(&first);
+    submit(&state, first, None).await;
+
+    let (status, body) = submission_status(&state, &forked_id).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "dropped");
+    assert_eq!(body["reason"], "stale");
+    assert!(body.get("receipt").is_none());
+
+    let (_, body) = submission_status(&state, &first_id).await;
+    assert_eq!(body["status"], "accepted");
+    assert_eq!(body["receipt"]["nonce"], 1);
+
+    let (status, _) = submission_status(&state, &hex::encode([5u8; 32])).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = submission_status(&state, "not-hex").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn idempotency_key_is_bound_to_its_first_submission() {
+    let state = app_state(Box::new(StubBackend));
+
+    let (status, _) = submit(&state, submission(1, [0; 32], [7; 32]), Some("retry-1")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, again) = submit(&state, submission(1, [0; 32], [7; 32]), Some("retry-1")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(again["replayed"], true);
+
+    let (status, body) = submit(&state, submission(2, [7; 32], [8; 32]), Some("retry-1")).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body, "idempotency key already used for a different submission");
+    assert_eq!(state.engine.lock().unwrap().state().nonce, 1);
+
+    let (status, _) = submit(&state, submission(2, [7; 32], [8; 32]), Some("bad key")).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn accepted_submissions_are_logged_in_applied_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("transitions.jsonl");
+
+    let mut state = app_state(Box::new(StubBackend));
+    state.history = TransitionLog::open(Some(path.clone()), 0).unwrap();
+    {
+        let mut engine = state.engine.lock().unwrap();
+        let genesis = engine.state().clone();
+        *engine = VerifierEngine::new(genesis, Box::new(StubBackend))
+            .with_mempool(MempoolConfig::default());
+    }
+
+    submit(&state, submission(2, [7; 32], [8; 32]), None).await;
+    submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    // a replayed resubmission is not logged again
+    submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    state.history.sync();
+
+    let logged: Vec<LoggedTransition> = std::fs::read_to_string(&path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let nonces: Vec<_> = logged.iter().map(|t| t.submission.public_inputs.nonce).collect();
+    assert_eq!(nonces, vec![1, 2]);
+    assert_eq!(logged[0].backend, "stub");
+    for t in &logged {
+        let decoded = Submission::from_wire(&t.submission, usize::MAX).unwrap();
+        assert_eq!(t.submission_id, hex::encode(decoded.id()));
+    }
+}
+
+#[tokio::test]
+async fn rejections_carry_their_error_code() {
+    let state = app_state(Box::new(StubBackend));
+    let post = |body: String, key: &str| {
+        Request::post("/v1/submit-proof")
+            .header(CONTENT_TYPE, "application/json")
+            .header("Idempotency-Key", key)
+            .body(Body::from(body))
+            .unwrap()
+    };
+
+    let res = app(state.clone())
+        .oneshot(post(submission(1, [9; 32], [7; 32]), "k1"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    assert_eq!(res.headers()["x-zkcg-error-code"], "ERR_STATE_MISMATCH");
+
+    // not a protocol rejection, so no code
+    let res = app(state)
+        .oneshot(post(submission(1, [0; 32], [7; 32]), "bad key"))
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.headers().get("x-zkcg-error-code").is_none());
+}
+
+#[tokio::test]
+async fn state_endpoint_reports_the_accepted_state() {
+    let state = app_state(Box::new(StubBackend));
+    submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+
+    let res = app(state.clone())
+        .oneshot(Request::get("/v1/state").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(res.into_body(), usize::MAX).await.unwrap()).unwrap();
+
+    let root = [7u8; 32];
+    assert_eq!(body["state_root"], serde_json::json!(root));
+    assert_eq!(body["nonce"], 1);
+    assert_eq!(body["provers"], serde_json::json!([]));
+}
+
+#[test]
+fn receipts_survive_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("receipts.json");
+
+    let store = ReceiptStore::open(Some(path.clone()), 16).unwrap();
+    let record = store.accepted("a", &state_at(1, 7), &[1; 32]);
+    assert_eq!(store.reserve_key("a", "k", &[1; 32]), Ok(true));
+    store.flush().unwrap();
+
+    let reopened = ReceiptStore::open(Some(path), 16).unwrap();
+    assert_eq!(reopened.get(&[1; 32]), Some(record));
+    assert_eq!(reopened.key_lookup("a", "k"), Some([1; 32]));
+    assert_eq!(reopened.key_lookup("b", "k"), None);
+}
+
+#[test]
+fn idempotency_keys_are_reserved_once() {
+    let store = ReceiptStore::open(None, 16).unwrap();
+
+    assert_eq!(store.reserve_key("a", "k", &[1; 32]), Ok(true));
+    assert_eq!(store.reserve_key("a", "k", &[1; 32]), Ok(false));
+    assert_eq!(store.reserve_key("a", "k", &[2; 32]), Err(KeyConflict));
+    assert_eq!(store.reserve_key("b", "k", &[2; 32]), Ok(true));
+
+    // a submission that was not admitted hands its key back
+    store.release_key("a", "k", &[1; 32]);
+    assert_eq!(store.reserve_key("a", "k", &[2; 32]), Ok(true));
+
+    // but not once it is queued or accepted
+    store.mark_queued("a", 2, &[2; 32]);
+    store.release_key("a", "k", &[2; 32]);
+    assert_eq!(store.key_lookup("a", "k"), Some([2; 32]));
+}
+
+#[test]
+fn queued_ids_are_dropped_once_their_nonce_is_passed() {
+    let store = ReceiptStore::open(None, 16).unwrap();
+    store.mark_queued("a", 2, &[2; 32]);
+    store.mark_queued("b", 3, &[3; 32]);
+    store.mark_queued("c", 5, &[5; 32]);
+
+    // "b" at nonce 3 is stale once the state reaches 3 through another client
+    store.accepted("a", &state_at(3, 3), &[9; 32]);
+    assert!(!store.is_queued(&[2; 32]));
+    assert!(!store.is_queued(&[3; 32]));
+    assert!(store.is_queued(&[5; 32]));
+
+    let released = store.accepted("c", &state_at(5, 5), &[5; 32]);
+    assert_eq!(released.submission_id, hex::encode([5; 32]));
+    assert!(!store.is_queued(&[5; 32]));
+}
+
+#[tokio::test]
+async fn competing_submissions_from_one_client_keep_their_own_ids() {
+    let state = app_state(Box::new(StubBackend));
+    {
+        let mut engine = state.engine.lock().unwrap();
+        let genesis = engine.state().clone();
+        *engine = VerifierEngine::new(genesis, Box::new(StubBackend))
+            .with_mempool(MempoolConfig::default());
+    }
+
+    let first = submission(2, [7; 32], [8; 32]);
+    let second = submission(2, [7; 32], [9; 32]);
+    let (first_id, second_id) = (submission_id(&first), submission_id(&second));
+    assert_eq!(submit(&state, first, None).await.1["status"], "queued");
+    assert_eq!(submit(&state, second, None).await.1["status"], "queued");
+    assert_eq!(submission_status(&state, &first_id).await.1["status"], "queued");
+    assert_eq!(submission_status(&state, &second_id).await.1["status"], "queued");
+
+    // the mempool applies the first one queued and supersedes the other
+    submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    let (_, body) = submission_status(&state, &first_id).await;
+    assert_eq!(body["status"], "accepted");
+    assert_eq!(body["receipt"]["submission_id"], first_id);
+    let (_, body) = submission_status(&state, &second_id).await;
+    assert_eq!(body["status"], "dropped");
+    assert_eq!(body["reason"], "superseded");
+
+    // a resubmission of the superseded one is not answered as a replay
+    let (status, _) = submit(&state, submission(2, [7; 32], [9; 32]), None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn rejected_submission_does_not_keep_its_idempotency_key() {
+    let state = app_state(Box::new(StubBackend));
+
+    let (status, _) = submit(&state, submission(1, [9; 32], [7; 32]), Some("k")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, body) = submit(&state, submission(1, [0; 32], [7; 32]), Some("k")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "accepted");
+}
+
+#[test]
+fn oldest_receipts_are_evicted_at_capacity() {
+    let store = ReceiptStore::open(None, 2).unwrap();
+    for n in 1..=3u8 {
+        store.accepted("a", &state_at(n as u64, n), &[n; 32]);
+    }
+
+    assert!(store.get(&[1; 32]).is_none());
+    assert!(store.get(&[2; 32]).is_some());
+    assert!(store.get(&[3; 32]).is_some());
+}
+
+#[tokio::test]
+async fn acceptance_is_not_acknowledged_until_the_state_is_on_disk() {
+    let mut state = app_state(Box::new(StubBackend));
+    let dir = tempfile::tempdir().unwrap();
+    let unwritable = dir.path().join("missing").join("state.json");
+    state.store = StateStore::open(Some(unwritable), ProtocolState::genesis()).unwrap();
+
+    let (status, body) = submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(body, "failed to persist state");
+
+    // applied in memory, so a retry learns what happened from the receipt
+    let (status, retried) = submit(&state, submission(1, [0; 32], [7; 32]), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(retried["replayed"], true);
+}
+
+#[tokio::test]
+async fn receipt_is_on_disk_before_acceptance_is_acknowledged() {
+    let mut state = app_state(Box::new(StubBackend));
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("receipts.json");
+    state.receipts = ReceiptStore::open(Some(path.clone()), 16).unwrap();
+
+    let body = submission(1, [0; 32], [7; 32]);
+    let id: [u8; 32] = hex::decode(submission_id(&body)).unwrap().try_into().unwrap();
+    let (status, accepted) = submit(&state, body, Some("k")).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let reopened = ReceiptStore::open(Some(path), 16).unwrap();
+    let record = reopened.get(&id).expect("receipt written with the state");
+    assert_eq!(accepted["receipt"]["submission_id"], record.submission_id);
+    assert_eq!(reopened.key_lookup("test", "k"), Some(id));
+}
+
+#[test]
+fn receipts_past_the_saved_state_are_forgotten() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("receipts.json");
+
+    // receipts for nonces 1 and 2 reached disk, the state only nonce 1
+    let store = ReceiptStore::open(Some(path.clone()), 16).unwrap();
+    store.accepted("a", &state_at(1, 7), &[1; 32]);
+    store.accepted("a", &state_at(2, 8), &[2; 32]);
+    store.flush().unwrap();
+
+    let reopened = ReceiptStore::open(Some(path.clone()), 16).unwrap();
+    assert_eq!(reopened.forget_after(1), 1);
+    assert!(reopened.get(&[1; 32]).is_some());
+    assert!(reopened.get(&[2; 32]).is_none());
+
+    reopened.flush().unwrap();
+    assert!(ReceiptStore::open(Some(path), 16).unwrap().get(&[2; 32]).is_none());
+}
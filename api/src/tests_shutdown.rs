@@ -0,0 +1,66 @@
+use crate::shutdown::Shutdown;
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::post,
+    Extension, Router,
+};
+use std::time::Duration;
+use tower::ServiceExt;
+
+fn app(shutdown: Shutdown) -> Router {
+    Router::new()
+        .route("/v1/submit-proof", post(|| async { StatusCode::OK }))
+        .route_layer(middleware::from_fn(Shutdown::middleware))
+        .layer(Extension(shutdown))
+}
+
+fn submit() -> Request<Body> {
+    Request::post("/v1/submit-proof").body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn submissions_pass_until_triggered() {
+    let shutdown = Shutdown::new();
+
+    let res = app(shutdown.clone()).oneshot(submit()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+
+    shutdown.trigger();
+    let res = app(shutdown.clone()).oneshot(submit()).await.unwrap();
+    assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert!(res.headers().contains_key("retry-after"));
+    assert_eq!(shutdown.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn drain_waits_for_in_flight_work() {
+    let shutdown = Shutdown::new();
+    let guard = shutdown.track().expect("not draining yet");
+    shutdown.trigger();
+
+    assert!(shutdown.track().is_none());
+    assert!(!shutdown.drain(Duration::from_millis(20)).await);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(guard);
+    });
+    assert!(shutdown.drain(Duration::from_secs(5)).await);
+    assert_eq!(shutdown.in_flight(), 0);
+}
+
+#[tokio::test]
+async fn triggered_resolves_after_trigger() {
+    let shutdown = Shutdown::new();
+    let waiter = tokio::spawn(shutdown.clone().triggered());
+
+    assert!(!shutdown.is_draining());
+    shutdown.trigger();
+
+    tokio::time::timeout(Duration::from_secs(5), waiter)
+        .await
+        .expect("waiter woke up")
+        .unwrap();
+}
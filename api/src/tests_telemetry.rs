@@ -0,0 +1,63 @@
+use crate::telemetry::{trace_requests, LogFormat, REQUEST_ID_HEADER};
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    middleware,
+    routing::get,
+    Router,
+};
+use tower::ServiceExt;
+
+fn app() -> Router {
+    Router::new()
+        .route("/", get(|| async { StatusCode::OK }))
+        .layer(middleware::from_fn(trace_requests))
+}
+
+#[tokio::test]
+async fn request_id_is_generated_when_missing() {
+    let res = app()
+        .oneshot(Request::get("/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+
+    let id = res.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+    assert_eq!(id.len(), 36);
+}
+
+#[tokio::test]
+async fn incoming_request_id_is_echoed() {
+    let res = app()
+        .oneshot(
+            Request::get("/")
+                .header(REQUEST_ID_HEADER, "trace-abc-123")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(res.headers()[REQUEST_ID_HEADER], "trace-abc-123");
+}
+
+#[tokio::test]
+async fn malformed_request_id_is_replaced() {
+    let res = app()
+        .oneshot(
+            Request::get("/")
+                .header(REQUEST_ID_HEADER, "has spaces")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_ne!(res.headers()[REQUEST_ID_HEADER], "has spaces");
+}
+
+#[test]
+fn log_format_parses_known_names() {
+    assert_eq!(LogFormat::parse("json"), Some(LogFormat::Json));
+    assert_eq!(LogFormat::parse("Pretty"), Some(LogFormat::Pretty));
+    assert_eq!(LogFormat::parse("xml"), None);
+}
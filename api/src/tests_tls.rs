@@ -0,0 +1,121 @@
+//! Certificates under `testdata/tls` come from a throwaway test CA:
+//! `server.pem` is valid for `localhost`/`127.0.0.1`, `client.pem` for client auth.
+
+use crate::auth::{require_role, Identity, KeyStore, Role};
+use crate::config::{TlsClient, TlsConfig};
+use crate::shutdown::Shutdown;
+use crate::tls::{fingerprint, TlsServer};
+use axum::{middleware, routing::get, Extension, Router};
+use std::{fs, net::SocketAddr, path::PathBuf};
+use tokio::net::TcpListener;
+
+fn testdata(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("testdata/tls").join(name)
+}
+
+fn client_fingerprint() -> String {
+    let pem = fs::read(testdata("client.pem")).unwrap();
+    let der = rustls_pemfile::certs(&mut pem.as_slice()).next().unwrap().unwrap();
+    fingerprint(&der)
+}
+
+fn tls_config(mtls: bool, require: bool) -> TlsConfig {
+    TlsConfig {
+        cert: testdata("server.pem"),
+        key: testdata("server.key"),
+        client_ca: mtls.then(|| testdata("ca.pem")),
+        require_client_cert: require,
+        clients: if mtls {
+            vec![TlsClient {
+                name: "lender".into(),
+                fingerprint: client_fingerprint(),
+                role: Role::Submitter,
+            }]
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+async fn whoami(Extension(identity): Extension<Identity>) -> String {
+    identity.key_id
+}
+
+/// Start a TLS node whose `/whoami` requires the submitter role.
+async fn spawn_node(config: TlsConfig) -> (SocketAddr, Shutdown) {
+    let app = Router::new()
+        .route("/whoami", get(whoami))
+        .route_layer(middleware::from_fn_with_state(Role::Submitter, require_role))
+        .route("/open", get(|| async { "ok" }))
+        .layer(Extension(KeyStore::open(None).unwrap()));
+
+    let tls = TlsServer::from_config(&config).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let shutdown = Shutdown::new();
+    tokio::spawn(tls.serve(listener, app, shutdown.clone()));
+
+    (addr, shutdown)
+}
+
+fn client(with_cert: bool) -> reqwest::Client {
+    let ca = reqwest::Certificate::from_pem(&fs::read(testdata("ca.pem")).unwrap()).unwrap();
+    let mut builder = reqwest::Client::builder()
+        .use_rustls_tls()
+        .add_root_certificate(ca);
+
+    if with_cert {
+        let mut pem = fs::read(testdata("client.pem")).unwrap();
+        pem.extend(fs::read(testdata("client.key")).unwrap());
+        builder = builder.identity(reqwest::Identity::from_pem(&pem).unwrap());
+    }
+    builder.build().unwrap()
+}
+
+#[tokio::test]
+async fn serves_https() {
+    let (addr, shutdown) = spawn_node(tls_config(false, false)).await;
+
+    let res = client(false)
+        .get(format!("https://localhost:{}/open", addr.port()))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.text().await.unwrap(), "ok");
+
+    shutdown.trigger();
+}
+
+#[tokio::test]
+async fn listed_client_cert_authenticates_as_submitter() {
+    let (addr, shutdown) = spawn_node(tls_config(true, false)).await;
+    let url = format!("https://localhost:{}/whoami", addr.port());
+
+    let res = client(true).get(&url).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.text().await.unwrap(), "cert:lender");
+
+    // optional mTLS: no certificate still connects, but needs an API key
+    let res = client(false).get(&url).send().await.unwrap();
+    assert_eq!(res.status(), 401);
+
+    shutdown.trigger();
+}
+
+#[tokio::test]
+async fn required_client_cert_rejects_anonymous_handshake() {
+    let (addr, shutdown) = spawn_node(tls_config(true, true)).await;
+    let url = format!("https://localhost:{}/open", addr.port());
+
+    assert!(client(false).get(&url).send().await.is_err());
+    assert_eq!(client(true).get(&url).send().await.unwrap().status(), 200);
+
+    shutdown.trigger();
+}
+
+#[test]
+fn missing_key_file_is_reported() {
+    let mut config = tls_config(false, false);
+    config.key = testdata("missing.key");
+    assert!(TlsServer::from_config(&config).is_err());
+}
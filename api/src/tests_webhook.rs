@@ -0,0 +1,298 @@
+use crate::webhook::{
+    verify_signature, RetryPolicy, WebhookDispatcher, WebhookEvent, EVENT_HEADER,
+    SIGNATURE_HEADER,
+};
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use std::{
+    os::unix::fs::PermissionsExt,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use zkcg_common::state::ProtocolState;
+
+/// Local HTTP stand-in for a customer endpoint.
+#[derive(Clone, Default)]
+struct Receiver {
+    received: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    /// Status codes to answer with, in order; 200 once exhausted.
+    script: Arc<Mutex<Vec<StatusCode>>>,
+}
+
+async fn receive(
+    State(rx): State<Receiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    rx.received.lock().unwrap().push((headers, body));
+    let mut script = rx.script.lock().unwrap();
+    if script.is_empty() {
+        StatusCode::OK
+    } else {
+        script.remove(0)
+    }
+}
+
+async fn spawn_receiver(script: Vec<StatusCode>) -> (String, Receiver) {
+    let rx = Receiver {
+        script: Arc::new(Mutex::new(script)),
+        ..Receiver::default()
+    };
+    let app = Router::new()
+        .route("/hook", post(receive))
+        .with_state(rx.clone());
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (format!("http://{}/hook", addr), rx)
+}
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        base_delay: Duration::ZERO,
+        max_delay: Duration::ZERO,
+        max_attempts,
+        request_timeout: Duration::from_secs(5),
+        ..RetryPolicy::default()
+    }
+}
+
+fn accepted(nonce: u64) -> WebhookEvent {
+    WebhookEvent::TransitionAccepted {
+        nonce,
+        epoch: 0,
+        old_state_root: [0u8; 32],
+        new_state_root: [42u8; 32],
+    }
+}
+
+#[tokio::test]
+async fn delivery_is_signed_and_acknowledged() {
+    let (url, rx) = spawn_receiver(vec![]).await;
+    let hooks = WebhookDispatcher::open(None, fast_policy(3)).unwrap();
+    hooks.register(url, "s3cret".into()).await.unwrap();
+
+    hooks.enqueue(accepted(1));
+    assert_eq!(hooks.deliver_due().await, 1);
+    assert!(hooks.pending().is_empty());
+
+    let received = rx.received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+
+    let (headers, body) = &received[0];
+    assert_eq!(headers[EVENT_HEADER], "transition_accepted");
+    let sig = headers[SIGNATURE_HEADER].to_str().unwrap();
+    assert!(verify_signature(b"s3cret", body, sig));
+    assert!(!verify_signature(b"wrong", body, sig));
+
+    let event: WebhookEvent = serde_json::from_slice(body).unwrap();
+    assert_eq!(event, accepted(1));
+}
+
+#[tokio::test]
+async fn failed_delivery_is_retried() {
+    let (url, rx) = spawn_receiver(vec![StatusCode::SERVICE_UNAVAILABLE]).await;
+    let hooks = WebhookDispatcher::open(None, fast_policy(3)).unwrap();
+    hooks.register(url, "s3cret".into()).await.unwrap();
+
+    hooks.enqueue(accepted(1));
+
+    assert_eq!(hooks.deliver_due().await, 0);
+    let pending = hooks.pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].attempts, 1);
+
+    assert_eq!(hooks.deliver_due().await, 1);
+    assert!(hooks.pending().is_empty());
+    assert_eq!(rx.received.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn exhausted_delivery_is_dead_lettered() {
+    let (url, _rx) = spawn_receiver(vec![StatusCode::INTERNAL_SERVER_ERROR; 5]).await;
+    let hooks = WebhookDispatcher::open(None, fast_policy(2)).unwrap();
+    hooks.register(url, "s3cret".into()).await.unwrap();
+
+    hooks.enqueue(accepted(1));
+    hooks.deliver_due().await;
+    hooks.deliver_due().await;
+
+    assert!(hooks.pending().is_empty());
+    let dead = hooks.dead_letters();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].attempts, 2);
+    assert!(dead[0].last_error.is_some());
+}
+
+#[tokio::test]
+async fn outbox_survives_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("outbox.json");
+
+    {
+        let hooks = WebhookDispatcher::open(Some(path.clone()), fast_policy(3)).unwrap();
+        // nothing listens here, so the delivery stays pending
+        hooks
+            .register("http://127.0.0.1:9/hook".into(), "s3cret".into())
+            .await
+            .unwrap();
+        hooks.enqueue(accepted(7));
+        hooks.flush().unwrap();
+    }
+
+    // the secret is sealed at rest, under a key only the owner can read
+    let outbox = std::fs::read_to_string(&path).unwrap();
+    assert!(!outbox.contains("s3cret"));
+    let key = std::fs::metadata(path.with_extension("key")).unwrap();
+    assert_eq!(key.permissions().mode() & 0o777, 0o600);
+
+    let reopened = WebhookDispatcher::open(Some(path.clone()), fast_policy(3)).unwrap();
+    assert_eq!(reopened.webhooks().len(), 1);
+    assert_eq!(reopened.webhooks()[0].secret, "s3cret");
+    let pending = reopened.pending();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].event, accepted(7));
+}
+
+#[tokio::test]
+async fn epoch_change_emits_second_event() {
+    let hooks = WebhookDispatcher::open(None, fast_policy(3)).unwrap();
+    hooks
+        .register("http://127.0.0.1:9/hook".into(), "s3cret".into())
+        .await
+        .unwrap();
+
+    let before = ProtocolState::genesis();
+    let after = ProtocolState {
+        state_root: [1u8; 32],
+        nonce: 1,
+        epoch: 1,
+        ..ProtocolState::genesis()
+    };
+
+    hooks.notify_transition(&before, &after);
+
+    let names: Vec<&str> = hooks.pending().iter().map(|d| d.event.name()).collect();
+    assert_eq!(names, vec!["transition_accepted", "epoch_changed"]);
+}
+
+#[tokio::test]
+async fn no_webhooks_means_nothing_queued() {
+    let hooks = WebhookDispatcher::open(None, fast_policy(3)).unwrap();
+    hooks.enqueue(accepted(1));
+    assert!(hooks.pending().is_empty());
+}
+
+#[tokio::test]
+async fn full_outbox_dead_letters_the_oldest_delivery() {
+    let policy = RetryPolicy {
+        max_pending: 2,
+        max_dead_letters: 1,
+        ..fast_policy(3)
+    };
+    let hooks = WebhookDispatcher::open(None, policy).unwrap();
+    hooks
+        .register("http://127.0.0.1:9/hook".into(), "s3cret".into())
+        .await
+        .unwrap();
+
+    for nonce in 1..=4 {
+        hooks.enqueue(accepted(nonce));
+    }
+
+    let pending: Vec<_> = hooks.pending().into_iter().map(|d| d.event).collect();
+    assert_eq!(pending, vec![accepted(3), accepted(4)]);
+    let dead = hooks.dead_letters();
+    assert_eq!(dead.len(), 1);
+    assert_eq!(dead[0].event, accepted(2));
+    assert_eq!(dead[0].last_error.as_deref(), Some("outbox full"));
+}
+
+#[tokio::test]
+async fn slow_endpoint_does_not_hold_up_the_others() {
+    let slow = Router::new().route(
+        "/hook",
+        post(|| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            StatusCode::OK
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let slow_url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, slow).await.unwrap() });
+    let (url, rx) = spawn_receiver(vec![]).await;
+
+    let policy = RetryPolicy {
+        request_timeout: Duration::from_secs(1),
+        ..fast_policy(3)
+    };
+    let hooks = WebhookDispatcher::open(None, policy).unwrap();
+    hooks.register(slow_url, "s3cret".into()).await.unwrap();
+    hooks.register(url, "s3cret".into()).await.unwrap();
+    hooks.enqueue(accepted(1));
+    hooks.enqueue(accepted(2));
+
+    // the slow endpoint, registered first, takes 2s to time out twice; the
+    // other gets both events meanwhile
+    let delivering = tokio::spawn({
+        let hooks = hooks.clone();
+        async move { hooks.deliver_due().await }
+    });
+    tokio::time::timeout(Duration::from_millis(500), async {
+        while rx.received.lock().unwrap().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("fast endpoint served while the slow one is pending");
+    assert!(!delivering.is_finished());
+    assert_eq!(delivering.await.unwrap(), 2);
+}
+
+#[tokio::test]
+async fn new_events_reach_other_endpoints_while_a_slow_one_is_in_flight() {
+    let slow = Router::new().route(
+        "/hook",
+        post(|| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            StatusCode::OK
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let slow_url = format!("http://{}/hook", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, slow).await.unwrap() });
+    let (url, rx) = spawn_receiver(vec![]).await;
+
+    let policy = RetryPolicy {
+        request_timeout: Duration::from_secs(10),
+        ..fast_policy(3)
+    };
+    let hooks = WebhookDispatcher::open(None, policy).unwrap();
+    hooks.register(slow_url, "s3cret".into()).await.unwrap();
+    hooks.register(url, "s3cret".into()).await.unwrap();
+    tokio::spawn(hooks.clone().run());
+
+    let received = |n: usize| {
+        let rx = rx.clone();
+        tokio::time::timeout(Duration::from_millis(500), async move {
+            while rx.received.lock().unwrap().len() < n {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+    };
+
+    // the slow endpoint holds its first event for the whole timeout; the
+    // next pass must not wait for it
+    hooks.enqueue(accepted(1));
+    received(1).await.expect("first event delivered");
+    hooks.enqueue(accepted(2));
+    received(2).await.expect("second event delivered while the slow endpoint is pending");
+}
@@ -0,0 +1,245 @@
+use crate::commitment::next_state_commitment;
+use crate::auth::{Identity, Role};
+use crate::handler::{claim_job, complete_job, register_worker, worker_heartbeat, AppState};
+use crate::jobs::{JobError, JobLimits, JobQueue, JobStatus};
+use crate::models::ProveRequest;
+use crate::test_support::app_state;
+use axum::{
+    body::{to_bytes, Body},
+    http::{header::CONTENT_TYPE, Request, StatusCode},
+    routing::post,
+    Extension, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+use tower::ServiceExt;
+use zkcg_common::{errors::ProtocolError, types::Commitment};
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::engine::PublicInputs;
+
+/// Stub-named backend that accepts every proof except the literal bytes `b"bad"`.
+struct Picky;
+
+impl ProofBackend for Picky {
+    fn verify(&self, proof_bytes: &[u8], _public_inputs: &PublicInputs) -> Result<(), ProtocolError> {
+        if proof_bytes == b"bad" {
+            Err(ProtocolError::InvalidProof)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "stub"
+    }
+}
+
+fn app(state: AppState) -> Router {
+    Router::new()
+        .route("/v1/workers", post(register_worker))
+        .route("/v1/workers/:id/heartbeat", post(worker_heartbeat))
+        .route("/v1/workers/:id/claim", post(claim_job))
+        .route("/v1/workers/:id/jobs/:job_id", post(complete_job))
+        .layer(Extension(state))
+        .layer(Extension(Identity {
+            key_id: "worker-key".into(),
+            role: Role::Worker,
+        }))
+}
+
+async fn call(state: &AppState, path: &str, body: Option<Value>) -> (StatusCode, Value) {
+    let req = Request::post(path).header(CONTENT_TYPE, "application/json");
+    let body = body.map_or_else(Body::empty, |b| Body::from(b.to_string()));
+    let res = app(state.clone())
+        .oneshot(req.body(body).unwrap())
+        .await
+        .unwrap();
+    let status = res.status();
+    let body = to_bytes(res.into_body(), usize::MAX).await.unwrap();
+    let body = serde_json::from_slice(&body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+    (status, body)
+}
+
+async fn register(state: &AppState, name: &str) -> String {
+    let (status, body) = call(state, "/v1/workers", Some(json!({ "name": name, "backends": ["stub"] }))).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", body);
+    body["worker_id"].as_str().unwrap().to_string()
+}
+
+fn queue_job(state: &AppState) -> String {
+    state
+        .jobs
+        .submit("alice", ProveRequest { secret_value: 300, threshold: 600 })
+        .unwrap()
+        .id
+}
+
+#[tokio::test]
+async fn claimed_job_is_bound_to_the_state_and_completed_by_the_worker() {
+    let state = app_state(Box::new(Picky));
+    state
+        .engine
+        .lock()
+        .unwrap()
+        .process_transition(
+            b"proof",
+            PublicInputs {
+                threshold: 10,
+                old_state_root: [0; 32],
+                nonce: 1,
+            },
+            Commitment([7; 32]),
+        )
+        .unwrap();
+    let worker = register(&state, "gpu-1").await;
+
+    let (status, _) = call(&state, &format!("/v1/workers/{}/claim", worker), None).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let job_id = queue_job(&state);
+    let (status, task) = call(&state, &format!("/v1/workers/{}/claim", worker), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(task["job_id"], job_id.as_str());
+    assert_eq!(task["backend"], "stub");
+    assert_eq!(task["secret_value"], 300);
+    assert_eq!(task["nonce"], 2);
+    assert_eq!(task["old_state_root"], json!([7u8; 32].to_vec()));
+    assert_eq!(state.jobs.get("alice", &job_id).unwrap().status, JobStatus::Running);
+
+    let path = format!("/v1/workers/{}/jobs/{}", worker, job_id);
+    let (status, _) = call(&state, &path, Some(json!({ "proof": STANDARD.encode(b"proof") }))).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let job = state.jobs.get("alice", &job_id).unwrap();
+    assert_eq!(job.status, JobStatus::Succeeded);
+    let result = job.result.unwrap();
+    assert_eq!(result.public_inputs.nonce, 2);
+    let expected = next_state_commitment(&[7; 32], 2, 600, b"proof");
+    assert_eq!(result.new_state_commitment, expected.0);
+
+    // reporting again is refused: the job is no longer assigned
+    let (status, _) = call(&state, &path, Some(json!({ "proof": STANDARD.encode(b"proof") }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn invalid_proofs_and_prover_errors_fail_the_job() {
+    let state = app_state(Box::new(Picky));
+    let worker = register(&state, "gpu-1").await;
+
+    let bad = queue_job(&state);
+    call(&state, &format!("/v1/workers/{}/claim", worker), None).await;
+    let (status, _) = call(
+        &state,
+        &format!("/v1/workers/{}/jobs/{}", worker, bad),
+        Some(json!({ "proof": STANDARD.encode(b"bad") })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let job = state.jobs.get("alice", &bad).unwrap();
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(job.error.as_deref(), Some("worker returned an invalid proof"));
+
+    let refused = queue_job(&state);
+    call(&state, &format!("/v1/workers/{}/claim", worker), None).await;
+    let path = format!("/v1/workers/{}/jobs/{}", worker, refused);
+    let (status, _) = call(&state, &path, Some(json!({ "proof": "AQID", "error": "both" }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = call(&state, &path, Some(json!({ "error": "policy violation" }))).await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    let job = state.jobs.get("alice", &refused).unwrap();
+    assert_eq!(job.status, JobStatus::Failed);
+    assert_eq!(job.error.as_deref(), Some("policy violation"));
+}
+
+#[tokio::test]
+async fn workers_must_prove_for_the_node_backend() {
+    let state = app_state(Box::new(Picky));
+
+    let (status, _) = call(&state, "/v1/workers", Some(json!({ "name": "cpu", "backends": ["halo2"] }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, body) = call(&state, "/v1/workers", Some(json!({ "name": "cpu", "backends": ["halo2", "stub"] }))).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["backend"], "stub");
+    assert_eq!(body["heartbeat_interval_secs"], 10);
+
+    let (status, _) = call(&state, "/v1/workers/nobody/heartbeat", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = call(&state, "/v1/workers/nobody/claim", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // marked, so a worker knows to register again without reading the message
+    let res = app(state.clone())
+        .oneshot(Request::post("/v1/workers/nobody/heartbeat").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.headers()["x-zkcg-error-code"], "ERR_UNKNOWN_WORKER");
+}
+
+#[test]
+fn silent_worker_loses_its_job_to_another() {
+    let queue = JobQueue::open(None, JobLimits::default()).unwrap();
+    let job = queue.submit("alice", ProveRequest { secret_value: 1, threshold: 2 }).unwrap();
+
+    let stalled = queue.register_worker("worker-key", "stalled");
+    let (claimed, _) = queue.claim_for("worker-key", &stalled, [0; 32], 1).unwrap().unwrap();
+    assert_eq!(claimed, job.id);
+
+    // nothing has timed out yet
+    queue.expire_workers(Instant::now());
+    let healthy = queue.register_worker("worker-key", "healthy");
+    assert!(queue.claim_for("worker-key", &healthy, [0; 32], 1).unwrap().is_none());
+
+    let later = Instant::now() + queue.worker_timeout() + Duration::from_secs(1);
+    queue.heartbeat("worker-key", &healthy).unwrap();
+    queue.expire_workers(later);
+    assert_eq!(queue.get("alice", &job.id).unwrap().status, JobStatus::Queued);
+    assert_eq!(queue.heartbeat("worker-key", &stalled).unwrap_err(), JobError::UnknownWorker);
+
+    // the healthy worker was dropped too, being silent as of `later`
+    let replacement = queue.register_worker("worker-key", "replacement");
+    let (reclaimed, _) = queue.claim_for("worker-key", &replacement, [1; 32], 2).unwrap().unwrap();
+    assert_eq!(reclaimed, job.id);
+    assert_eq!(queue.assignment("worker-key", &replacement, &job.id).unwrap().nonce, 2);
+
+    // the stalled worker's late answer is refused
+    let late = queue.complete_for("worker-key", &stalled, &job.id, Err("late".into()));
+    assert_eq!(late.unwrap_err(), JobError::UnknownWorker);
+    assert_eq!(queue.get("alice", &job.id).unwrap().status, JobStatus::Running);
+}
+
+#[test]
+fn workers_answer_only_to_the_key_that_registered_them() {
+    let queue = JobQueue::open(None, JobLimits::default()).unwrap();
+    let job = queue.submit("alice", ProveRequest { secret_value: 1, threshold: 2 }).unwrap();
+    let worker = queue.register_worker("worker-key", "gpu");
+
+    let stolen = queue.claim_for("other-key", &worker, [0; 32], 1);
+    assert_eq!(stolen.unwrap_err(), JobError::UnknownWorker);
+    queue.claim_for("worker-key", &worker, [0; 32], 1).unwrap().unwrap();
+
+    assert_eq!(queue.heartbeat("other-key", &worker).unwrap_err(), JobError::UnknownWorker);
+    let peek = queue.assignment("other-key", &worker, &job.id);
+    assert_eq!(peek.unwrap_err(), JobError::UnknownWorker);
+    let forged = queue.complete_for("other-key", &worker, &job.id, Err("forged".into()));
+    assert_eq!(forged.unwrap_err(), JobError::UnknownWorker);
+    assert_eq!(queue.get("alice", &job.id).unwrap().status, JobStatus::Running);
+}
+
+#[test]
+fn cancelled_remote_job_stays_cancelled() {
+    let queue = JobQueue::open(None, JobLimits::default()).unwrap();
+    let job = queue.submit("alice", ProveRequest { secret_value: 1, threshold: 2 }).unwrap();
+    let worker = queue.register_worker("worker-key", "gpu");
+    queue.claim_for("worker-key", &worker, [0; 32], 1).unwrap().unwrap();
+
+    queue.cancel("alice", &job.id).unwrap();
+    queue.complete_for("worker-key", &worker, &job.id, Err("too late".into())).unwrap();
+
+    let job = queue.get("alice", &job.id).unwrap();
+    assert_eq!(job.status, JobStatus::Cancelled);
+    assert!(job.error.is_none());
+}
@@ -0,0 +1,196 @@
+use axum::{extract::ConnectInfo, http::Request, Router};
+use hyper::body::Incoming;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::{conn::auto::Builder, graceful::GracefulShutdown},
+    service::TowerToHyperService,
+};
+use rustls::{
+    pki_types::{CertificateDer, PrivateKeyDer},
+    server::WebPkiClientVerifier,
+    RootCertStore, ServerConfig,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::BufReader,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceExt;
+
+use crate::auth::Identity;
+use crate::config::TlsConfig;
+use crate::shutdown::Shutdown;
+
+/// Handshakes that take longer than this are dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Identity proven by a client certificate listed in `[[tls.clients]]`.
+///
+/// Inserted by the TLS listener, never by request data, so [`crate::auth::require_role`]
+/// can trust it in place of an API key.
+#[derive(Debug, Clone)]
+pub struct ClientCertIdentity(pub Identity);
+
+/// Hex SHA-256 of a DER certificate, the form used in `[[tls.clients]]`.
+pub fn fingerprint(der: &[u8]) -> String {
+    hex::encode(Sha256::digest(der))
+}
+
+/// Ready-to-serve TLS settings: rustls config plus the certificate → identity map.
+#[derive(Clone)]
+pub struct TlsServer {
+    acceptor: TlsAcceptor,
+    clients: Arc<HashMap<String, Identity>>,
+}
+
+impl TlsServer {
+    pub fn from_config(config: &TlsConfig) -> Result<Self, String> {
+        let certs = load_certs(&config.cert)?;
+        let key = load_key(&config.key)?;
+
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("tls: {}", e))?;
+
+        let builder = match &config.client_ca {
+            Some(ca) => {
+                let mut roots = RootCertStore::empty();
+                for cert in load_certs(ca)? {
+                    roots
+                        .add(cert)
+                        .map_err(|e| format!("tls.client_ca {}: {}", ca.display(), e))?;
+                }
+                let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = if config.require_client_cert {
+                    verifier.build()
+                } else {
+                    verifier.allow_unauthenticated().build()
+                }
+                .map_err(|e| format!("tls.client_ca {}: {}", ca.display(), e))?;
+                builder.with_client_cert_verifier(verifier)
+            }
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut server = builder
+            .with_single_cert(certs, key)
+            .map_err(|e| format!("tls: {}", e))?;
+        server.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        let clients = config
+            .clients
+            .iter()
+            .map(|c| {
+                let identity = Identity {
+                    key_id: format!("cert:{}", c.name),
+                    role: c.role,
+                };
+                (c.fingerprint.to_ascii_lowercase(), identity)
+            })
+            .collect();
+
+        Ok(Self {
+            acceptor: TlsAcceptor::from(Arc::new(server)),
+            clients: Arc::new(clients),
+        })
+    }
+
+    /// Identity for a verified peer certificate, if it is listed.
+    fn identify(&self, peer: Option<&[CertificateDer<'_>]>) -> Option<Identity> {
+        let leaf = peer?.first()?;
+        self.clients.get(&fingerprint(leaf)).cloned()
+    }
+
+    /// Serve `app` over TLS until `shutdown` is triggered, then let open
+    /// connections finish their current requests.
+    ///
+    /// Requests carry `ConnectInfo<SocketAddr>` like plain `axum::serve`, plus
+    /// a [`ClientCertIdentity`] when the client presented a listed certificate.
+    pub async fn serve(self, listener: TcpListener, app: Router, shutdown: Shutdown) {
+        let graceful = GracefulShutdown::new();
+        let stop = shutdown.triggered();
+        tokio::pin!(stop);
+
+        loop {
+            let (tcp, peer) = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok(conn) => conn,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to accept connection");
+                        continue;
+                    }
+                },
+                _ = &mut stop => break,
+            };
+
+            let tls = self.clone();
+            let app = app.clone();
+            let watcher = graceful.watcher();
+
+            tokio::spawn(async move {
+                let stream = match tokio::time::timeout(HANDSHAKE_TIMEOUT, tls.acceptor.accept(tcp)).await {
+                    Ok(Ok(stream)) => stream,
+                    Ok(Err(e)) => {
+                        tracing::debug!(%peer, error = %e, "tls handshake failed");
+                        return;
+                    }
+                    Err(_) => {
+                        tracing::debug!(%peer, "tls handshake timed out");
+                        return;
+                    }
+                };
+
+                let client = tls.identify(stream.get_ref().1.peer_certificates());
+                if let Some(identity) = &client {
+                    tracing::debug!(%peer, key_id = %identity.key_id, "client certificate accepted");
+                }
+
+                let service = app.map_request(move |mut req: Request<Incoming>| {
+                    req.extensions_mut().insert(ConnectInfo(peer));
+                    if let Some(identity) = &client {
+                        req.extensions_mut().insert(ClientCertIdentity(identity.clone()));
+                    }
+                    req
+                });
+
+                let builder = Builder::new(TokioExecutor::new());
+                let conn = builder.serve_connection_with_upgrades(
+                    TokioIo::new(stream),
+                    TowerToHyperService::new(service),
+                );
+                if let Err(e) = watcher.watch(conn.into_owned()).await {
+                    tracing::debug!(%peer, error = %e, "connection closed with error");
+                }
+            });
+        }
+
+        drop(listener);
+        graceful.shutdown().await;
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    if certs.is_empty() {
+        return Err(format!("{}: no certificates found", path.display()));
+    }
+    Ok(certs)
+}
+
+fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, String> {
+    let file = File::open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|e| format!("{}: {}", path.display(), e))?
+        .ok_or_else(|| format!("{}: no private key found", path.display()))
+}
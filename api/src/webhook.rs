@@ -0,0 +1,664 @@
+use hmac::{Hmac, Mac};
+use rand::{rngs::OsRng, RngCore};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    fs,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{
+    sync::{Notify, Semaphore},
+    task::JoinSet,
+};
+use utoipa::ToSchema;
+use zkcg_common::{state::ProtocolState, types::Hash};
+use zkcg_verifier::storage::write_private_json;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying `sha256=<hex hmac>` of the raw request body.
+pub const SIGNATURE_HEADER: &str = "x-zkcg-signature";
+pub const EVENT_HEADER: &str = "x-zkcg-event";
+pub const DELIVERY_HEADER: &str = "x-zkcg-delivery";
+
+/// Events pushed to registered webhooks.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    TransitionAccepted {
+        nonce: u64,
+        epoch: u64,
+        #[schema(schema_with = crate::openapi::hash_schema)]
+        old_state_root: Hash,
+        #[schema(schema_with = crate::openapi::hash_schema)]
+        new_state_root: Hash,
+    },
+    EpochChanged {
+        old_epoch: u64,
+        new_epoch: u64,
+        nonce: u64,
+    },
+}
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::TransitionAccepted { .. } => "transition_accepted",
+            WebhookEvent::EpochChanged { .. } => "epoch_changed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub id: u64,
+    pub url: String,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Delivery {
+    pub id: u64,
+    pub webhook_id: u64,
+    pub event: WebhookEvent,
+    pub attempts: u32,
+    /// Unix time (ms) before which the delivery must not be retried.
+    pub next_attempt_at: u64,
+    pub last_error: Option<String>,
+}
+
+/// Backoff schedule and queue bounds for deliveries.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Deliveries are dead-lettered after this many failed attempts.
+    pub max_attempts: u32,
+    pub request_timeout: Duration,
+    /// Past this many pending deliveries the oldest is dead-lettered.
+    pub max_pending: usize,
+    /// Past this many dead letters the oldest is dropped.
+    pub max_dead_letters: usize,
+    /// Endpoints delivered to at the same time, across delivery passes.
+    pub max_concurrent: usize,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(15 * 60),
+            max_attempts: 10,
+            request_timeout: Duration::from_secs(10),
+            max_pending: 10_000,
+            max_dead_letters: 1_000,
+            max_concurrent: 16,
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay_for(&self, attempts: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Default)]
+struct Outbox {
+    webhooks: Vec<Webhook>,
+    /// Oldest first
+    pending: VecDeque<Delivery>,
+    /// Oldest first
+    dead_letters: VecDeque<Delivery>,
+    next_id: u64,
+    /// Bumped on every change
+    generation: u64,
+}
+
+impl Outbox {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn dead_letter(&mut self, delivery: Delivery, max_dead_letters: usize) {
+        tracing::warn!(
+            delivery = %delivery.id,
+            attempts = delivery.attempts,
+            error = delivery.last_error.as_deref().unwrap_or_default(),
+            "webhook delivery dead-lettered"
+        );
+        self.dead_letters.push_back(delivery);
+        while self.dead_letters.len() > max_dead_letters {
+            if let Some(dropped) = self.dead_letters.pop_front() {
+                tracing::warn!(delivery = %dropped.id, "dead letter dropped to stay within max_dead_letters");
+            }
+        }
+    }
+}
+
+/// Outbox as written to disk, with webhook secrets sealed.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct OutboxFile {
+    webhooks: Vec<StoredWebhook>,
+    pending: Vec<Delivery>,
+    dead_letters: Vec<Delivery>,
+    next_id: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredWebhook {
+    id: u64,
+    url: String,
+    /// Hex nonce and ciphertext, see [`SealingKey`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sealed_secret: Option<String>,
+    /// Plaintext, as outboxes written before sealing hold it
+    #[serde(default, skip_serializing)]
+    secret: Option<String>,
+}
+
+/// AES-256-GCM key for the webhook secrets in the outbox, kept in a file of
+/// its own so that a copy of the outbox does not give the secrets away.
+struct SealingKey(LessSafeKey);
+
+impl SealingKey {
+    /// Read the key at `path`, or create it (mode 0600) if there is none.
+    fn load_or_create(path: &Path) -> io::Result<Self> {
+        let bytes = if path.exists() {
+            let hex_key: String = serde_json::from_slice(&fs::read(path)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            hex::decode(hex_key).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        } else {
+            let mut bytes = vec![0u8; 32];
+            OsRng.fill_bytes(&mut bytes);
+            write_private_json(path, &hex::encode(&bytes))?;
+            bytes
+        };
+
+        let key = UnboundKey::new(&AES_256_GCM, &bytes).map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "webhook key is not 32 bytes")
+        })?;
+        Ok(Self(LessSafeKey::new(key)))
+    }
+
+    /// Sealed under the webhook id, so sealed secrets cannot be swapped between webhooks.
+    fn seal(&self, webhook_id: u64, secret: &str) -> String {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let mut sealed = secret.as_bytes().to_vec();
+        self.0
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(webhook_id.to_be_bytes()),
+                &mut sealed,
+            )
+            .expect("secrets are far below the AES-GCM length limit");
+        hex::encode([nonce.as_slice(), &sealed].concat())
+    }
+
+    fn open(&self, webhook_id: u64, sealed: &str) -> io::Result<String> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("cannot unseal the secret of webhook {}; was its key file replaced?", webhook_id),
+            )
+        };
+
+        let raw = hex::decode(sealed).map_err(|_| invalid())?;
+        if raw.len() < NONCE_LEN {
+            return Err(invalid());
+        }
+        let (nonce, ciphertext) = raw.split_at(NONCE_LEN);
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| invalid())?;
+        let mut ciphertext = ciphertext.to_vec();
+        let plain = self
+            .0
+            .open_in_place(nonce, Aad::from(webhook_id.to_be_bytes()), &mut ciphertext)
+            .map_err(|_| invalid())?;
+        String::from_utf8(plain.to_vec()).map_err(|_| invalid())
+    }
+}
+
+/// Webhook outbox plus the background delivery loop.
+///
+/// Events are queued in memory and written out by a background task, never
+/// on the request path; see [`WebhookDispatcher::run`]. Both queues are
+/// bounded by the [`RetryPolicy`], and the outbox is written with webhook
+/// secrets sealed under a key kept next to it (`<outbox>.key`).
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    outbox: Arc<Mutex<Outbox>>,
+    path: Option<PathBuf>,
+    key: Option<Arc<SealingKey>>,
+    policy: RetryPolicy,
+    client: reqwest::Client,
+    wake: Arc<Notify>,
+    dirty: Arc<Notify>,
+    /// Generation last written to `path`; held for the whole write so
+    /// flushes never overtake each other
+    written: Arc<Mutex<u64>>,
+    /// Webhooks with a delivery task running
+    in_flight: Arc<Mutex<HashSet<u64>>>,
+    /// One per endpoint being delivered to, `max_concurrent` in all
+    permits: Arc<Semaphore>,
+}
+
+/// Marks a webhook as being delivered to until dropped, so a failed task
+/// never leaves its endpoint stuck.
+struct InFlight {
+    webhook_id: u64,
+    in_flight: Arc<Mutex<HashSet<u64>>>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(&self.webhook_id);
+    }
+}
+
+impl WebhookDispatcher {
+    /// Open (or create) an outbox backed by `path`. `None` keeps it in memory.
+    pub fn open(path: Option<PathBuf>, policy: RetryPolicy) -> io::Result<Self> {
+        let key = match &path {
+            Some(p) => Some(Arc::new(SealingKey::load_or_create(&p.with_extension("key"))?)),
+            None => None,
+        };
+        let file: OutboxFile = match &path {
+            Some(p) if p.exists() => {
+                let raw = fs::read(p)?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            _ => OutboxFile::default(),
+        };
+
+        let mut outbox = Outbox {
+            pending: file.pending.into(),
+            dead_letters: file.dead_letters.into(),
+            next_id: file.next_id,
+            ..Outbox::default()
+        };
+        for stored in file.webhooks {
+            let secret = match (&stored.sealed_secret, stored.secret, &key) {
+                (Some(sealed), _, Some(key)) => key.open(stored.id, sealed)?,
+                (None, Some(plain), _) => {
+                    // written before secrets were sealed; sealed on the next write
+                    outbox.generation = 1;
+                    plain
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("webhook {} has no secret", stored.id),
+                    ))
+                }
+            };
+            outbox.webhooks.push(Webhook {
+                id: stored.id,
+                url: stored.url,
+                secret,
+            });
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(policy.request_timeout)
+            .build()
+            .map_err(io::Error::other)?;
+
+        let permits = Arc::new(Semaphore::new(policy.max_concurrent.max(1)));
+
+        Ok(Self {
+            outbox: Arc::new(Mutex::new(outbox)),
+            path,
+            key,
+            policy,
+            client,
+            wake: Arc::new(Notify::new()),
+            dirty: Arc::new(Notify::new()),
+            written: Arc::new(Mutex::new(0)),
+            in_flight: Arc::new(Mutex::new(HashSet::new())),
+            permits,
+        })
+    }
+
+    /// Register a webhook; returns once it is on disk.
+    pub async fn register(&self, url: String, secret: String) -> io::Result<Webhook> {
+        let webhook = {
+            let mut outbox = self.outbox.lock().unwrap();
+            let webhook = Webhook {
+                id: outbox.next_id(),
+                url,
+                secret,
+            };
+            outbox.webhooks.push(webhook.clone());
+            outbox.generation += 1;
+            webhook
+        };
+        self.flush_in_background().await?;
+        Ok(webhook)
+    }
+
+    /// Remove a webhook and its pending deliveries; returns once that is on disk.
+    pub async fn unregister(&self, id: u64) -> io::Result<bool> {
+        let removed = {
+            let mut outbox = self.outbox.lock().unwrap();
+            let before = outbox.webhooks.len();
+            outbox.webhooks.retain(|w| w.id != id);
+            outbox.pending.retain(|d| d.webhook_id != id);
+            outbox.generation += 1;
+            outbox.webhooks.len() != before
+        };
+        self.flush_in_background().await?;
+        Ok(removed)
+    }
+
+    pub fn webhooks(&self) -> Vec<Webhook> {
+        self.outbox.lock().unwrap().webhooks.clone()
+    }
+
+    pub fn pending(&self) -> Vec<Delivery> {
+        self.outbox.lock().unwrap().pending.iter().cloned().collect()
+    }
+
+    pub fn dead_letters(&self) -> Vec<Delivery> {
+        self.outbox.lock().unwrap().dead_letters.iter().cloned().collect()
+    }
+
+    /// Queue one delivery per registered webhook.
+    ///
+    /// Only touches memory; the background task writes the outbox. With
+    /// `max_pending` deliveries already queued, the oldest is dead-lettered.
+    pub fn enqueue(&self, event: WebhookEvent) {
+        {
+            let mut outbox = self.outbox.lock().unwrap();
+            if outbox.webhooks.is_empty() {
+                return;
+            }
+
+            let now = now_ms();
+            let ids: Vec<u64> = outbox.webhooks.iter().map(|w| w.id).collect();
+            for webhook_id in ids {
+                let id = outbox.next_id();
+                outbox.pending.push_back(Delivery {
+                    id,
+                    webhook_id,
+                    event: event.clone(),
+                    attempts: 0,
+                    next_attempt_at: now,
+                    last_error: None,
+                });
+            }
+            while outbox.pending.len() > self.policy.max_pending {
+                if let Some(mut oldest) = outbox.pending.pop_front() {
+                    oldest.last_error = Some("outbox full".to_string());
+                    outbox.dead_letter(oldest, self.policy.max_dead_letters);
+                }
+            }
+            outbox.generation += 1;
+        }
+
+        self.dirty.notify_one();
+        self.wake.notify_one();
+    }
+
+    /// Queue the events implied by moving from `before` to `after`.
+    pub fn notify_transition(&self, before: &ProtocolState, after: &ProtocolState) {
+        self.enqueue(WebhookEvent::TransitionAccepted {
+            nonce: after.nonce,
+            epoch: after.epoch,
+            old_state_root: before.state_root,
+            new_state_root: after.state_root,
+        });
+
+        if before.epoch != after.epoch {
+            self.enqueue(WebhookEvent::EpochChanged {
+                old_epoch: before.epoch,
+                new_epoch: after.epoch,
+                nonce: after.nonce,
+            });
+        }
+    }
+
+    /// Deliver every delivery that is currently due and wait for the sends.
+    /// Returns how many succeeded.
+    pub async fn deliver_due(&self) -> usize {
+        let mut sends = JoinSet::new();
+        self.start_due(&mut sends);
+
+        let mut delivered = 0;
+        while let Some(sent) = sends.join_next().await {
+            delivered += sent.unwrap_or(0);
+        }
+        delivered
+    }
+
+    /// Start a task for each endpoint with due deliveries that has none
+    /// running already. Each task yields how many of its deliveries succeeded.
+    ///
+    /// Up to `max_concurrent` endpoints are delivered to at once; each one
+    /// gets its deliveries one at a time, in queue order, so a slow endpoint
+    /// only holds up its own.
+    fn start_due(&self, sends: &mut JoinSet<usize>) {
+        let due: Vec<(Webhook, Vec<Delivery>)> = {
+            let outbox = self.outbox.lock().unwrap();
+            let mut in_flight = self.in_flight.lock().unwrap();
+            let now = now_ms();
+            let mut by_webhook: BTreeMap<u64, Vec<Delivery>> = BTreeMap::new();
+            for delivery in outbox.pending.iter().filter(|d| d.next_attempt_at <= now) {
+                by_webhook.entry(delivery.webhook_id).or_default().push(delivery.clone());
+            }
+            let due: Vec<_> = outbox
+                .webhooks
+                .iter()
+                .filter(|w| !in_flight.contains(&w.id))
+                .filter_map(|w| Some((w.clone(), by_webhook.remove(&w.id)?)))
+                .collect();
+            for (webhook, _) in &due {
+                in_flight.insert(webhook.id);
+            }
+            due
+        };
+
+        for (webhook, deliveries) in due {
+            let this = self.clone();
+            let guard = InFlight {
+                webhook_id: webhook.id,
+                in_flight: self.in_flight.clone(),
+            };
+            sends.spawn(async move {
+                let _guard = guard;
+                let _permit = this.permits.clone().acquire_owned().await.expect("the semaphore is never closed");
+                let mut delivered = 0;
+                for delivery in deliveries {
+                    let result = this.send(&delivery, &webhook).await;
+                    if result.is_ok() {
+                        delivered += 1;
+                    }
+                    this.record(delivery.id, result);
+                }
+                delivered
+            });
+        }
+    }
+
+    /// Background delivery loop, plus the task that writes the outbox.
+    /// Never returns.
+    ///
+    /// A pass starts whatever is due without waiting for endpoints still
+    /// being delivered to; those are picked up again once their task ends.
+    pub async fn run(self) {
+        tokio::spawn(self.clone().persist_loop());
+        let mut sends = JoinSet::new();
+        loop {
+            self.start_due(&mut sends);
+
+            let sleep = self.time_until_next_due();
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(sleep) => {}
+                // an endpoint is free again and may have more due
+                Some(_) = sends.join_next() => {}
+            }
+        }
+    }
+
+    /// Write the outbox whenever it changed, on the blocking pool.
+    async fn persist_loop(self) {
+        loop {
+            self.dirty.notified().await;
+            if let Err(e) = self.flush_in_background().await {
+                tracing::error!(error = %e, "failed to persist webhook outbox");
+            }
+        }
+    }
+
+    async fn flush_in_background(&self) -> io::Result<()> {
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.flush())
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)))
+    }
+
+    /// Write the outbox to disk, unless a concurrent flush already has.
+    /// Blocks; a no-op for in-memory outboxes.
+    pub fn flush(&self) -> io::Result<()> {
+        let (Some(path), Some(key)) = (&self.path, &self.key) else {
+            return Ok(());
+        };
+        let mut written = self.written.lock().unwrap();
+        let (file, generation) = {
+            let outbox = self.outbox.lock().unwrap();
+            if *written >= outbox.generation {
+                return Ok(());
+            }
+            let file = OutboxFile {
+                webhooks: outbox
+                    .webhooks
+                    .iter()
+                    .map(|w| StoredWebhook {
+                        id: w.id,
+                        url: w.url.clone(),
+                        sealed_secret: Some(key.seal(w.id, &w.secret)),
+                        secret: None,
+                    })
+                    .collect(),
+                pending: outbox.pending.iter().cloned().collect(),
+                dead_letters: outbox.dead_letters.iter().cloned().collect(),
+                next_id: outbox.next_id,
+            };
+            (file, outbox.generation)
+        };
+
+        write_private_json(path, &file)?;
+        *written = generation;
+        Ok(())
+    }
+
+    async fn send(&self, delivery: &Delivery, webhook: &Webhook) -> Result<(), String> {
+        let body = serde_json::to_vec(&delivery.event).map_err(|e| e.to_string())?;
+        let signature = sign(webhook.secret.as_bytes(), &body);
+
+        let res = self
+            .client
+            .post(&webhook.url)
+            .header("content-type", "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .header(EVENT_HEADER, delivery.event.name())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+
+        if res.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("endpoint returned {}", res.status()))
+        }
+    }
+
+    fn record(&self, delivery_id: u64, result: Result<(), String>) {
+        {
+            let mut outbox = self.outbox.lock().unwrap();
+            let Some(pos) = outbox.pending.iter().position(|d| d.id == delivery_id) else {
+                // webhook was unregistered while the request was in flight
+                return;
+            };
+
+            match result {
+                Ok(()) => {
+                    outbox.pending.remove(pos);
+                }
+                Err(err) => {
+                    let delivery = &mut outbox.pending[pos];
+                    delivery.attempts += 1;
+                    delivery.last_error = Some(err);
+
+                    if delivery.attempts >= self.policy.max_attempts {
+                        if let Some(dead) = outbox.pending.remove(pos) {
+                            outbox.dead_letter(dead, self.policy.max_dead_letters);
+                        }
+                    } else {
+                        let delay = self.policy.delay_for(delivery.attempts);
+                        delivery.next_attempt_at = now_ms() + delay.as_millis() as u64;
+                    }
+                }
+            }
+            outbox.generation += 1;
+        }
+
+        self.dirty.notify_one();
+    }
+
+    /// Until the next delivery due to an endpoint with no task running.
+    fn time_until_next_due(&self) -> Duration {
+        let outbox = self.outbox.lock().unwrap();
+        let in_flight = self.in_flight.lock().unwrap();
+        let now = now_ms();
+        outbox
+            .pending
+            .iter()
+            .filter(|d| !in_flight.contains(&d.webhook_id))
+            .map(|d| Duration::from_millis(d.next_attempt_at.saturating_sub(now)))
+            .min()
+            .unwrap_or(self.policy.max_delay)
+    }
+}
+
+/// `sha256=<hex>` HMAC of `body` under `secret`, as sent in [`SIGNATURE_HEADER`].
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Check a received signature header against `body`.
+pub fn verify_signature(secret: &[u8], body: &[u8], header: &str) -> bool {
+    let Some(hex_sig) = header.strip_prefix("sha256=") else {
+        return false;
+    };
+    let Ok(sig) = hex::decode(hex_sig) else {
+        return false;
+    };
+
+    let mut mac = HmacSha256::new_from_slice(secret).expect("hmac accepts any key length");
+    mac.update(body);
+    mac.verify_slice(&sig).is_ok()
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,142 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::path::PathBuf;
+use zkcg_common::types::Hash;
+
+#[derive(Debug, Parser)]
+#[command(name = "zkcg", about = "Verify and inspect ZKCG proofs offline")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Verify a proof file against public inputs
+    Verify(VerifyArgs),
+    /// Replay a JSON-lines file of submissions through a verifier engine
+    Replay(ReplayArgs),
+    /// Re-verify a node's transition log against another backend or key
+    Reverify(ReverifyArgs),
+    /// Decode the envelope of a zkVM proof, and optionally its journal
+    #[cfg(feature = "zk-vm")]
+    Inspect(InspectArgs),
+    /// Decode a zkVM journal
+    #[cfg(feature = "zk-vm")]
+    Journal(JournalArgs),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BackendKind {
+    Zkvm,
+    Halo2,
+    /// Accepts every proof. Only in builds with the `dev-backends` feature.
+    #[cfg(any(test, feature = "dev-backends"))]
+    Stub,
+}
+
+impl From<BackendKind> for zkcg_verifier::backend::BackendKind {
+    fn from(kind: BackendKind) -> Self {
+        match kind {
+            BackendKind::Zkvm => Self::Zkvm,
+            BackendKind::Halo2 => Self::Halo2,
+            #[cfg(any(test, feature = "dev-backends"))]
+            BackendKind::Stub => Self::Stub,
+        }
+    }
+}
+
+/// Backend selection shared by every command that verifies proofs.
+#[derive(Debug, Args)]
+pub struct BackendArgs {
+    /// Proof backend to verify with
+    #[arg(long, value_enum)]
+    pub backend: BackendKind,
+
+    /// zkVM guest image id (hex); the one built into this binary when unset
+    #[arg(long, value_parser = parse_hash)]
+    pub image_id: Option<Hash>,
+
+    /// Halo2 params file; the bundled artifacts are used when unset
+    #[arg(long)]
+    pub params: Option<PathBuf>,
+
+    /// Serialized Halo2 verifying key; derived from the params when unset
+    #[arg(long, requires = "params")]
+    pub vk: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    /// Raw proof bytes
+    pub proof: PathBuf,
+
+    #[command(flatten)]
+    pub backend: BackendArgs,
+
+    #[arg(long)]
+    pub threshold: u64,
+
+    /// Hex-encoded 32-byte root; all zeroes when unset
+    #[arg(long, value_parser = parse_hash)]
+    pub old_state_root: Option<Hash>,
+
+    #[arg(long, default_value_t = 1)]
+    pub nonce: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct ReplayArgs {
+    /// One `SubmitProofRequest` JSON object per line
+    pub submissions: PathBuf,
+
+    #[command(flatten)]
+    pub backend: BackendArgs,
+
+    /// Starting state, as persisted by a node (`storage.state`); genesis when unset
+    #[arg(long)]
+    pub state: Option<PathBuf>,
+
+    /// Submissions verified in parallel per batch
+    #[arg(long, default_value_t = 1024, value_parser = clap::value_parser!(u64).range(1..))]
+    pub batch_size: u64,
+}
+
+#[derive(Debug, Args)]
+pub struct ReverifyArgs {
+    /// Transition log written by a node (`storage.transitions`)
+    pub log: PathBuf,
+
+    #[command(flatten)]
+    pub backend: BackendArgs,
+
+    /// Transitions verified in parallel per batch
+    #[arg(long, default_value_t = 1024, value_parser = clap::value_parser!(u64).range(1..))]
+    pub batch_size: u64,
+}
+
+#[cfg(feature = "zk-vm")]
+#[derive(Debug, Args)]
+pub struct InspectArgs {
+    /// zkVM proof file
+    pub proof: PathBuf,
+
+    /// Journal to decode and check against the envelope's journal digest
+    #[arg(long)]
+    pub journal: Option<PathBuf>,
+}
+
+#[cfg(feature = "zk-vm")]
+#[derive(Debug, Args)]
+pub struct JournalArgs {
+    /// Journal bytes as committed by the guest
+    pub journal: PathBuf,
+}
+
+/// Parse 32 hex-encoded bytes, with or without a `0x` prefix.
+pub fn parse_hash(s: &str) -> Result<Hash, String> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let bytes = hex::decode(s).map_err(|e| format!("not hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|b: Vec<u8>| format!("expected 32 bytes, got {}", b.len()))
+}
@@ -0,0 +1,66 @@
+use std::fmt;
+
+use zkcg_common::errors::ProtocolError;
+
+/// The proof verified, or an inspection succeeded.
+pub const OK: u8 = 0;
+/// Unreadable files or arguments the protocol never saw. clap itself exits
+/// with 2 on usage errors.
+pub const FAILURE: u8 = 1;
+/// A replay finished, but some of its entries were rejected.
+pub const REJECTIONS: u8 = 3;
+/// A re-verification finished, but the backend rejected logged transitions.
+pub const DIVERGENCES: u8 = 4;
+
+/// Exit code for a rejection, one per [`ProtocolError`] so scripts can branch
+/// on the reason without parsing output.
+pub fn for_error(err: &ProtocolError) -> u8 {
+    match err {
+        ProtocolError::InvalidFormat => 10,
+        ProtocolError::StateMismatch => 11,
+        ProtocolError::InvalidNonce => 12,
+        ProtocolError::InvalidProof => 13,
+        ProtocolError::PolicyViolation => 14,
+        ProtocolError::CommitmentMismatch => 15,
+        ProtocolError::UnauthorizedProver => 16,
+    }
+}
+
+/// Why a command did not succeed.
+#[derive(Debug)]
+pub enum Failure {
+    Rejected(ProtocolError),
+    /// Number of rejected replay entries; the report itself was written.
+    Rejections(usize),
+    /// Number of logged transitions the backend rejected; the report itself was written.
+    Divergences(usize),
+    Other(String),
+}
+
+impl Failure {
+    pub fn code(&self) -> u8 {
+        match self {
+            Failure::Rejected(err) => for_error(err),
+            Failure::Rejections(_) => REJECTIONS,
+            Failure::Divergences(_) => DIVERGENCES,
+            Failure::Other(_) => FAILURE,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Rejected(err) => write!(f, "rejected: {} ({})", err, err.code()),
+            Failure::Rejections(n) => write!(f, "{} entries rejected", n),
+            Failure::Divergences(n) => write!(f, "{} transitions diverged", n),
+            Failure::Other(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<ProtocolError> for Failure {
+    fn from(err: ProtocolError) -> Self {
+        Failure::Rejected(err)
+    }
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+
+use risc0_zkp::core::digest::Digest;
+use serde_json::{json, Value};
+use sha2::{Digest as _, Sha256};
+use zkcg_verifier::backend_zkvm::{ZkVmJournal, ZkVmProof};
+use zkcg_zkvm_host::method_id;
+
+use crate::args::{InspectArgs, JournalArgs};
+use crate::exit::Failure;
+use crate::{print_json, read_file};
+
+pub fn inspect(args: &InspectArgs, out: &mut dyn Write) -> Result<(), Failure> {
+    let proof_bytes = read_file(&args.proof)?;
+    let proof = ZkVmProof::decode(&proof_bytes)?;
+
+    let mut report = json!({
+        "proof_bytes": proof_bytes.len(),
+        "method_id": digest_hex(&proof.method_id),
+        "built_in_image": proof.method_id == method_id(),
+        "journal_digest": digest_hex(&proof.journal_digest),
+    });
+
+    if let Some(path) = &args.journal {
+        let journal_bytes = read_file(path)?;
+        let digest: [u8; 32] = Sha256::digest(&journal_bytes).into();
+        report["journal"] = journal_json(&ZkVmJournal::decode(&journal_bytes)?);
+        report["journal_digest_matches"] = Value::Bool(proof.journal_digest.as_bytes() == digest);
+    }
+
+    print_json(out, &report)
+}
+
+pub fn journal(args: &JournalArgs, out: &mut dyn Write) -> Result<(), Failure> {
+    let journal = ZkVmJournal::decode(&read_file(&args.journal)?)?;
+    print_json(out, &journal_json(&journal))
+}
+
+fn journal_json(journal: &ZkVmJournal) -> Value {
+    json!({
+        "threshold": journal.threshold,
+        "old_state_root": hex::encode(journal.old_state_root),
+        "nonce": journal.nonce,
+        "ok": journal.ok,
+    })
+}
+
+fn digest_hex(digest: &Digest) -> String {
+    hex::encode(digest.as_bytes())
+}
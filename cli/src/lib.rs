@@ -0,0 +1,50 @@
+pub mod args;
+pub mod exit;
+pub mod replay;
+pub mod reverify;
+pub mod verify;
+#[cfg(feature = "zk-vm")]
+pub mod inspect;
+
+use serde::Serialize;
+use std::{fs, io::Write, path::Path};
+
+use crate::args::{Cli, Command};
+use crate::exit::Failure;
+
+/// Run one command, writing its report to `out`.
+pub fn run(cli: &Cli, out: &mut dyn Write) -> Result<(), Failure> {
+    match &cli.command {
+        Command::Verify(args) => verify::run(args, out),
+        Command::Replay(args) => replay::run(args, out),
+        Command::Reverify(args) => reverify::run(args, out),
+        #[cfg(feature = "zk-vm")]
+        Command::Inspect(args) => inspect::inspect(args, out),
+        #[cfg(feature = "zk-vm")]
+        Command::Journal(args) => inspect::journal(args, out),
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, Failure> {
+    fs::read(path).map_err(|e| Failure::Other(format!("cannot read {}: {}", path.display(), e)))
+}
+
+fn print_json(out: &mut dyn Write, value: &impl Serialize) -> Result<(), Failure> {
+    let text = serde_json::to_string_pretty(value).map_err(|e| Failure::Other(e.to_string()))?;
+    writeln!(out, "{}", text).map_err(|e| Failure::Other(e.to_string()))
+}
+
+#[cfg(test)]
+mod test_support;
+
+#[cfg(test)]
+mod tests_verify;
+
+#[cfg(test)]
+mod tests_replay;
+
+#[cfg(test)]
+mod tests_reverify;
+
+#[cfg(all(test, feature = "zk-vm"))]
+mod tests_inspect;
@@ -0,0 +1,17 @@
+use clap::Parser;
+use std::{io, process::ExitCode};
+
+use zkcg_cli::args::Cli;
+use zkcg_cli::exit;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match zkcg_cli::run(&cli, &mut io::stdout()) {
+        Ok(()) => ExitCode::from(exit::OK),
+        Err(failure) => {
+            eprintln!("{}", failure);
+            ExitCode::from(failure.code())
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+};
+use zkcg_common::{errors::ProtocolError, state::ProtocolState, wire::SubmitProofRequest};
+use zkcg_verifier::engine::VerifierEngine;
+use zkcg_verifier::pipeline::Submission;
+
+use crate::args::ReplayArgs;
+use crate::exit::Failure;
+use crate::{print_json, read_file};
+use crate::verify::build_backend;
+
+/// Decode one input line, a JSON `SubmitProofRequest`.
+fn parse_line(line: &str, max_proof: usize) -> Result<Submission, ProtocolError> {
+    let req: SubmitProofRequest =
+        serde_json::from_str(line).map_err(|_| ProtocolError::InvalidFormat)?;
+    Submission::from_wire(&req, max_proof)
+}
+
+/// Outcome of one input line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Entry {
+    /// 1-based line number in the input
+    pub line: usize,
+    /// Absent when the line could not be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    /// `accepted` or `rejected`
+    pub status: &'static str,
+    /// SPEC §9 code of a rejection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl Entry {
+    fn new(line: usize, nonce: Option<u64>, result: Result<(), ProtocolError>) -> Self {
+        match result {
+            Ok(()) => Self {
+                line,
+                nonce,
+                status: "accepted",
+                code: None,
+                reason: None,
+            },
+            Err(err) => Self {
+                line,
+                nonce,
+                status: "rejected",
+                code: Some(err.code()),
+                reason: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FinalState {
+    /// Hex
+    pub state_root: String,
+    pub nonce: u64,
+    pub epoch: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub accepted: usize,
+    pub rejected: usize,
+    pub final_state: FinalState,
+    /// In input order
+    pub entries: Vec<Entry>,
+}
+
+/// Replay every line of `input` through `engine`.
+///
+/// Lines are read in batches of `batch_size`; each batch goes through
+/// [`VerifierEngine::process_batch`], so its proofs are verified in parallel
+/// and applied in nonce order. Unparseable lines are rejected with
+/// `ERR_INVALID_FORMAT` without stopping the replay; blank lines are skipped.
+pub fn replay(
+    engine: &mut VerifierEngine,
+    input: impl BufRead,
+    batch_size: usize,
+) -> Result<Report, Failure> {
+    let mut entries = Vec::new();
+    let mut batch: Vec<(usize, Submission)> = Vec::with_capacity(batch_size);
+    let max_proof = engine.backend().max_proof_size();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| Failure::Other(format!("cannot read submissions: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_line(&line, max_proof) {
+            Ok(submission) => batch.push((index + 1, submission)),
+            Err(err) => entries.push(Entry::new(index + 1, None, Err(err))),
+        }
+        if batch.len() >= batch_size {
+            apply_batch(engine, &mut batch, &mut entries);
+        }
+    }
+    apply_batch(engine, &mut batch, &mut entries);
+    entries.sort_by_key(|e| e.line);
+
+    let accepted = entries.iter().filter(|e| e.code.is_none()).count();
+    let state = engine.state();
+    Ok(Report {
+        accepted,
+        rejected: entries.len() - accepted,
+        final_state: FinalState {
+            state_root: hex::encode(state.state_root),
+            nonce: state.nonce,
+            epoch: state.epoch,
+        },
+        entries,
+    })
+}
+
+fn apply_batch(
+    engine: &mut VerifierEngine,
+    batch: &mut Vec<(usize, Submission)>,
+    entries: &mut Vec<Entry>,
+) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let (lines, submissions): (Vec<_>, Vec<_>) = batch.drain(..).unzip();
+    let nonces: Vec<u64> = submissions.iter().map(|s| s.public_inputs.nonce).collect();
+    let results = engine.process_batch(submissions);
+
+    for ((line, nonce), result) in lines.into_iter().zip(nonces).zip(results) {
+        entries.push(Entry::new(line, Some(nonce), result));
+    }
+}
+
+pub fn run(args: &ReplayArgs, out: &mut dyn Write) -> Result<(), Failure> {
+    let state = match &args.state {
+        Some(path) => serde_json::from_slice(&read_file(path)?)
+            .map_err(|e| Failure::Other(format!("invalid state file {}: {}", path.display(), e)))?,
+        None => ProtocolState::genesis(),
+    };
+    let backend = build_backend(&args.backend)?;
+    let mut engine = VerifierEngine::new(state, backend);
+
+    let input = File::open(&args.submissions).map_err(|e| {
+        Failure::Other(format!("cannot read {}: {}", args.submissions.display(), e))
+    })?;
+    let report = replay(&mut engine, BufReader::new(input), args.batch_size as usize)?;
+
+    print_json(out, &report)?;
+
+    match report.rejected {
+        0 => Ok(()),
+        n => Err(Failure::Rejections(n)),
+    }
+}
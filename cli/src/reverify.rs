@@ -0,0 +1,166 @@
+use rayon::prelude::*;
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Write},
+};
+use zkcg_common::{errors::ProtocolError, wire::LoggedTransition};
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::engine::check_proof;
+use zkcg_verifier::pipeline::Submission;
+use zkcg_verifier::{policy, signature};
+
+use crate::args::ReverifyArgs;
+use crate::exit::Failure;
+use crate::print_json;
+use crate::verify::build_backend;
+
+/// A logged transition the target backend does not accept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Divergence {
+    /// 1-based line number in the log
+    pub line: usize,
+    /// Absent when the line could not be parsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub submission_id: Option<String>,
+    /// Backend that originally accepted the transition
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accepted_by: Option<String>,
+    /// SPEC §9 code of the rejection
+    pub code: &'static str,
+    pub reason: String,
+}
+
+impl Divergence {
+    fn new(line: usize, logged: Option<&LoggedTransition>, err: ProtocolError) -> Self {
+        Self {
+            line,
+            nonce: logged.map(|t| t.submission.public_inputs.nonce),
+            submission_id: logged.map(|t| t.submission_id.clone()),
+            accepted_by: logged.map(|t| t.backend.clone()),
+            code: err.code(),
+            reason: err.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// Backend every proof was re-verified with
+    pub backend: &'static str,
+    pub checked: usize,
+    /// In log order
+    pub divergences: Vec<Divergence>,
+}
+
+/// Decode a logged submission, checking it against its recorded id.
+pub fn logged_submission(logged: &LoggedTransition) -> Result<Submission, ProtocolError> {
+    let submission = Submission::from_wire(&logged.submission, usize::MAX)?;
+
+    // an edited line no longer matches the id it was logged under
+    if hex::encode(submission.id()) != logged.submission_id {
+        return Err(ProtocolError::InvalidFormat);
+    }
+    Ok(submission)
+}
+
+/// Re-verify logged transitions against `backend`, in parallel.
+///
+/// Each proof (and signature) is checked on its own together with the
+/// policy, without replaying state, so one failure does not hide the ones
+/// after it. Every entry was accepted when it was logged: any `Err` is a
+/// divergence between the backend that accepted it and `backend`. Results
+/// are in the order given.
+pub fn reverify_batch(
+    backend: &dyn ProofBackend,
+    transitions: &[LoggedTransition],
+) -> Vec<Result<(), ProtocolError>> {
+    transitions
+        .par_iter()
+        .map(|logged| {
+            let s = logged_submission(logged)?;
+            if let Some(sig) = &s.signature {
+                signature::verify_submission(
+                    sig,
+                    &logged.channel,
+                    &s.proof_bytes,
+                    &s.public_inputs,
+                    &s.commitment,
+                )?;
+            }
+            check_proof(backend, &s.proof_bytes, &s.public_inputs)?;
+            policy::enforce(&s.public_inputs)
+        })
+        .collect()
+}
+
+/// Re-verify every transition in `log` against `backend`.
+///
+/// Lines are read in batches of `batch_size` and verified in parallel with
+/// [`reverify_batch`]. Unparseable lines are reported as
+/// `ERR_INVALID_FORMAT` divergences; blank lines are skipped.
+pub fn reverify(
+    backend: &dyn ProofBackend,
+    log: impl BufRead,
+    batch_size: usize,
+) -> Result<Report, Failure> {
+    let mut checked = 0;
+    let mut divergences = Vec::new();
+    let mut batch: Vec<(usize, LoggedTransition)> = Vec::with_capacity(batch_size);
+
+    for (index, line) in log.lines().enumerate() {
+        let line = line.map_err(|e| Failure::Other(format!("cannot read transition log: {}", e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        checked += 1;
+        match serde_json::from_str(&line) {
+            Ok(logged) => batch.push((index + 1, logged)),
+            Err(_) => divergences.push(Divergence::new(index + 1, None, ProtocolError::InvalidFormat)),
+        }
+        if batch.len() >= batch_size {
+            check_batch(backend, &mut batch, &mut divergences);
+        }
+    }
+    check_batch(backend, &mut batch, &mut divergences);
+    divergences.sort_by_key(|d| d.line);
+
+    Ok(Report {
+        backend: backend.name(),
+        checked,
+        divergences,
+    })
+}
+
+fn check_batch(
+    backend: &dyn ProofBackend,
+    batch: &mut Vec<(usize, LoggedTransition)>,
+    divergences: &mut Vec<Divergence>,
+) {
+    let (lines, logged): (Vec<_>, Vec<_>) = batch.drain(..).unzip();
+    let results = reverify_batch(backend, &logged);
+
+    for ((line, logged), result) in lines.into_iter().zip(&logged).zip(results) {
+        if let Err(err) = result {
+            divergences.push(Divergence::new(line, Some(logged), err));
+        }
+    }
+}
+
+pub fn run(args: &ReverifyArgs, out: &mut dyn Write) -> Result<(), Failure> {
+    let backend = build_backend(&args.backend)?;
+
+    let log = File::open(&args.log)
+        .map_err(|e| Failure::Other(format!("cannot read {}: {}", args.log.display(), e)))?;
+    let report = reverify(backend.as_ref(), BufReader::new(log), args.batch_size as usize)?;
+
+    print_json(out, &report)?;
+
+    match report.divergences.len() {
+        0 => Ok(()),
+        n => Err(Failure::Divergences(n)),
+    }
+}
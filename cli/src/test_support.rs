@@ -0,0 +1,9 @@
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+/// Temporary file holding `bytes`, removed when dropped.
+pub fn temp_file(bytes: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(bytes).unwrap();
+    file
+}
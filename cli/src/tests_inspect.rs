@@ -0,0 +1,62 @@
+use clap::Parser;
+use risc0_zkp::core::digest::Digest;
+use serde_json::Value;
+use zkcg_verifier::backend_zkvm::ZkVmProof;
+use zkcg_zkvm_host::method_id;
+
+use crate::args::Cli;
+use crate::exit::Failure;
+use crate::test_support::temp_file;
+
+fn run(args: &[&str]) -> (Result<(), Failure>, String) {
+    let cli = Cli::try_parse_from(std::iter::once("zkcg").chain(args.iter().copied())).unwrap();
+    let mut out = Vec::new();
+    let result = crate::run(&cli, &mut out);
+    (result, String::from_utf8(out).unwrap())
+}
+
+fn envelope(method_id: Digest) -> Vec<u8> {
+    bincode::serialize(&ZkVmProof {
+        method_id,
+        journal_digest: Digest::from([7u8; 32]),
+    })
+    .unwrap()
+}
+
+#[test]
+fn envelope_header_is_printed() {
+    let proof = temp_file(&envelope(method_id()));
+    let (result, out) = run(&["inspect", proof.path().to_str().unwrap()]);
+    result.unwrap();
+
+    let report: Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(report["built_in_image"], true);
+    assert_eq!(report["journal_digest"], "07".repeat(32));
+    assert!(report.get("journal").is_none());
+}
+
+#[test]
+fn foreign_image_verifies_only_when_pinned() {
+    let foreign = [9u8; 32];
+    let proof = temp_file(&envelope(Digest::from(foreign)));
+    let path = proof.path().to_str().unwrap();
+
+    let (result, _) = run(&["verify", path, "--backend", "zkvm", "--threshold", "10"]);
+    assert_eq!(result.unwrap_err().code(), 13);
+
+    let image = hex::encode(foreign);
+    let pinned = ["verify", path, "--backend", "zkvm", "--threshold", "10", "--image-id", &image];
+    let (result, _) = run(&pinned);
+    assert!(result.is_ok());
+}
+
+#[test]
+fn undecodable_inputs_are_rejected() {
+    let proof = temp_file(b"not a proof");
+    let (result, _) = run(&["inspect", proof.path().to_str().unwrap()]);
+    assert_eq!(result.unwrap_err().code(), 13);
+
+    let journal = temp_file(b"\x01");
+    let (result, _) = run(&["journal", journal.path().to_str().unwrap()]);
+    assert_eq!(result.unwrap_err().code(), 10);
+}
@@ -0,0 +1,96 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::io::Cursor;
+use zkcg_common::state::ProtocolState;
+use zkcg_verifier::backend_stub::StubBackend;
+use zkcg_verifier::engine::VerifierEngine;
+
+use crate::args::Cli;
+use crate::exit::{self, Failure};
+use crate::replay::replay;
+use crate::test_support::temp_file;
+
+fn line(nonce: u64, old_root: u8, new_root: u8) -> String {
+    let (old_root, new_root) = ([old_root; 32], [new_root; 32]);
+    json!({
+        "proof": STANDARD.encode(b"proof"),
+        "public_inputs": { "threshold": 10, "old_state_root": old_root, "nonce": nonce },
+        "new_state_commitment": new_root,
+    })
+    .to_string()
+}
+
+fn engine() -> VerifierEngine {
+    VerifierEngine::new(ProtocolState::genesis(), Box::new(StubBackend))
+}
+
+#[test]
+fn chain_is_replayed_across_batches() {
+    // out of order within the first batch, which is applied in nonce order
+    let input = [line(2, 1, 2), line(1, 0, 1), line(3, 2, 3)].join("\n");
+    let mut engine = engine();
+
+    let report = replay(&mut engine, Cursor::new(input), 2).unwrap();
+
+    assert_eq!(report.accepted, 3);
+    assert_eq!(report.rejected, 0);
+    let lines: Vec<_> = report.entries.iter().map(|e| (e.line, e.nonce)).collect();
+    assert_eq!(lines, vec![(1, Some(2)), (2, Some(1)), (3, Some(3))]);
+    assert_eq!(report.final_state.nonce, 3);
+    assert_eq!(report.final_state.state_root, "03".repeat(32));
+}
+
+#[test]
+fn rejections_carry_their_reason_and_line() {
+    let input = [
+        line(1, 0, 1),
+        String::new(),
+        "{ not json".to_string(),
+        line(2, 9, 2),
+        line(2, 1, 2),
+    ]
+    .join("\n");
+    let mut engine = engine();
+
+    let report = replay(&mut engine, Cursor::new(input), 1024).unwrap();
+
+    let summary: Vec<_> = report
+        .entries
+        .iter()
+        .map(|e| (e.line, e.status, e.code))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (1, "accepted", None),
+            (3, "rejected", Some("ERR_INVALID_FORMAT")),
+            (4, "rejected", Some("ERR_STATE_MISMATCH")),
+            (5, "accepted", None),
+        ]
+    );
+    assert_eq!(report.entries[2].reason.as_deref(), Some("state mismatch"));
+    assert_eq!(report.final_state.nonce, 2);
+}
+
+#[test]
+fn report_is_written_even_when_entries_are_rejected() {
+    let log = temp_file([line(1, 0, 1), line(1, 0, 1)].join("\n").as_bytes());
+
+    let cli = Cli::try_parse_from([
+        "zkcg",
+        "replay",
+        log.path().to_str().unwrap(),
+        "--backend",
+        "stub",
+    ])
+    .unwrap();
+    let mut out = Vec::new();
+    let failure = crate::run(&cli, &mut out).unwrap_err();
+
+    assert!(matches!(failure, Failure::Rejections(1)));
+    assert_eq!(failure.code(), exit::REJECTIONS);
+    let report: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["accepted"], 1);
+    assert_eq!(report["final_state"]["nonce"], 1);
+}
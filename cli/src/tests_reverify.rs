@@ -0,0 +1,144 @@
+use clap::Parser;
+use serde_json::Value;
+use std::io::Cursor;
+use zkcg_common::{errors::ProtocolError, types::Commitment, wire::LoggedTransition};
+use zkcg_verifier::backend::ProofBackend;
+use zkcg_verifier::backend_stub::StubBackend;
+use zkcg_verifier::engine::PublicInputs;
+use zkcg_verifier::pipeline::Submission;
+
+use crate::args::Cli;
+use crate::exit::{self, Failure};
+use crate::reverify::{logged_submission, reverify, reverify_batch};
+use crate::test_support::temp_file;
+
+/// Stands in for an upgraded backend that no longer accepts `b"old"` proofs.
+struct UpgradedBackend;
+
+impl ProofBackend for UpgradedBackend {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        _public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        if proof_bytes == b"old" {
+            Err(ProtocolError::InvalidProof)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "upgraded"
+    }
+}
+
+fn logged(nonce: u64, proof: &[u8]) -> String {
+    let submission = Submission {
+        proof_bytes: proof.to_vec(),
+        public_inputs: PublicInputs {
+            threshold: 10,
+            old_state_root: [nonce as u8 - 1; 32],
+            nonce,
+        },
+        commitment: Commitment([nonce as u8; 32]),
+        signature: None,
+    };
+    serde_json::to_string(&log_entry(&submission)).unwrap()
+}
+
+fn log_entry(submission: &Submission) -> LoggedTransition {
+    LoggedTransition {
+        submission_id: hex::encode(submission.id()),
+        backend: "stub".into(),
+        channel: [0u8; 32],
+        epoch: 0,
+        submission: submission.to_wire(),
+        accepted_at: 0,
+    }
+}
+
+#[test]
+fn divergences_are_reported_in_log_order() {
+    let log = [
+        logged(1, b"new"),
+        logged(2, b"old"),
+        String::new(),
+        "{ not json".to_string(),
+        logged(3, b"old"),
+    ]
+    .join("\n");
+
+    let report = reverify(&UpgradedBackend, Cursor::new(log), 2).unwrap();
+
+    assert_eq!(report.backend, "upgraded");
+    assert_eq!(report.checked, 4);
+    let summary: Vec<_> = report
+        .divergences
+        .iter()
+        .map(|d| (d.line, d.nonce, d.code))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (2, Some(2), "ERR_PROOF_INVALID"),
+            (4, None, "ERR_INVALID_FORMAT"),
+            (5, Some(3), "ERR_PROOF_INVALID"),
+        ]
+    );
+    assert_eq!(report.divergences[0].accepted_by.as_deref(), Some("stub"));
+}
+
+#[test]
+fn matching_backend_reports_no_divergence() {
+    let log = [logged(1, b"old"), logged(2, b"new")].join("\n");
+
+    let report = reverify(&StubBackend, Cursor::new(log), 1024).unwrap();
+
+    assert_eq!(report.checked, 2);
+    assert!(report.divergences.is_empty());
+}
+
+#[test]
+fn divergences_set_their_own_exit_code() {
+    let log = temp_file([logged(1, b"p1"), "{}".to_string()].join("\n").as_bytes());
+
+    let cli = Cli::try_parse_from([
+        "zkcg",
+        "reverify",
+        log.path().to_str().unwrap(),
+        "--backend",
+        "stub",
+    ])
+    .unwrap();
+    let mut out = Vec::new();
+    let failure = crate::run(&cli, &mut out).unwrap_err();
+
+    assert!(matches!(failure, Failure::Divergences(1)));
+    assert_eq!(failure.code(), exit::DIVERGENCES);
+    let report: Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["checked"], 2);
+    assert_eq!(report["divergences"][0]["line"], 2);
+}
+
+#[test]
+fn edited_entry_is_rejected_as_malformed() {
+    let submission = Submission {
+        proof_bytes: b"p1".to_vec(),
+        public_inputs: PublicInputs {
+            threshold: 10,
+            old_state_root: [0; 32],
+            nonce: 1,
+        },
+        commitment: Commitment([1; 32]),
+        signature: None,
+    };
+    let mut entry = log_entry(&submission);
+    entry.submission.public_inputs.threshold = 11;
+
+    assert!(matches!(logged_submission(&entry), Err(ProtocolError::InvalidFormat)));
+    assert!(matches!(
+        reverify_batch(&StubBackend, &[entry]).as_slice(),
+        [Err(ProtocolError::InvalidFormat)]
+    ));
+}
@@ -0,0 +1,114 @@
+use clap::Parser;
+use zkcg_common::errors::ProtocolError;
+
+use crate::args::{parse_hash, Cli};
+use crate::exit::{self, Failure};
+use crate::test_support::temp_file;
+
+fn run(args: &[&str]) -> (Result<(), Failure>, String) {
+    let cli = Cli::try_parse_from(std::iter::once("zkcg").chain(args.iter().copied())).unwrap();
+    let mut out = Vec::new();
+    let result = crate::run(&cli, &mut out);
+    (result, String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn valid_proof_exits_zero() {
+    let proof = temp_file(b"proof");
+    let (result, out) = run(&[
+        "verify",
+        proof.path().to_str().unwrap(),
+        "--backend",
+        "stub",
+        "--threshold",
+        "10",
+    ]);
+
+    assert!(result.is_ok());
+    assert_eq!(out, "valid (stub backend, 5 byte proof)\n");
+}
+
+#[test]
+fn rejection_exits_with_its_own_code() {
+    let proof = temp_file(b"");
+    let (result, _) = run(&[
+        "verify",
+        proof.path().to_str().unwrap(),
+        "--backend",
+        "stub",
+        "--threshold",
+        "10",
+    ]);
+
+    let failure = result.unwrap_err();
+    assert!(matches!(failure, Failure::Rejected(ProtocolError::InvalidFormat)));
+    assert_eq!(failure.code(), 10);
+    assert_eq!(failure.to_string(), "rejected: invalid message format (ERR_INVALID_FORMAT)");
+}
+
+#[test]
+fn unreadable_file_is_a_plain_failure() {
+    let (result, _) = run(&[
+        "verify",
+        "/nonexistent/proof.bin",
+        "--backend",
+        "stub",
+        "--threshold",
+        "10",
+    ]);
+    assert_eq!(result.unwrap_err().code(), exit::FAILURE);
+}
+
+#[test]
+fn backend_specific_flags_are_checked() {
+    let proof = temp_file(b"proof");
+    let root = "00".repeat(32);
+    let (result, _) = run(&[
+        "verify",
+        proof.path().to_str().unwrap(),
+        "--backend",
+        "stub",
+        "--threshold",
+        "10",
+        "--image-id",
+        &root,
+    ]);
+    assert_eq!(result.unwrap_err().code(), exit::FAILURE);
+
+    // --vk without --params is a usage error
+    let args = ["zkcg", "verify", "p", "--backend", "halo2", "--threshold", "1", "--vk", "vk"];
+    assert!(Cli::try_parse_from(args).is_err());
+}
+
+#[test]
+fn every_protocol_error_has_a_distinct_code() {
+    use ProtocolError::*;
+
+    let codes: Vec<u8> = [
+        InvalidFormat,
+        StateMismatch,
+        InvalidNonce,
+        InvalidProof,
+        PolicyViolation,
+        CommitmentMismatch,
+        UnauthorizedProver,
+    ]
+    .iter()
+    .map(exit::for_error)
+    .collect();
+
+    let mut unique = codes.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), codes.len());
+    let reserved = [exit::OK, exit::FAILURE, 2, exit::REJECTIONS, exit::DIVERGENCES];
+    assert!(codes.iter().all(|c| !reserved.contains(c)));
+}
+
+#[test]
+fn hashes_parse_with_or_without_prefix() {
+    assert_eq!(parse_hash(&"ab".repeat(32)).unwrap(), [0xab; 32]);
+    assert_eq!(parse_hash(&format!("0x{}", "01".repeat(32))).unwrap(), [1; 32]);
+    assert!(parse_hash("abcd").is_err());
+    assert!(parse_hash("zz").is_err());
+}
@@ -0,0 +1,43 @@
+use std::io::Write;
+
+use zkcg_verifier::backend::{BackendSpec, ProofBackend};
+use zkcg_verifier::engine::{check_proof, PublicInputs};
+
+use crate::args::{BackendArgs, BackendKind, VerifyArgs};
+use crate::exit::Failure;
+use crate::read_file;
+
+/// Instantiate the backend selected by `args`.
+pub fn build_backend(args: &BackendArgs) -> Result<Box<dyn ProofBackend>, Failure> {
+    let spec = BackendSpec {
+        kind: args.backend.into(),
+        zkvm_image_id: args.image_id,
+        halo2_params: args.params.clone(),
+        halo2_vk: args.vk.clone(),
+        max_proof_size: None,
+    };
+    let backend = spec.build().map_err(Failure::Other)?;
+
+    if args.backend == BackendKind::Zkvm {
+        eprintln!(
+            "warning: zkvm receipts are not cryptographically verified; \
+             only the envelope's image id is compared"
+        );
+    }
+    Ok(backend)
+}
+
+pub fn run(args: &VerifyArgs, out: &mut dyn Write) -> Result<(), Failure> {
+    let proof = read_file(&args.proof)?;
+    let backend = build_backend(&args.backend)?;
+
+    let inputs = PublicInputs {
+        threshold: args.threshold,
+        old_state_root: args.old_state_root.unwrap_or_default(),
+        nonce: args.nonce,
+    };
+    check_proof(backend.as_ref(), &proof, &inputs)?;
+
+    writeln!(out, "valid ({} backend, {} byte proof)", backend.name(), proof.len())
+        .map_err(|e| Failure::Other(e.to_string()))
+}
@@ -0,0 +1,343 @@
+use std::{future::Future, time::Duration};
+
+use reqwest::{header::RETRY_AFTER, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use zkcg_common::wire::{self, SubmitProofRequest};
+
+use crate::error::{self, ClientError};
+use crate::types::{
+    CompleteJobBody, DemoProveBody, DemoProveResponseBody, DemoVerifyBody, DemoVerifyResponseBody,
+    Job, JobStatus, NodeState, ProveBody, RegisterWorkerBody, Submission,
+    Submitted, WorkerJob, WorkerRegistration,
+};
+
+/// Header carrying the API key.
+const API_KEY_HEADER: &str = "x-api-key";
+/// Header carrying a client-chosen retry key.
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// How often [`Client::wait_for_job`] polls by default.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How [`Client::submit_latest`] retries submissions that lost a race for
+/// the next nonce.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Submissions attempted in total, including the first.
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub request_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn delay_for(&self, attempts: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempts.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
+
+/// Client for one verifier node.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), zkcg_client::ClientError> {
+/// let node = zkcg_client::Client::new("http://localhost:8080")?.with_api_key("zkcg_...");
+/// let state = node.state().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+    retry: RetryPolicy,
+    poll_interval: Duration,
+}
+
+impl Client {
+    pub fn new(base_url: impl Into<String>) -> Result<Self, ClientError> {
+        Self::with_retry(base_url, RetryPolicy::default())
+    }
+
+    pub fn with_retry(base_url: impl Into<String>, retry: RetryPolicy) -> Result<Self, ClientError> {
+        let http = reqwest::Client::builder()
+            .timeout(retry.request_timeout)
+            .build()?;
+        Ok(Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            retry,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    /// Authenticate protocol requests with `key` (sent as `X-API-Key`).
+    pub fn with_api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// How often [`Client::wait_for_job`] asks for a job's status.
+    pub fn with_poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// `GET /v1/state`: the node's latest accepted state. Needs an observer key.
+    pub async fn state(&self) -> Result<NodeState, ClientError> {
+        self.send(self.http.get(self.url("/v1/state"))).await
+    }
+
+    /// `POST /v1/submit-proof`.
+    ///
+    /// Resubmitting an accepted or queued submission is answered from the
+    /// node's receipts with `replayed` set, so a timed-out call can simply be
+    /// repeated.
+    pub async fn submit(&self, submission: &Submission) -> Result<Submitted, ClientError> {
+        self.submit_request(submission, None).await
+    }
+
+    /// [`Client::submit`] under an idempotency key, which the node binds to the
+    /// first submission it is used with.
+    pub async fn submit_with_key(
+        &self,
+        submission: &Submission,
+        idempotency_key: &str,
+    ) -> Result<Submitted, ClientError> {
+        self.submit_request(submission, Some(idempotency_key)).await
+    }
+
+    async fn submit_request(
+        &self,
+        submission: &Submission,
+        idempotency_key: Option<&str>,
+    ) -> Result<Submitted, ClientError> {
+        let mut req = self
+            .http
+            .post(self.url("/v1/submit-proof"))
+            .json(&SubmitProofRequest::from(submission));
+        if let Some(key) = idempotency_key {
+            req = req.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+        self.send(req).await
+    }
+
+    /// Submit a transition built on the node's current state.
+    ///
+    /// `build` is handed the latest [`NodeState`] and returns a submission
+    /// proved (and signed) against it. If another transition takes the nonce
+    /// first, the node answers `ERR_NONCE_INVALID` or `ERR_STATE_MISMATCH`; the
+    /// state is then re-read and `build` called again, up to
+    /// [`RetryPolicy::max_attempts`] times with backoff. Every other error is
+    /// returned as is.
+    pub async fn submit_latest<F, Fut>(&self, mut build: F) -> Result<Submitted, ClientError>
+    where
+        F: FnMut(NodeState) -> Fut,
+        Fut: Future<Output = Result<Submission, ClientError>>,
+    {
+        let mut attempts = 0;
+        loop {
+            let state = self.state().await?;
+            let submission = build(state).await?;
+            attempts += 1;
+
+            match self.submit(&submission).await {
+                Err(err) if err.is_state_conflict() && attempts < self.retry.max_attempts => {
+                    tokio::time::sleep(self.retry.delay_for(attempts)).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Have the node prove `secret_value` against `threshold` on top of its
+    /// current state, waiting for the proving job to finish.
+    ///
+    /// The result is ready for [`Client::submit`], or for signing first, and
+    /// goes stale once another transition is accepted; calling this from the
+    /// `build` closure of [`Client::submit_latest`] proves again on a conflict.
+    pub async fn prove(&self, secret_value: u64, threshold: u64) -> Result<Submission, ClientError> {
+        let job = self.start_prove(secret_value, threshold).await?;
+        self.wait_for_job(&job.id).await
+    }
+
+    /// `POST /v1/prove`: queue a proving job without waiting for it.
+    pub async fn start_prove(&self, secret_value: u64, threshold: u64) -> Result<Job, ClientError> {
+        let req = self.http.post(self.url("/v1/prove")).json(&ProveBody {
+            secret_value,
+            threshold,
+        });
+        self.send(req).await
+    }
+
+    /// `GET /v1/jobs/{id}`.
+    pub async fn job(&self, id: &str) -> Result<Job, ClientError> {
+        self.send(self.http.get(self.url(&format!("/v1/jobs/{}", id)))).await
+    }
+
+    /// `DELETE /v1/jobs/{id}`.
+    pub async fn cancel_job(&self, id: &str) -> Result<Job, ClientError> {
+        self.send(self.http.delete(self.url(&format!("/v1/jobs/{}", id)))).await
+    }
+
+    /// Poll job `id` until it finishes and return its submission; a failed or
+    /// cancelled job is [`ClientError::JobFailed`].
+    pub async fn wait_for_job(&self, id: &str) -> Result<Submission, ClientError> {
+        loop {
+            let job = self.job(id).await?;
+            match job.status {
+                JobStatus::Succeeded => {
+                    return job
+                        .result
+                        .ok_or_else(|| ClientError::InvalidResponse("succeeded job without a result".into()));
+                }
+                JobStatus::Failed | JobStatus::Cancelled => {
+                    return Err(ClientError::JobFailed {
+                        id: job.id,
+                        status: job.status,
+                        message: job.error.unwrap_or_else(|| "cancelled".into()),
+                    });
+                }
+                JobStatus::Queued | JobStatus::Running => {
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+            }
+        }
+    }
+
+    /// `POST /v1/workers`: register as a remote prover for `backends`. Needs a
+    /// worker key; the node refuses workers that cannot prove for its backend.
+    pub async fn register_worker(
+        &self,
+        name: &str,
+        backends: &[&str],
+    ) -> Result<WorkerRegistration, ClientError> {
+        let req = self
+            .http
+            .post(self.url("/v1/workers"))
+            .json(&RegisterWorkerBody { name, backends });
+        self.send(req).await
+    }
+
+    /// `POST /v1/workers/{id}/heartbeat`.
+    pub async fn worker_heartbeat(&self, worker_id: &str) -> Result<(), ClientError> {
+        let url = self.url(&format!("/v1/workers/{}/heartbeat", worker_id));
+        self.execute(self.http.post(url)).await.map(drop)
+    }
+
+    /// `POST /v1/workers/{id}/claim`: the oldest queued job, or `None` when
+    /// nothing is queued.
+    pub async fn claim_job(&self, worker_id: &str) -> Result<Option<WorkerJob>, ClientError> {
+        let url = self.url(&format!("/v1/workers/{}/claim", worker_id));
+        let res = self.execute(self.http.post(url)).await?;
+        if res.status() == StatusCode::NO_CONTENT {
+            return Ok(None);
+        }
+        decode_body(res).await.map(Some)
+    }
+
+    /// `POST /v1/workers/{id}/jobs/{job_id}`: report a proof, or why proving failed.
+    pub async fn complete_job(
+        &self,
+        worker_id: &str,
+        job_id: &str,
+        outcome: Result<&[u8], &str>,
+    ) -> Result<(), ClientError> {
+        let url = self.url(&format!("/v1/workers/{}/jobs/{}", worker_id, job_id));
+        let body = match outcome {
+            Ok(proof) => CompleteJobBody {
+                proof: Some(wire::encode_proof(proof)),
+                error: None,
+            },
+            Err(error) => CompleteJobBody {
+                proof: None,
+                error: Some(error),
+            },
+        };
+        self.execute(self.http.post(url).json(&body)).await.map(drop)
+    }
+
+    /// `POST /demo/prove`: a stateless demo proof.
+    pub async fn demo_prove(&self, score: u64, threshold: u64) -> Result<Vec<u8>, ClientError> {
+        let req = self
+            .http
+            .post(self.url("/demo/prove"))
+            .json(&DemoProveBody { score, threshold });
+        let res: DemoProveResponseBody = self.send(req).await?;
+        decode_proof(&res.proof)
+    }
+
+    /// `POST /demo/verify`: whether `proof` verifies against `threshold`.
+    pub async fn demo_verify(&self, proof: &[u8], threshold: u64) -> Result<bool, ClientError> {
+        let req = self.http.post(self.url("/demo/verify")).json(&DemoVerifyBody {
+            proof: wire::encode_proof(proof),
+            threshold,
+        });
+        let res: DemoVerifyResponseBody = self.send(req).await?;
+        Ok(res.verified)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, req: RequestBuilder) -> Result<T, ClientError> {
+        decode_body(self.execute(req).await?).await
+    }
+
+    /// Send `req` with the API key; unsuccessful responses become errors.
+    async fn execute(&self, mut req: RequestBuilder) -> Result<Response, ClientError> {
+        if let Some(key) = &self.api_key {
+            req = req.header(API_KEY_HEADER, key);
+        }
+        let res = req.send().await?;
+        if !res.status().is_success() {
+            return Err(decode_error(res).await);
+        }
+        Ok(res)
+    }
+}
+
+async fn decode_body<T: DeserializeOwned>(res: Response) -> Result<T, ClientError> {
+    let body = res.bytes().await?;
+    serde_json::from_slice(&body).map_err(|e| ClientError::InvalidResponse(e.to_string()))
+}
+
+async fn decode_error(res: Response) -> ClientError {
+    let status = res.status().as_u16();
+    let retry_after = res
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .map(Duration::from_secs);
+    let code = res
+        .headers()
+        .get(wire::ERROR_CODE_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    match res.text().await {
+        Ok(body) => error::decode(status, retry_after, code.as_deref(), body),
+        Err(e) => e.into(),
+    }
+}
+
+fn decode_proof(b64: &str) -> Result<Vec<u8>, ClientError> {
+    wire::decode_proof(b64, usize::MAX)
+        .map_err(|_| ClientError::InvalidResponse("proof is not base64".into()))
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use thiserror::Error;
+use zkcg_common::{errors::ProtocolError, wire};
+
+use crate::types::JobStatus;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The node rejected the request for a protocol reason (SPEC §9).
+    #[error("rejected: {0} ({code})", code = .0.code())]
+    Rejected(ProtocolError),
+
+    /// `429`; `retry_after` comes from the node's `Retry-After` header.
+    #[error("rate limited")]
+    RateLimited { retry_after: Option<Duration> },
+
+    /// The node does not know the worker, e.g. after it timed out or the
+    /// node restarted; the worker has to register again.
+    #[error("unknown worker")]
+    UnknownWorker,
+
+    /// Any other unsuccessful status, with the node's plain-text message.
+    #[error("{status}: {message}")]
+    Status { status: u16, message: String },
+
+    /// A proving job ended without a proof: it failed or was cancelled.
+    #[error("job {id} ended {status:?}: {message}")]
+    JobFailed {
+        id: String,
+        status: JobStatus,
+        message: String,
+    },
+
+    #[error("transport error: {0}")]
+    Transport(#[from] reqwest::Error),
+
+    /// The node answered with a body this client cannot decode.
+    #[error("invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+impl ClientError {
+    /// Whether the submission lost a race for the next nonce, so rebuilding
+    /// it against a fresh state may succeed.
+    pub fn is_state_conflict(&self) -> bool {
+        matches!(
+            self,
+            ClientError::Rejected(ProtocolError::InvalidNonce | ProtocolError::StateMismatch)
+        )
+    }
+
+    /// Whether the node does not know the worker; see
+    /// [`ClientError::UnknownWorker`].
+    pub fn is_unknown_worker(&self) -> bool {
+        matches!(self, ClientError::UnknownWorker)
+    }
+}
+
+/// Decode an unsuccessful response.
+///
+/// The node marks protocol rejections with their SPEC §9 `code` in the
+/// `x-zkcg-error-code` header, and unknown workers with
+/// [`wire::UNKNOWN_WORKER_CODE`]; anything else becomes [`ClientError::Status`].
+pub(crate) fn decode(
+    status: u16,
+    retry_after: Option<Duration>,
+    code: Option<&str>,
+    body: String,
+) -> ClientError {
+    if status == 429 {
+        return ClientError::RateLimited { retry_after };
+    }
+
+    if code == Some(wire::UNKNOWN_WORKER_CODE) {
+        return ClientError::UnknownWorker;
+    }
+
+    match code.and_then(ProtocolError::from_code) {
+        Some(err) => ClientError::Rejected(err),
+        None => ClientError::Status {
+            status,
+            message: body,
+        },
+    }
+}
@@ -0,0 +1,19 @@
+//! Typed client for the HTTP API of a ZKCG verifier node.
+//!
+//! Requests are built from `zkcg_common` types; proofs are base64-encoded and
+//! keys hex-encoded on the wire, and rejections are decoded back into
+//! [`zkcg_common::errors::ProtocolError`].
+
+pub mod client;
+pub mod error;
+pub mod types;
+
+pub use client::{Client, RetryPolicy};
+pub use error::ClientError;
+pub use types::{
+    Job, JobStatus, NodeState, PublicInputs, Receipt, Submission, SubmissionSignature,
+    SubmitStatus, Submitted, WorkerJob, WorkerRegistration,
+};
+
+#[cfg(test)]
+mod tests_client;
@@ -0,0 +1,302 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use zkcg_common::{errors::ProtocolError, types::Commitment};
+
+use crate::error::decode;
+use crate::{
+    Client, ClientError, JobStatus, PublicInputs, RetryPolicy, Submission, SubmissionSignature,
+    SubmitStatus,
+};
+
+/// Body and idempotency key of a submission.
+type Seen = (Value, Option<String>);
+
+/// Stand-in node that answers submissions with a nonce conflict, advancing
+/// its state as if another submitter got there first, until it reaches
+/// nonce `contested`.
+#[derive(Clone, Default)]
+struct Node {
+    nonce: Arc<AtomicU64>,
+    contested: u64,
+    last: Arc<Mutex<Option<Seen>>>,
+    /// Threshold of the last proving job and how often it was polled
+    job: Arc<Mutex<(Value, u64)>>,
+}
+
+async fn state(State(node): State<Node>) -> Json<Value> {
+    let nonce = node.nonce.load(Ordering::SeqCst);
+    let root = [nonce as u8; 32];
+    Json(json!({
+        "channel": hex::encode([6u8; 32]),
+        "state_root": root,
+        "nonce": nonce,
+        "epoch": 0,
+        "provers": [hex::encode([9u8; 32])],
+    }))
+}
+
+async fn submit(
+    State(node): State<Node>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<Json<Value>, (StatusCode, [(&'static str, &'static str); 1], &'static str)> {
+    let key = headers
+        .get("idempotency-key")
+        .map(|v| v.to_str().unwrap().to_string());
+    *node.last.lock().unwrap() = Some((body.clone(), key));
+
+    let current = node.nonce.load(Ordering::SeqCst);
+    if current < node.contested {
+        node.nonce.fetch_add(1, Ordering::SeqCst);
+        return Err((
+            StatusCode::CONFLICT,
+            [("x-zkcg-error-code", "ERR_NONCE_INVALID")],
+            "invalid nonce",
+        ));
+    }
+
+    let nonce = body["public_inputs"]["nonce"].as_u64().unwrap();
+    Ok(Json(json!({
+        "status": "accepted",
+        "receipt": {
+            "submission_id": hex::encode([1u8; 32]),
+            "nonce": nonce,
+            "epoch": 0,
+            "state_root": body["new_state_commitment"],
+            "accepted_at": 1,
+        },
+        "replayed": false,
+    })))
+}
+
+async fn demo_verify(Json(body): Json<Value>) -> Json<Value> {
+    Json(json!({ "verified": body["proof"] == "AQID" }))
+}
+
+async fn prove(State(node): State<Node>, Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    *node.job.lock().unwrap() = (body["threshold"].clone(), 0);
+    let job = json!({ "id": "job-1", "status": "queued", "threshold": body["threshold"], "created_at": 1 });
+    (StatusCode::ACCEPTED, Json(job))
+}
+
+/// Reports the job running on the first poll, and proved against the
+/// state current at the second.
+async fn job(State(node): State<Node>, Path(id): Path<String>) -> Json<Value> {
+    if id == "broken" {
+        return Json(json!({
+            "id": id, "status": "failed", "threshold": 10, "created_at": 1, "error": "policy violation",
+        }));
+    }
+
+    let mut job = node.job.lock().unwrap();
+    job.1 += 1;
+    if job.1 == 1 {
+        return Json(json!({ "id": id, "status": "running", "threshold": job.0, "created_at": 1 }));
+    }
+
+    let nonce = node.nonce.load(Ordering::SeqCst);
+    let (root, commitment) = ([nonce as u8; 32], [42u8; 32]);
+    Json(json!({
+        "id": id,
+        "status": "succeeded",
+        "threshold": job.0,
+        "created_at": 1,
+        "result": {
+            "proof": "AQID",
+            "public_inputs": { "threshold": job.0, "old_state_root": root, "nonce": nonce + 1 },
+            "new_state_commitment": commitment,
+        },
+    }))
+}
+
+async fn rate_limited() -> (StatusCode, [(&'static str, &'static str); 1], &'static str) {
+    (StatusCode::TOO_MANY_REQUESTS, [("retry-after", "3")], "rate limit exceeded")
+}
+
+async fn serve(node: Node) -> String {
+    let app = Router::new()
+        .route("/v1/state", get(state))
+        .route("/v1/submit-proof", post(submit))
+        .route("/v1/prove", post(prove))
+        .route("/v1/jobs/:id", get(job))
+        .route("/demo/verify", post(demo_verify))
+        .route("/demo/prove", post(rate_limited))
+        .with_state(node);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/", addr)
+}
+
+fn quick_retry(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        ..RetryPolicy::default()
+    }
+}
+
+fn submission(inputs: PublicInputs) -> Submission {
+    Submission {
+        proof: vec![1, 2, 3],
+        public_inputs: inputs,
+        new_state_commitment: Commitment([7; 32]),
+        signature: None,
+    }
+}
+
+#[tokio::test]
+async fn submission_is_encoded_for_the_wire() {
+    let node = Node::default();
+    let client = Client::new(serve(node.clone()).await).unwrap();
+
+    let mut signed = submission(PublicInputs {
+        threshold: 10,
+        old_state_root: [0; 32],
+        nonce: 1,
+    });
+    signed.signature = Some(SubmissionSignature {
+        public_key: [4; 32],
+        signature: [5; 64],
+    });
+    let submitted = client.submit_with_key(&signed, "retry-1").await.unwrap();
+
+    assert_eq!(submitted.status, SubmitStatus::Accepted);
+    assert!(!submitted.replayed);
+    let receipt = submitted.receipt.unwrap();
+    assert_eq!(receipt.submission_id, [1; 32]);
+    assert_eq!(receipt.state_root, [7; 32]);
+
+    let (body, key) = node.last.lock().unwrap().clone().unwrap();
+    assert_eq!(key.as_deref(), Some("retry-1"));
+    assert_eq!(body["proof"], "AQID");
+    assert_eq!(body["signature"]["public_key"], hex::encode([4u8; 32]));
+    assert_eq!(body["signature"]["signature"], hex::encode([5u8; 64]));
+}
+
+#[tokio::test]
+async fn nonce_conflict_rebuilds_against_a_fresh_state() {
+    let node = Node {
+        contested: 2,
+        ..Node::default()
+    };
+    let client = Client::with_retry(serve(node.clone()).await, quick_retry(5)).unwrap();
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let submitted = client
+        .submit_latest(|state| {
+            seen.lock().unwrap().push(state.nonce);
+            async move { Ok(submission(state.next_inputs(10))) }
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), vec![0, 1, 2]);
+    assert_eq!(submitted.receipt.unwrap().nonce, 3);
+}
+
+#[tokio::test]
+async fn conflict_is_returned_once_attempts_run_out() {
+    let node = Node {
+        contested: 10,
+        ..Node::default()
+    };
+    let client = Client::with_retry(serve(node.clone()).await, quick_retry(2)).unwrap();
+
+    let err = client
+        .submit_latest(|state| async move { Ok(submission(state.next_inputs(10))) })
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, ClientError::Rejected(ProtocolError::InvalidNonce)));
+    assert_eq!(node.nonce.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn proved_submission_is_ready_to_submit() {
+    let node = Node {
+        contested: 1,
+        ..Node::default()
+    };
+    let client = Client::with_retry(serve(node.clone()).await, quick_retry(3))
+        .unwrap()
+        .with_poll_interval(Duration::from_millis(1));
+
+    let proved = client.prove(300, 600).await.unwrap();
+    assert_eq!(proved.proof, vec![1, 2, 3]);
+    assert_eq!(proved.public_inputs.nonce, 1);
+    assert_eq!(proved.public_inputs.threshold, 600);
+    assert_eq!(proved.new_state_commitment.0, [42; 32]);
+
+    // proving again from the retry loop follows the state past the conflict
+    let submitted = client
+        .submit_latest(|_| client.prove(300, 600))
+        .await
+        .unwrap();
+    assert_eq!(submitted.receipt.unwrap().nonce, 2);
+
+    let err = client.wait_for_job("broken").await.unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::JobFailed { status: JobStatus::Failed, ref message, .. } if message == "policy violation"
+    ));
+}
+
+#[tokio::test]
+async fn state_and_demo_responses_are_typed() {
+    let client = Client::new(serve(Node::default()).await).unwrap();
+
+    let state = client.state().await.unwrap();
+    assert_eq!(state.provers, vec![[9; 32]]);
+    assert_eq!(state.channel, [6; 32]);
+    assert_eq!(state.next_inputs(5).nonce, 1);
+
+    assert!(client.demo_verify(&[1, 2, 3], 10).await.unwrap());
+    assert!(!client.demo_verify(&[9], 10).await.unwrap());
+
+    let err = client.demo_prove(10, 20).await.unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::RateLimited { retry_after: Some(d) } if d == Duration::from_secs(3)
+    ));
+}
+
+#[test]
+fn error_codes_decode_to_protocol_errors() {
+    assert!(matches!(
+        decode(409, None, Some("ERR_STATE_MISMATCH"), "state mismatch".into()),
+        ClientError::Rejected(ProtocolError::StateMismatch)
+    ));
+    assert!(matches!(
+        decode(400, None, Some("ERR_INVALID_FORMAT"), "missing field `proof`".into()),
+        ClientError::Rejected(ProtocolError::InvalidFormat)
+    ));
+    // the message alone is not trusted to identify a rejection
+    assert!(matches!(
+        decode(409, None, None, "state mismatch".into()),
+        ClientError::Status { status: 409, .. }
+    ));
+    assert!(matches!(
+        decode(422, None, Some("ERR_SOMETHING_NEW"), "from a newer node".into()),
+        ClientError::Status { status: 422, .. }
+    ));
+}
+
+#[test]
+fn unknown_workers_are_told_by_their_code_not_the_message() {
+    assert!(decode(404, None, Some("ERR_UNKNOWN_WORKER"), "worker not found".into()).is_unknown_worker());
+    assert!(!decode(404, None, None, "unknown worker".into()).is_unknown_worker());
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use zkcg_common::types::{Commitment, Hash};
+use zkcg_common::wire::{self, PublicInputsDto, SubmissionSignatureDto, SubmitProofRequest};
+
+/// Public inputs of a state transition (SPEC §3.2).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublicInputs {
+    pub threshold: u64,
+    pub old_state_root: Hash,
+    pub nonce: u64,
+}
+
+/// Ed25519 signature by an allowlisted prover over the canonical
+/// submission bytes for the node's channel (SPEC §5.3).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionSignature {
+    pub public_key: Hash,
+    pub signature: [u8; 64],
+}
+
+/// One proof submission, as sent to `/v1/submit-proof`.
+#[derive(Debug, Clone)]
+pub struct Submission {
+    /// Raw proof bytes; base64-encoded on the wire
+    pub proof: Vec<u8>,
+    pub public_inputs: PublicInputs,
+    pub new_state_commitment: Commitment,
+    pub signature: Option<SubmissionSignature>,
+}
+
+impl From<&Submission> for SubmitProofRequest {
+    fn from(s: &Submission) -> Self {
+        Self {
+            proof: wire::encode_proof(&s.proof),
+            public_inputs: PublicInputsDto {
+                threshold: s.public_inputs.threshold,
+                old_state_root: s.public_inputs.old_state_root,
+                nonce: s.public_inputs.nonce,
+            },
+            new_state_commitment: s.new_state_commitment.0,
+            signature: s
+                .signature
+                .as_ref()
+                .map(|sig| SubmissionSignatureDto::new(&sig.public_key, &sig.signature)),
+        }
+    }
+}
+
+impl TryFrom<SubmitProofRequest> for Submission {
+    type Error = String;
+
+    fn try_from(body: SubmitProofRequest) -> Result<Self, String> {
+        let signature = body
+            .signature
+            .map(|sig| {
+                let (public_key, signature) = sig
+                    .decode()
+                    .map_err(|_| "signature is not 32 + 64 hex-encoded bytes".to_string())?;
+                Ok::<_, String>(SubmissionSignature { public_key, signature })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            proof: wire::decode_proof(&body.proof, usize::MAX)
+                .map_err(|_| "proof is not base64".to_string())?,
+            public_inputs: PublicInputs {
+                threshold: body.public_inputs.threshold,
+                old_state_root: body.public_inputs.old_state_root,
+                nonce: body.public_inputs.nonce,
+            },
+            new_state_commitment: Commitment(body.new_state_commitment),
+            signature,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmitStatus {
+    Accepted,
+    /// Waiting in the node's mempool for its predecessor
+    Queued,
+}
+
+/// Node's record of an accepted submission.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Receipt {
+    /// `sha256` of the canonical submission bytes
+    #[serde(deserialize_with = "hex_hash")]
+    pub submission_id: Hash,
+    /// Nonce of the state this submission produced
+    pub nonce: u64,
+    pub epoch: u64,
+    pub state_root: Hash,
+    /// Unix time (ms)
+    pub accepted_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Submitted {
+    pub status: SubmitStatus,
+    /// Present once accepted
+    #[serde(default)]
+    pub receipt: Option<Receipt>,
+    /// The node had already seen this submission and answered from its receipts
+    pub replayed: bool,
+}
+
+/// Latest accepted state of a node, from `GET /v1/state`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NodeState {
+    /// Channel id a prover signs for
+    #[serde(deserialize_with = "hex_hash")]
+    pub channel: Hash,
+    pub state_root: Hash,
+    pub nonce: u64,
+    pub epoch: u64,
+    /// Allowlisted prover keys; empty allows any prover
+    #[serde(deserialize_with = "hex_hashes")]
+    pub provers: Vec<Hash>,
+}
+
+impl NodeState {
+    /// Public inputs for a transition on top of this state.
+    pub fn next_inputs(&self, threshold: u64) -> PublicInputs {
+        PublicInputs {
+            threshold,
+            old_state_root: self.state_root,
+            nonce: self.nonce + 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Succeeded | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// A proving job on the node, from `/v1/prove` and `/v1/jobs/{id}`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Job {
+    pub id: String,
+    pub status: JobStatus,
+    pub threshold: u64,
+    /// Unix time (ms)
+    pub created_at: u64,
+    /// Unix time (ms)
+    #[serde(default)]
+    pub started_at: Option<u64>,
+    /// Unix time (ms)
+    #[serde(default)]
+    pub finished_at: Option<u64>,
+    /// The proved submission, once succeeded
+    #[serde(default, deserialize_with = "job_result")]
+    pub result: Option<Submission>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A worker's registration with a node, from `POST /v1/workers`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WorkerRegistration {
+    pub worker_id: String,
+    /// Backend every job from the node must be proved for
+    pub backend: String,
+    /// Heartbeat at least this often while proving
+    pub heartbeat_interval_secs: u64,
+}
+
+/// A proving job handed to a worker, witness included.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WorkerJob {
+    pub job_id: String,
+    pub backend: String,
+    pub secret_value: u64,
+    pub threshold: u64,
+    pub old_state_root: Hash,
+    pub nonce: u64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct RegisterWorkerBody<'a> {
+    pub name: &'a str,
+    pub backends: &'a [&'a str],
+}
+
+#[derive(Serialize)]
+pub(crate) struct CompleteJobBody<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proof: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+pub(crate) struct ProveBody {
+    pub secret_value: u64,
+    pub threshold: u64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct DemoProveBody {
+    pub score: u64,
+    pub threshold: u64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DemoProveResponseBody {
+    pub proof: String,
+}
+
+#[derive(Serialize)]
+pub(crate) struct DemoVerifyBody {
+    pub proof: String,
+    pub threshold: u64,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct DemoVerifyResponseBody {
+    pub verified: bool,
+}
+
+fn parse_hash(s: &str) -> Option<Hash> {
+    wire::decode_hex_array(s).ok()
+}
+
+fn hex_hash<'de, D: Deserializer<'de>>(d: D) -> Result<Hash, D::Error> {
+    let s = String::deserialize(d)?;
+    parse_hash(&s).ok_or_else(|| serde::de::Error::custom("expected 32 hex-encoded bytes"))
+}
+
+fn job_result<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Submission>, D::Error> {
+    Option::<SubmitProofRequest>::deserialize(d)?
+        .map(Submission::try_from)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+fn hex_hashes<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Hash>, D::Error> {
+    Vec::<String>::deserialize(d)?
+        .iter()
+        .map(|s| parse_hash(s).ok_or_else(|| serde::de::Error::custom("expected 32 hex-encoded bytes")))
+        .collect()
+}
@@ -1,24 +1,56 @@
-#[cfg(feature = "std")]
-use thiserror::Error;
-
-#[derive(Debug)]
-#[cfg_attr(feature = "std", derive(Error))]
-pub enum ProtocolError {
-    #[cfg_attr(feature = "std", error("invalid message format"))]
-    InvalidFormat,
-
-    #[cfg_attr(feature = "std", error("state mismatch"))]
-    StateMismatch,
-
-    #[cfg_attr(feature = "std", error("invalid nonce"))]
-    InvalidNonce,
-
-    #[cfg_attr(feature = "std", error("proof verification failed"))]
-    InvalidProof,
-
-    #[cfg_attr(feature = "std", error("policy violation"))]
-    PolicyViolation,
-
-    #[cfg_attr(feature = "std", error("commitment mismatch"))]
-    CommitmentMismatch,
-}
+#[cfg(feature = "std")]
+use thiserror::Error;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Error))]
+pub enum ProtocolError {
+    #[cfg_attr(feature = "std", error("invalid message format"))]
+    InvalidFormat,
+
+    #[cfg_attr(feature = "std", error("state mismatch"))]
+    StateMismatch,
+
+    #[cfg_attr(feature = "std", error("invalid nonce"))]
+    InvalidNonce,
+
+    #[cfg_attr(feature = "std", error("proof verification failed"))]
+    InvalidProof,
+
+    #[cfg_attr(feature = "std", error("policy violation"))]
+    PolicyViolation,
+
+    #[cfg_attr(feature = "std", error("commitment mismatch"))]
+    CommitmentMismatch,
+
+    #[cfg_attr(feature = "std", error("prover not authorized"))]
+    UnauthorizedProver,
+}
+
+impl ProtocolError {
+    /// Stable error code, as listed in SPEC §9.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProtocolError::InvalidFormat => "ERR_INVALID_FORMAT",
+            ProtocolError::StateMismatch => "ERR_STATE_MISMATCH",
+            ProtocolError::InvalidNonce => "ERR_NONCE_INVALID",
+            ProtocolError::InvalidProof => "ERR_PROOF_INVALID",
+            ProtocolError::PolicyViolation => "ERR_POLICY_VIOLATION",
+            ProtocolError::CommitmentMismatch => "ERR_COMMITMENT_MISMATCH",
+            ProtocolError::UnauthorizedProver => "ERR_UNAUTHORIZED_PROVER",
+        }
+    }
+
+    /// The error with SPEC §9 code `code`, e.g. from a node's response.
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "ERR_INVALID_FORMAT" => Some(ProtocolError::InvalidFormat),
+            "ERR_STATE_MISMATCH" => Some(ProtocolError::StateMismatch),
+            "ERR_NONCE_INVALID" => Some(ProtocolError::InvalidNonce),
+            "ERR_PROOF_INVALID" => Some(ProtocolError::InvalidProof),
+            "ERR_POLICY_VIOLATION" => Some(ProtocolError::PolicyViolation),
+            "ERR_COMMITMENT_MISMATCH" => Some(ProtocolError::CommitmentMismatch),
+            "ERR_UNAUTHORIZED_PROVER" => Some(ProtocolError::UnauthorizedProver),
+            _ => None,
+        }
+    }
+}
@@ -1,5 +1,11 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 pub mod state;
 pub mod errors;
 pub mod types;
+
+#[cfg(feature = "wire")]
+pub mod wire;
@@ -1,19 +1,41 @@
-use crate::types::Hash;
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProtocolState {
-    pub state_root: Hash,
-    pub nonce: u64,
-    pub epoch: u64,
-}
-
-impl ProtocolState {
-    pub fn genesis() -> Self {
-        Self {
-            state_root: [0u8; 32],
-            nonce: 0,
-            epoch: 0,
-        }
-    }
-}
+use crate::types::Hash;
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtocolState {
+    /// Channel this state advances. Prover signatures cover it, so the
+    /// allowlist below only admits submissions signed for this channel.
+    #[serde(default)]
+    pub channel: Hash,
+    pub state_root: Hash,
+    pub nonce: u64,
+    pub epoch: u64,
+    /// Ed25519 public keys allowed to submit transitions on `channel`.
+    /// Empty means any prover may submit; that is set at genesis, a
+    /// non-empty allowlist cannot be emptied.
+    #[serde(default)]
+    pub provers: Vec<Hash>,
+}
+
+impl ProtocolState {
+    pub fn genesis() -> Self {
+        Self {
+            channel: [0u8; 32],
+            state_root: [0u8; 32],
+            nonce: 0,
+            epoch: 0,
+            provers: Vec::new(),
+        }
+    }
+
+    /// Whether `public_key` may advance this state.
+    pub fn allows_prover(&self, public_key: Option<&Hash>) -> bool {
+        if self.provers.is_empty() {
+            return true;
+        }
+        public_key.is_some_and(|k| self.provers.contains(k))
+    }
+}
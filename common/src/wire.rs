@@ -0,0 +1,123 @@
+//! JSON wire form of a proof submission (SPEC §5.1, §5.3), shared by the
+//! node, its clients and the offline tools so the shape is defined once.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{errors::ProtocolError, types::Hash};
+
+/// Response header (REST) and metadata key (gRPC) carrying the SPEC §9 code
+/// of a protocol rejection, or [`UNKNOWN_WORKER_CODE`].
+pub const ERROR_CODE_HEADER: &str = "x-zkcg-error-code";
+
+/// [`ERROR_CODE_HEADER`] value of a request from a worker the node does not
+/// know, e.g. after it timed out or the node restarted; the worker has to
+/// register again. Not a protocol rejection, so not in SPEC §9.
+pub const UNKNOWN_WORKER_CODE: &str = "ERR_UNKNOWN_WORKER";
+
+/// Body of `/v1/submit-proof`, and one line of a replay or transition log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmitProofRequest {
+    /// Base64-encoded proof
+    pub proof: String,
+    pub public_inputs: PublicInputsDto,
+    #[cfg_attr(feature = "openapi", schema(schema_with = hash_schema))]
+    pub new_state_commitment: Hash,
+    /// Required when the node has a prover allowlist.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<SubmissionSignatureDto>,
+}
+
+/// Ed25519 signature over the canonical submission bytes (see SPEC §5.3).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SubmissionSignatureDto {
+    /// Hex, 32 bytes
+    pub public_key: String,
+    /// Hex, 64 bytes
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct PublicInputsDto {
+    pub threshold: u64,
+    #[cfg_attr(feature = "openapi", schema(schema_with = hash_schema))]
+    pub old_state_root: Hash,
+    pub nonce: u64,
+}
+
+/// One accepted transition, as a line of a node's transition log.
+///
+/// The submission is flattened in the shape of [`SubmitProofRequest`], so a
+/// log can also be fed to anything that replays submissions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggedTransition {
+    /// Hex id of the submission: `sha256` of its canonical bytes
+    pub submission_id: String,
+    /// Backend that accepted the proof
+    pub backend: String,
+    /// Channel the submission was accepted on; signatures cover it
+    #[serde(default)]
+    pub channel: Hash,
+    pub epoch: u64,
+    #[serde(flatten)]
+    pub submission: SubmitProofRequest,
+    /// Unix time (ms)
+    pub accepted_at: u64,
+}
+
+impl SubmissionSignatureDto {
+    pub fn new(public_key: &Hash, signature: &[u8; 64]) -> Self {
+        Self {
+            public_key: hex::encode(public_key),
+            signature: hex::encode(signature),
+        }
+    }
+
+    /// Decoded `(public_key, signature)`.
+    pub fn decode(&self) -> Result<(Hash, [u8; 64]), ProtocolError> {
+        Ok((decode_hex_array(&self.public_key)?, decode_hex_array(&self.signature)?))
+    }
+}
+
+pub fn encode_proof(proof: &[u8]) -> String {
+    STANDARD.encode(proof)
+}
+
+/// Decode a base64 proof of at most `max_proof` bytes.
+///
+/// Oversized input is refused from its length alone, before anything is
+/// allocated for the decoded bytes.
+pub fn decode_proof(b64: &str, max_proof: usize) -> Result<Vec<u8>, ProtocolError> {
+    let max_encoded = base64::encoded_len(max_proof, true).unwrap_or(usize::MAX);
+    if b64.len() > max_encoded {
+        return Err(ProtocolError::InvalidFormat);
+    }
+    STANDARD.decode(b64).map_err(|_| ProtocolError::InvalidFormat)
+}
+
+/// Exactly `N` hex-encoded bytes.
+pub fn decode_hex_array<const N: usize>(s: &str) -> Result<[u8; N], ProtocolError> {
+    hex::decode(s)
+        .ok()
+        .and_then(|v| v.try_into().ok())
+        .ok_or(ProtocolError::InvalidFormat)
+}
+
+/// Schema for a 32-byte hash, which serde writes as an array of 32 integers.
+#[cfg(feature = "openapi")]
+pub fn hash_schema() -> utoipa::openapi::ArrayBuilder {
+    use utoipa::openapi::{ArrayBuilder, ObjectBuilder, SchemaType};
+
+    ArrayBuilder::new()
+        .items(
+            ObjectBuilder::new()
+                .schema_type(SchemaType::Integer)
+                .minimum(Some(0.0))
+                .maximum(Some(255.0)),
+        )
+        .min_items(Some(32))
+        .max_items(Some(32))
+}
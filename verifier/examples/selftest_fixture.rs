@@ -0,0 +1,64 @@
+//! Write a self-test fixture: a known-good proof for the node's
+//! `backend.self_test`, made offline against the canonical artifacts.
+//!
+//! ```text
+//! cargo run --example selftest_fixture --features zk-halo2 -- halo2 selftest-halo2.json
+//! cargo run --example selftest_fixture --features zk-vm -- zkvm selftest-zkvm.json
+//! ```
+
+// without either backend feature there is nothing to prove with
+#![cfg_attr(not(any(feature = "zk-halo2", feature = "zk-vm")), allow(dead_code, unused_variables))]
+
+use std::{env, fs, process::ExitCode};
+
+use zkcg_verifier::{engine::PublicInputs, selftest::Fixture};
+
+/// `score <= threshold`, so the proof is one the backend must accept.
+const SCORE: u64 = 40;
+const THRESHOLD: u64 = 50;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [backend, out] = args.as_slice() else {
+        eprintln!("usage: selftest_fixture <halo2|zkvm> <out.json>");
+        return ExitCode::FAILURE;
+    };
+
+    let public_inputs = PublicInputs {
+        threshold: THRESHOLD,
+        old_state_root: [0u8; 32],
+        nonce: 1,
+    };
+    let proof_bytes = match prove(backend, &public_inputs) {
+        Ok(proof) => proof,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fixture = Fixture {
+        proof_bytes,
+        public_inputs,
+    };
+    let json = serde_json::to_string_pretty(&fixture.to_wire()).expect("fixture serializes");
+    if let Err(e) = fs::write(out, json) {
+        eprintln!("cannot write {}: {}", out, e);
+        return ExitCode::FAILURE;
+    }
+    println!("wrote {} fixture to {}", backend, out);
+    ExitCode::SUCCESS
+}
+
+fn prove(backend: &str, inputs: &PublicInputs) -> Result<Vec<u8>, String> {
+    match backend {
+        #[cfg(feature = "zk-halo2")]
+        "halo2" => zkcg_verifier::Halo2Backend::bundled()
+            .prove_score(SCORE, inputs.threshold)
+            .ok_or_else(|| "halo2 proving failed".to_string()),
+        #[cfg(feature = "zk-vm")]
+        "zkvm" => zkcg_zkvm_host::prove(SCORE, inputs.threshold, inputs.old_state_root, inputs.nonce)
+            .map_err(|e| format!("zkvm proving failed: {:?}", e)),
+        other => Err(format!("backend {:?} is unknown or not compiled in", other)),
+    }
+}
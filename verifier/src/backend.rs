@@ -1,10 +1,178 @@
-use zkcg_common::errors::ProtocolError;
-use crate::engine::PublicInputs;
-
-pub trait ProofBackend: Send + Sync {
-    fn verify(
-        &self,
-        proof_bytes: &[u8],
-        public_inputs: &PublicInputs,
-    ) -> Result<(), ProtocolError>;
-}
+use std::path::PathBuf;
+
+use zkcg_common::errors::ProtocolError;
+use crate::{
+    engine::PublicInputs,
+    selftest::{Fixture, KnownAnswer},
+};
+
+/// Proof size cap for backends that do not set their own.
+pub const DEFAULT_MAX_PROOF_SIZE: usize = 1 << 20;
+
+pub trait ProofBackend: Send + Sync {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError>;
+
+    /// Short identifier used in logs and metrics.
+    fn name(&self) -> &'static str {
+        "unknown"
+    }
+
+    /// Proofs with a known verdict, checked by [`crate::selftest::run`] at startup.
+    ///
+    /// Only proofs that must be rejected can be built in; a good one has to
+    /// come from a [`Fixture`].
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        Vec::new()
+    }
+
+    /// `fixture`, which this backend must accept, and variations of it that
+    /// it must reject.
+    fn fixture_answers(&self, fixture: &Fixture) -> Vec<KnownAnswer> {
+        vec![KnownAnswer::valid(
+            "fixture",
+            fixture.proof_bytes.clone(),
+            fixture.public_inputs.clone(),
+        )]
+    }
+
+    /// Largest proof, in bytes, this backend will look at.
+    ///
+    /// Larger proofs are rejected with `InvalidFormat` before verification,
+    /// and transports use it to bound what they read off the wire.
+    fn max_proof_size(&self) -> usize {
+        DEFAULT_MAX_PROOF_SIZE
+    }
+}
+
+/// A backend with its proof size cap replaced, e.g. from node config.
+pub struct WithMaxProofSize {
+    inner: Box<dyn ProofBackend>,
+    max: usize,
+}
+
+impl WithMaxProofSize {
+    pub fn new(inner: Box<dyn ProofBackend>, max: usize) -> Self {
+        Self { inner, max }
+    }
+}
+
+impl ProofBackend for WithMaxProofSize {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        self.inner.verify(proof_bytes, public_inputs)
+    }
+
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        self.inner.known_answers()
+    }
+
+    fn fixture_answers(&self, fixture: &Fixture) -> Vec<KnownAnswer> {
+        self.inner.fixture_answers(fixture)
+    }
+
+    fn max_proof_size(&self) -> usize {
+        self.max
+    }
+}
+
+/// Reject proofs that are empty or larger than `backend` accepts.
+pub fn check_proof_size(backend: &dyn ProofBackend, proof_bytes: &[u8]) -> Result<(), ProtocolError> {
+    if proof_bytes.is_empty() || proof_bytes.len() > backend.max_proof_size() {
+        tracing::debug!(
+            backend = backend.name(),
+            proof_len = proof_bytes.len(),
+            max = backend.max_proof_size(),
+            "proof size out of bounds"
+        );
+        return Err(ProtocolError::InvalidFormat);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackendKind {
+    #[default]
+    Zkvm,
+    Halo2,
+    /// Accepts every proof. Development only.
+    Stub,
+}
+
+/// A backend selection, as made by node config or command-line flags.
+#[derive(Debug, Clone, Default)]
+pub struct BackendSpec {
+    pub kind: BackendKind,
+    /// zkVM guest image id; the one built into this binary when unset
+    pub zkvm_image_id: Option<[u8; 32]>,
+    /// Halo2 params file; the bundled artifacts are used when unset
+    pub halo2_params: Option<PathBuf>,
+    /// Serialized Halo2 verifying key; derived from the params when unset
+    pub halo2_vk: Option<PathBuf>,
+    /// Overrides the backend's own proof size cap, in bytes
+    pub max_proof_size: Option<usize>,
+}
+
+impl BackendSpec {
+    pub fn new(kind: BackendKind) -> Self {
+        Self {
+            kind,
+            ..Self::default()
+        }
+    }
+
+    /// Instantiate the selected backend. Fails on options that do not apply
+    /// to `kind`, keys that cannot be loaded, and backends whose feature is
+    /// not compiled in.
+    pub fn build(&self) -> Result<Box<dyn ProofBackend>, String> {
+        if self.zkvm_image_id.is_some() && self.kind != BackendKind::Zkvm {
+            return Err("an image id only applies to the zkvm backend".into());
+        }
+        if self.halo2_vk.is_some() && self.halo2_params.is_none() {
+            return Err("a halo2 verifying key needs its params file".into());
+        }
+        if self.halo2_params.is_some() && self.kind != BackendKind::Halo2 {
+            return Err("halo2 params and keys only apply to the halo2 backend".into());
+        }
+
+        let backend = self.build_base()?;
+        Ok(match self.max_proof_size {
+            Some(max) => Box::new(WithMaxProofSize::new(backend, max)),
+            None => backend,
+        })
+    }
+
+    fn build_base(&self) -> Result<Box<dyn ProofBackend>, String> {
+        match self.kind {
+            #[cfg(feature = "zk-vm")]
+            BackendKind::Zkvm => Ok(match self.zkvm_image_id {
+                Some(id) => Box::new(crate::backend_zkvm::ZkVmImageBackend::new(id.into())),
+                None => Box::new(crate::backend_zkvm::ZkVmBackend),
+            }),
+            #[cfg(feature = "zk-halo2")]
+            BackendKind::Halo2 => {
+                let loaded = match (&self.halo2_params, &self.halo2_vk) {
+                    (Some(params), Some(vk)) => crate::Halo2Backend::from_files(params, vk),
+                    (Some(params), None) => crate::Halo2Backend::from_params_file(params),
+                    (None, _) => Ok(crate::Halo2Backend::bundled()),
+                };
+                loaded
+                    .map(|b| Box::new(b) as Box<dyn ProofBackend>)
+                    .map_err(|e| format!("cannot load halo2 keys: {}", e))
+            }
+            BackendKind::Stub => Ok(Box::new(crate::backend_stub::StubBackend)),
+            #[allow(unreachable_patterns)]
+            kind => Err(format!("backend {:?} is not compiled into this binary", kind)),
+        }
+    }
+}
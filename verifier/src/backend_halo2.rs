@@ -1,63 +1,179 @@
-#![cfg(feature = "zk-halo2")]
-
-use zkcg_common::errors::ProtocolError;
-use crate::{
-    backend::ProofBackend,
-    engine::PublicInputs,
-};
-
-use halo2_proofs::{
-    plonk::{verify_proof, VerifyingKey, SingleVerifier},
-    poly::commitment::Params,
-    transcript::{Blake2bRead, Challenge255},
-};
-
-use halo2curves::bn256::{Fr, G1Affine};
-
-/// Real Halo2 verifier backend (runtime keys, KZG implicit)
-pub struct Halo2Backend {
-    pub vk: VerifyingKey<G1Affine>,
-    pub params: Params<G1Affine>,
-}
-
-impl ProofBackend for Halo2Backend {
-    fn verify(
-        &self,
-        proof_bytes: &[u8],
-        public_inputs: &PublicInputs,
-    ) -> Result<(), ProtocolError> {
-        // --- public inputs (instance columns)
-        let threshold = Fr::from(public_inputs.threshold as u64);
-
-        let instance_values = vec![vec![threshold]];
-        let instance_slices: Vec<&[Fr]> =
-            instance_values.iter().map(|v| v.as_slice()).collect();
-        let all_instances: Vec<&[&[Fr]]> =
-            vec![instance_slices.as_slice()];
-
-        // --- transcript
-        let mut transcript =
-            Blake2bRead::<_, G1Affine, Challenge255<G1Affine>>::init(proof_bytes);
-
-        // --- verification strategy
-        let strategy = SingleVerifier::new(&self.params);
-        println!("Starting proof verification...");
-        println!("Public inputs: {:?}", all_instances);
-        println!("Proof bytes length: {}", proof_bytes.len());
-        // println!("Params: {:?}", self.params);
-        // println!("Transcript state: {:?}", transcript);
-        println!("Using SingleVerifier strategy.");
-        // --- verify
-        verify_proof(
-            &self.params,
-            &self.vk,
-            strategy,
-            &all_instances,
-            &mut transcript,
-        )
-        .map_err(|_| ProtocolError::InvalidProof)?;
-
-        Ok(())
-    }
-}
-
+#![cfg(feature = "zk-halo2")]
+
+use zkcg_common::errors::ProtocolError;
+use crate::{
+    backend::ProofBackend,
+    engine::PublicInputs,
+    selftest::{Fixture, KnownAnswer},
+};
+
+use halo2_proofs::{
+    circuit::Value,
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255, TranscriptWrite},
+};
+use rand::rngs::OsRng;
+
+use halo2curves::bn256::{Fr, G1Affine};
+use circuits::score_circuit::ScoreCircuit;
+use std::{fs::File, io, io::BufReader, path::Path};
+
+/// Real Halo2 verifier backend (runtime keys, KZG implicit)
+pub struct Halo2Backend {
+    pub vk: VerifyingKey<G1Affine>,
+    pub params: Params<G1Affine>,
+}
+
+impl Halo2Backend {
+    /// Backend using the artifacts bundled with the `circuits` crate.
+    pub fn bundled() -> Self {
+        let artifacts = circuits::halo2_artifacts::verifier_artifacts();
+        Self {
+            vk: artifacts.vk,
+            params: artifacts.params,
+        }
+    }
+
+    /// Load params from `path` and derive the score circuit's verifying key.
+    pub fn from_params_file(path: &Path) -> io::Result<Self> {
+        let params = read_params(path)?;
+
+        // keygen only needs the circuit shape, not a witness
+        let circuit = ScoreCircuit::<Fr> {
+            score: Value::unknown(),
+            threshold: Value::unknown(),
+        };
+        let vk = keygen_vk(&params, &circuit)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+
+        Ok(Self { vk, params })
+    }
+
+    /// Load params and a serialized verifying key for the score circuit,
+    /// e.g. from a circuit build other than the bundled one.
+    pub fn from_files(params_path: &Path, vk_path: &Path) -> io::Result<Self> {
+        let params = read_params(params_path)?;
+        let mut reader = BufReader::new(File::open(vk_path)?);
+        let vk = VerifyingKey::<G1Affine>::read::<_, ScoreCircuit<Fr>>(&mut reader, &params)?;
+
+        Ok(Self { vk, params })
+    }
+}
+
+fn read_params(path: &Path) -> io::Result<Params<G1Affine>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Params::<G1Affine>::read(&mut reader)
+}
+
+impl ProofBackend for Halo2Backend {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        // --- public inputs (instance columns)
+        let threshold = Fr::from(public_inputs.threshold as u64);
+
+        let instance_values = vec![vec![threshold]];
+        let instance_slices: Vec<&[Fr]> =
+            instance_values.iter().map(|v| v.as_slice()).collect();
+        let all_instances: Vec<&[&[Fr]]> =
+            vec![instance_slices.as_slice()];
+
+        // --- transcript
+        let mut transcript =
+            Blake2bRead::<_, G1Affine, Challenge255<G1Affine>>::init(proof_bytes);
+
+        // --- verification strategy
+        let strategy = SingleVerifier::new(&self.params);
+        tracing::debug!(
+            threshold = public_inputs.threshold,
+            proof_len = proof_bytes.len(),
+            "verifying halo2 proof"
+        );
+        // --- verify
+        verify_proof(
+            &self.params,
+            &self.vk,
+            strategy,
+            &all_instances,
+            &mut transcript,
+        )
+        .map_err(|_| {
+            tracing::debug!("halo2 proof rejected");
+            ProtocolError::InvalidProof
+        })?;
+
+        Ok(())
+    }
+
+    fn name(&self) -> &'static str {
+        "halo2"
+    }
+
+    /// The score circuit's proofs are a few KiB at any `k` we would use.
+    fn max_proof_size(&self) -> usize {
+        64 << 10
+    }
+
+    /// The fixture, the fixture with its last byte flipped, and the fixture
+    /// against a threshold it was not proved for.
+    fn fixture_answers(&self, fixture: &Fixture) -> Vec<KnownAnswer> {
+        let good = fixture.proof_bytes.clone();
+        let inputs = fixture.public_inputs.clone();
+
+        let mut tampered = good.clone();
+        if let Some(last) = tampered.last_mut() {
+            *last ^= 0x01;
+        }
+
+        let wrong_threshold = PublicInputs {
+            threshold: inputs.threshold + 1,
+            ..inputs.clone()
+        };
+
+        vec![
+            KnownAnswer::valid("score proof", good.clone(), inputs.clone()),
+            KnownAnswer::invalid("tampered proof", tampered, inputs),
+            KnownAnswer::invalid("mismatched threshold", good, wrong_threshold),
+        ]
+    }
+}
+
+impl Halo2Backend {
+    /// Prove `score` against `threshold` with this backend's params and VK.
+    ///
+    /// Used by provers and to make self-test fixtures offline; the node's
+    /// self-test never checks a proof made this way.
+    ///
+    /// `None` when no proving key can be derived or proving fails.
+    pub fn prove_score(&self, score: u64, threshold: u64) -> Option<Vec<u8>> {
+        let circuit = ScoreCircuit::<Fr> {
+            score: Value::known(Fr::from(score)),
+            threshold: Value::known(Fr::from(threshold)),
+        };
+        let pk = keygen_pk(&self.params, self.vk.clone(), &circuit).ok()?;
+
+        let public_inputs = vec![vec![Fr::from(threshold)]];
+        let instance_slices: Vec<&[Fr]> =
+            public_inputs.iter().map(|v| v.as_slice()).collect();
+        let all_instances: Vec<&[&[Fr]]> =
+            vec![instance_slices.as_slice()];
+
+        let mut transcript =
+            Blake2bWrite::<_, G1Affine, Challenge255<G1Affine>>::init(Vec::new());
+        create_proof(
+            &self.params,
+            &pk,
+            &[circuit],
+            &all_instances,
+            OsRng,
+            &mut transcript,
+        )
+        .ok()?;
+
+        Some(transcript.finalize())
+    }
+}
+
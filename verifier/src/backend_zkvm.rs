@@ -1,57 +1,189 @@
-#![cfg(feature = "zk-vm")]
-
-use zkcg_common::errors::ProtocolError;
-use crate::{backend::ProofBackend, engine::PublicInputs};
-
-use risc0_zkp::core::digest::Digest;
-use serde::Deserialize;
-
-use serde::Serialize;
-use zkcg_zkvm_host::method_id;
-use bincode;
-
-#[derive(Deserialize, Debug)]
-struct ZkVmProof {
-    method_id: Digest,
-    journal_digest: Digest,
-}
-
-#[derive(serde::Serialize)]
-struct ZkVmOutput {
-    pub ok: bool,
-}
-
-#[derive(Serialize, Deserialize)]
-pub struct ZkVmJournal {
-    pub threshold: u64,
-    pub old_state_root: [u8; 32],
-    pub nonce: u64,
-    pub ok: bool,
-}
-
-pub struct ZkVmBackend;
-
-impl ProofBackend for ZkVmBackend {
-    fn verify(
-        &self,
-        proof_bytes: &[u8],
-        _public_inputs: &PublicInputs,
-    ) -> Result<(), ProtocolError> {
-        // 1️⃣ Deserialize opaque proof
-        let proof: ZkVmProof =
-            bincode::deserialize(proof_bytes)
-                .map_err(|_| ProtocolError::InvalidProof)?;
-        println!("Verifying zkVM proof: {:?}", proof);
-        // 2️⃣ Verify method identity
-        if proof.method_id != method_id() {
-            return Err(ProtocolError::InvalidProof);
-        }
-        // NOTE:
-        // Journal digest is produced by zkVM runtime.
-        // Verifier must NOT recompute it from raw values.
-        // State binding is enforced inside the guest.
-
-
-        Ok(())
-    }
-}
+#![cfg(feature = "zk-vm")]
+
+use zkcg_common::errors::ProtocolError;
+use crate::{
+    backend::ProofBackend,
+    engine::PublicInputs,
+    selftest::{Fixture, KnownAnswer},
+};
+
+use risc0_zkp::core::digest::Digest;
+use serde::Deserialize;
+
+use serde::Serialize;
+use zkcg_zkvm_host::method_id;
+use bincode;
+
+/// Envelope of a zkVM proof: the guest image it was produced by and the
+/// digest of the journal that guest committed.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ZkVmProof {
+    pub method_id: Digest,
+    pub journal_digest: Digest,
+}
+
+impl ZkVmProof {
+    pub fn decode(proof_bytes: &[u8]) -> Result<Self, ProtocolError> {
+        bincode::deserialize(proof_bytes).map_err(|_| ProtocolError::InvalidProof)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("a zkVM envelope always serializes")
+    }
+}
+
+/// An image id that is not `image_id`: every byte inverted.
+fn other_image(image_id: &Digest) -> Digest {
+    let mut bytes = [0u8; 32];
+    for (b, id) in bytes.iter_mut().zip(image_id.as_bytes()) {
+        *b = !id;
+    }
+    Digest::from(bytes)
+}
+
+/// An envelope for another guest image, which a backend pinned to
+/// `image_id` must reject.
+fn foreign_envelope(image_id: &Digest) -> KnownAnswer {
+    let envelope = ZkVmProof {
+        method_id: other_image(image_id),
+        journal_digest: Digest::from([0u8; 32]),
+    };
+    let inputs = PublicInputs {
+        threshold: 50,
+        old_state_root: [0u8; 32],
+        nonce: 1,
+    };
+    KnownAnswer::invalid("envelope for another image", envelope.encode(), inputs)
+}
+
+/// The fixture, and the fixture re-addressed to another image id.
+fn fixture_answers(fixture: &Fixture) -> Vec<KnownAnswer> {
+    let mut answers = vec![KnownAnswer::valid(
+        "receipt envelope",
+        fixture.proof_bytes.clone(),
+        fixture.public_inputs.clone(),
+    )];
+    // a fixture that does not decode is reported by the valid answer above
+    if let Ok(mut envelope) = ZkVmProof::decode(&fixture.proof_bytes) {
+        envelope.method_id = other_image(&envelope.method_id);
+        answers.push(KnownAnswer::invalid(
+            "envelope under a wrong image id",
+            envelope.encode(),
+            fixture.public_inputs.clone(),
+        ));
+    }
+    answers
+}
+
+#[derive(serde::Serialize)]
+struct ZkVmOutput {
+    pub ok: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ZkVmJournal {
+    pub threshold: u64,
+    pub old_state_root: [u8; 32],
+    pub nonce: u64,
+    pub ok: bool,
+}
+
+impl ZkVmJournal {
+    /// Decode a journal as committed by the guest (risc0 serde words).
+    pub fn decode(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        risc0_zkvm::serde::from_slice::<Self, u8>(bytes).map_err(|_| ProtocolError::InvalidFormat)
+    }
+}
+
+/// Check a proof envelope against the expected guest image.
+fn verify_image(proof_bytes: &[u8], image_id: &Digest) -> Result<(), ProtocolError> {
+    // 1️⃣ Deserialize opaque proof
+    let proof = ZkVmProof::decode(proof_bytes)?;
+    tracing::debug!(
+        method_id = ?proof.method_id,
+        proof_len = proof_bytes.len(),
+        "verifying zkVM proof"
+    );
+    // 2️⃣ Verify method identity
+    if proof.method_id != *image_id {
+        tracing::debug!("zkVM proof has unexpected method id");
+        return Err(ProtocolError::InvalidProof);
+    }
+    // NOTE:
+    // Journal digest is produced by zkVM runtime.
+    // Verifier must NOT recompute it from raw values.
+    // State binding is enforced inside the guest.
+
+
+    Ok(())
+}
+
+pub struct ZkVmBackend;
+
+impl ProofBackend for ZkVmBackend {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        _public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        verify_image(proof_bytes, &method_id())
+    }
+
+    fn name(&self) -> &'static str {
+        "zkvm"
+    }
+
+    /// Succinct receipts are a few hundred KiB; composite receipts from long
+    /// executions can run to a few MiB.
+    fn max_proof_size(&self) -> usize {
+        4 << 20
+    }
+
+    /// Only a rejection: an envelope built here from this binary's own image
+    /// id would pass whatever the id, so the good envelope is the fixture.
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![foreign_envelope(&method_id())]
+    }
+
+    fn fixture_answers(&self, fixture: &Fixture) -> Vec<KnownAnswer> {
+        fixture_answers(fixture)
+    }
+}
+
+/// [`ZkVmBackend`] pinned to an explicit guest image id instead of the one
+/// built into this binary, e.g. to check proofs from another release.
+pub struct ZkVmImageBackend {
+    image_id: Digest,
+}
+
+impl ZkVmImageBackend {
+    pub fn new(image_id: Digest) -> Self {
+        Self { image_id }
+    }
+}
+
+impl ProofBackend for ZkVmImageBackend {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        _public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        verify_image(proof_bytes, &self.image_id)
+    }
+
+    fn name(&self) -> &'static str {
+        "zkvm"
+    }
+
+    fn max_proof_size(&self) -> usize {
+        ZkVmBackend.max_proof_size()
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        vec![foreign_envelope(&self.image_id)]
+    }
+
+    fn fixture_answers(&self, fixture: &Fixture) -> Vec<KnownAnswer> {
+        fixture_answers(fixture)
+    }
+}
@@ -1,64 +1,299 @@
-use zkcg_common::{
-    errors::ProtocolError,
-    state::ProtocolState,
-    types::Commitment,
-};
-use crate::backend::ProofBackend;
-use crate::policy;
-
-pub struct VerifierEngine {
-    state: ProtocolState,
-    backend: Box<dyn ProofBackend>,
-}
-
-impl VerifierEngine {
-    pub fn new(
-        state: ProtocolState,
-        backend: Box<dyn ProofBackend>,
-    ) -> Self {
-        Self { state, backend }
-    }
-    
-    pub fn state(&self) -> &ProtocolState {
-        &self.state
-    }
-
-
-    pub fn process_transition(
-        &mut self,
-        proof_bytes: &[u8],
-        public_inputs: PublicInputs,
-        commitment: Commitment,
-    ) -> Result<(), ProtocolError> {
-        // 1. Check state root
-        if public_inputs.old_state_root != self.state.state_root {
-            return Err(ProtocolError::StateMismatch);
-        }
-
-        // 2. Check nonce
-        if public_inputs.nonce != self.state.nonce + 1 {
-            return Err(ProtocolError::InvalidNonce);
-        }
-
-        // 3. Verify proof
-        self.backend.verify(proof_bytes, &public_inputs)?;
-
-
-
-        // 4. Enforce policy
-        policy::enforce(&public_inputs)?;
-
-        // 5. Update state
-        self.state.state_root = commitment.0;
-        self.state.nonce += 1;
-
-        Ok(())
-    }
-}
-
-#[derive(Debug)]
-pub struct PublicInputs {
-    pub threshold: u64,
-    pub old_state_root: [u8; 32],
-    pub nonce: u64,
-}
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+use zkcg_common::{
+    errors::ProtocolError,
+    state::ProtocolState,
+    types::{Commitment, Hash},
+};
+use crate::backend::{check_proof_size, ProofBackend};
+use crate::mempool::Mempool;
+use crate::policy;
+use crate::signature::{self, ProverSignature};
+
+/// Source of [`VerifierEngine`] ids, so verified inputs can be tied to the
+/// engine whose backend checked them.
+static NEXT_ENGINE_ID: AtomicU64 = AtomicU64::new(0);
+
+pub struct VerifierEngine {
+    id: u64,
+    state: ProtocolState,
+    backend: Arc<dyn ProofBackend>,
+    pub(crate) mempool: Option<Mempool>,
+}
+
+impl VerifierEngine {
+    pub fn new(
+        state: ProtocolState,
+        backend: Box<dyn ProofBackend>,
+    ) -> Self {
+        Self::with_shared_backend(state, Arc::from(backend))
+    }
+
+    /// Engine over a backend that is also used elsewhere, e.g. by a startup self-test.
+    pub fn with_shared_backend(
+        state: ProtocolState,
+        backend: Arc<dyn ProofBackend>,
+    ) -> Self {
+        Self {
+            id: NEXT_ENGINE_ID.fetch_add(1, Ordering::Relaxed),
+            state,
+            backend,
+            mempool: None,
+        }
+    }
+
+    pub fn state(&self) -> &ProtocolState {
+        &self.state
+    }
+
+    /// Shared handle to the backend, e.g. for its name and proof size cap.
+    pub fn backend(&self) -> Arc<dyn ProofBackend> {
+        Arc::clone(&self.backend)
+    }
+
+    /// Handle that verifies proofs with this engine's backend without
+    /// holding a lock on the engine. Only what it verifies can be applied
+    /// to this engine.
+    pub fn verifier(&self) -> Verifier {
+        Verifier {
+            engine: self.id,
+            channel: self.state.channel,
+            backend: self.backend(),
+        }
+    }
+
+    pub fn process_transition(
+        &mut self,
+        proof_bytes: &[u8],
+        public_inputs: PublicInputs,
+        commitment: Commitment,
+    ) -> Result<(), ProtocolError> {
+        // 1. + 2. Check state root and nonce
+        self.check_state(&public_inputs)?;
+
+        // 3. Verify proof
+        let verified = self.verifier().verify(proof_bytes, public_inputs)?;
+
+        // 4. + 5. Enforce policy, update state
+        self.apply(verified, commitment)
+    }
+
+    /// [`VerifierEngine::process_transition`] for a submission signed by a prover.
+    pub fn process_signed_transition(
+        &mut self,
+        proof_bytes: &[u8],
+        public_inputs: PublicInputs,
+        commitment: Commitment,
+        signature: &ProverSignature,
+    ) -> Result<(), ProtocolError> {
+        self.check_state(&public_inputs)?;
+        self.authorize(Some(&signature.public_key))?;
+
+        let verified = self
+            .verifier()
+            .verify_signed(proof_bytes, public_inputs, &commitment, signature)?;
+
+        self.apply(verified, commitment)
+    }
+
+    /// Check `public_key` against the prover allowlist in the current state.
+    pub fn authorize(&self, public_key: Option<&Hash>) -> Result<(), ProtocolError> {
+        if self.state.allows_prover(public_key) {
+            Ok(())
+        } else {
+            Err(ProtocolError::UnauthorizedProver)
+        }
+    }
+
+    /// Add a prover to the allowlist. Returns `false` if already present.
+    pub fn register_prover(&mut self, public_key: Hash) -> bool {
+        if self.state.provers.contains(&public_key) {
+            return false;
+        }
+        self.state.provers.push(public_key);
+        true
+    }
+
+    /// Remove a prover from the allowlist.
+    ///
+    /// The last prover is kept: an empty allowlist admits anyone, and that
+    /// is only ever chosen at genesis, never reached by removing keys.
+    pub fn remove_prover(&mut self, public_key: &Hash) -> ProverRemoval {
+        if !self.state.provers.contains(public_key) {
+            return ProverRemoval::NotListed;
+        }
+        if self.state.provers.len() == 1 {
+            return ProverRemoval::LastProver;
+        }
+        self.state.provers.retain(|k| k != public_key);
+        ProverRemoval::Removed
+    }
+
+    /// Cheap pre-check of `public_inputs` against the current state.
+    ///
+    /// Lets callers reject stale submissions before paying for verification.
+    pub fn check_state(&self, public_inputs: &PublicInputs) -> Result<(), ProtocolError> {
+        if public_inputs.old_state_root != self.state.state_root {
+            return Err(ProtocolError::StateMismatch);
+        }
+
+        if public_inputs.nonce != self.state.nonce + 1 {
+            return Err(ProtocolError::InvalidNonce);
+        }
+
+        Ok(())
+    }
+
+    /// Apply a transition whose proof was checked by this engine's
+    /// [`Verifier`]; inputs verified by another engine are `InvalidProof`.
+    ///
+    /// State root and nonce are re-checked here because the state may have
+    /// moved on while the proof was being verified.
+    pub fn apply(
+        &mut self,
+        verified: VerifiedInputs,
+        commitment: Commitment,
+    ) -> Result<(), ProtocolError> {
+        if verified.engine != self.id {
+            return Err(ProtocolError::InvalidProof);
+        }
+        self.check_state(verified.inputs())?;
+        self.authorize(verified.signer())?;
+
+        if let Some(signed) = &verified.signed {
+            // the prover signed a specific post-state; nothing else may be applied
+            if signed.commitment.0 != commitment.0 {
+                return Err(ProtocolError::CommitmentMismatch);
+            }
+        }
+
+        let public_inputs = verified.into_inner();
+        policy::enforce(&public_inputs)?;
+
+        self.state.state_root = commitment.0;
+        self.state.nonce += 1;
+        tracing::debug!(nonce = self.state.nonce, epoch = self.state.epoch, "transition applied");
+
+        Ok(())
+    }
+}
+
+/// Outcome of [`VerifierEngine::remove_prover`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProverRemoval {
+    Removed,
+    NotListed,
+    /// Refused: removing it would open submission to any prover
+    LastProver,
+}
+
+/// Check a proof against `backend` without producing anything that can be
+/// applied, e.g. for stateless or offline verification.
+pub fn check_proof(
+    backend: &dyn ProofBackend,
+    proof_bytes: &[u8],
+    public_inputs: &PublicInputs,
+) -> Result<(), ProtocolError> {
+    let _span =
+        tracing::debug_span!("verify", backend = backend.name(), nonce = public_inputs.nonce)
+            .entered();
+    check_proof_size(backend, proof_bytes)?;
+    backend.verify(proof_bytes, public_inputs)
+}
+
+/// Verifies proofs for one [`VerifierEngine`], from [`VerifierEngine::verifier`].
+///
+/// Verification does not touch engine state. It is the expensive step, safe
+/// to run on any thread and concurrently with other verifications.
+#[derive(Clone)]
+pub struct Verifier {
+    engine: u64,
+    channel: Hash,
+    backend: Arc<dyn ProofBackend>,
+}
+
+impl Verifier {
+    pub fn backend(&self) -> &dyn ProofBackend {
+        self.backend.as_ref()
+    }
+
+    pub fn verify(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs: PublicInputs,
+    ) -> Result<VerifiedInputs, ProtocolError> {
+        check_proof(self.backend(), proof_bytes, &public_inputs)?;
+        Ok(VerifiedInputs {
+            engine: self.engine,
+            inputs: public_inputs,
+            signed: None,
+        })
+    }
+
+    /// [`Verifier::verify`] for a signed submission.
+    ///
+    /// The signature is checked first since it is far cheaper than the proof.
+    pub fn verify_signed(
+        &self,
+        proof_bytes: &[u8],
+        public_inputs: PublicInputs,
+        commitment: &Commitment,
+        signature: &ProverSignature,
+    ) -> Result<VerifiedInputs, ProtocolError> {
+        check_proof_size(self.backend(), proof_bytes)?;
+        signature::verify_submission(signature, &self.channel, proof_bytes, &public_inputs, commitment)?;
+        check_proof(self.backend(), proof_bytes, &public_inputs)?;
+
+        Ok(VerifiedInputs {
+            engine: self.engine,
+            inputs: public_inputs,
+            signed: Some(SignedBy {
+                public_key: signature.public_key,
+                commitment: commitment.clone(),
+            }),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PublicInputs {
+    pub threshold: u64,
+    pub old_state_root: [u8; 32],
+    pub nonce: u64,
+}
+
+/// Public inputs whose proof has passed backend verification.
+///
+/// Only obtainable from a [`Verifier`], and [`VerifierEngine::apply`] only
+/// takes those of its own engine, so it cannot be reached with a proof its
+/// backend did not accept.
+#[derive(Debug)]
+pub struct VerifiedInputs {
+    engine: u64,
+    inputs: PublicInputs,
+    signed: Option<SignedBy>,
+}
+
+#[derive(Debug)]
+struct SignedBy {
+    public_key: Hash,
+    commitment: Commitment,
+}
+
+impl VerifiedInputs {
+    pub fn inputs(&self) -> &PublicInputs {
+        &self.inputs
+    }
+
+    /// Public key of the prover that signed the submission, if any.
+    pub fn signer(&self) -> Option<&Hash> {
+        self.signed.as_ref().map(|s| &s.public_key)
+    }
+
+    pub fn into_inner(self) -> PublicInputs {
+        self.inputs
+    }
+}
@@ -2,6 +2,10 @@ pub mod engine;
 pub mod policy;
 pub mod proof;
 pub mod storage;
+pub mod pipeline;
+pub mod mempool;
+pub mod signature;
+pub mod selftest;
 pub mod backend;
 pub mod backend_stub;
 
@@ -18,6 +22,21 @@ pub mod backend_zkvm;
 #[cfg(test)]
 mod tests;
 
+#[cfg(test)]
+mod tests_pipeline;
+
+#[cfg(test)]
+mod tests_mempool;
+
+#[cfg(test)]
+mod tests_signature;
+
+#[cfg(test)]
+mod tests_storage;
+
+#[cfg(test)]
+mod tests_selftest;
+
 #[cfg(all(test, feature = "zk-halo2"))]
 mod tests_halo2;
 
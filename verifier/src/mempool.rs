@@ -0,0 +1,330 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+use zkcg_common::{
+    errors::ProtocolError,
+    state::ProtocolState,
+    types::{Commitment, Hash},
+};
+
+use crate::engine::{PublicInputs, VerifiedInputs, VerifierEngine};
+
+#[derive(Debug, Clone)]
+pub struct MempoolConfig {
+    /// Total queued submissions across all clients.
+    pub max_entries: usize,
+    /// Queued submissions any single client may hold.
+    pub max_per_client: usize,
+    /// How far past `nonce + 1` a submission may be queued.
+    pub max_lookahead: u64,
+    /// Queued submissions older than this are dropped.
+    pub ttl: Duration,
+}
+
+impl Default for MempoolConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 1024,
+            max_per_client: 16,
+            max_lookahead: 64,
+            ttl: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Outcome of [`VerifierEngine::admit`].
+#[derive(Debug)]
+pub enum Admission {
+    /// The submission was applied, followed by any queued successors it
+    /// unblocked. Transitions are listed in the order they were applied.
+    Accepted(Vec<AppliedTransition>),
+    /// The submission is waiting in the mempool for its predecessor.
+    Queued,
+}
+
+/// Why a queued submission left the mempool without being applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropReason {
+    /// Queued for longer than [`MempoolConfig::ttl`]
+    Expired,
+    /// Another submission for its nonce was applied first
+    Superseded,
+    /// Its nonce came up but it did not chain onto the state
+    Stale,
+}
+
+impl DropReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DropReason::Expired => "expired",
+            DropReason::Superseded => "superseded",
+            DropReason::Stale => "stale",
+        }
+    }
+}
+
+/// A queued submission that will never be applied, from
+/// [`VerifierEngine::take_dropped`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DroppedSubmission {
+    pub client: String,
+    pub submission_id: Hash,
+    pub nonce: u64,
+    pub reason: DropReason,
+}
+
+#[derive(Debug, Clone)]
+pub struct AppliedTransition {
+    pub client: String,
+    /// Id the submission was admitted with
+    pub submission_id: Hash,
+    pub before: ProtocolState,
+    pub after: ProtocolState,
+}
+
+struct Pending {
+    client: String,
+    id: Hash,
+    verified: VerifiedInputs,
+    commitment: Commitment,
+    queued_at: Instant,
+}
+
+/// Verified submissions whose nonce is ahead of the engine state.
+///
+/// Proofs are verified before they are queued, so draining only re-runs the
+/// cheap state checks in [`VerifierEngine::apply`].
+pub struct Mempool {
+    config: MempoolConfig,
+    by_nonce: BTreeMap<u64, VecDeque<Pending>>,
+    len: usize,
+    /// Since the last [`VerifierEngine::take_dropped`]
+    dropped: Vec<DroppedSubmission>,
+}
+
+impl Mempool {
+    pub fn new(config: MempoolConfig) -> Self {
+        Self {
+            config,
+            by_nonce: BTreeMap::new(),
+            len: 0,
+            dropped: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Nonces currently waiting, with how many submissions each.
+    pub fn queued_nonces(&self) -> Vec<(u64, usize)> {
+        self.by_nonce.iter().map(|(n, q)| (*n, q.len())).collect()
+    }
+
+    fn client_len(&self, client: &str) -> usize {
+        self.by_nonce
+            .values()
+            .flat_map(|q| q.iter())
+            .filter(|p| p.client == client)
+            .count()
+    }
+
+    /// Whether a submission for `nonce` from `client` would be queued.
+    fn can_queue(&self, client: &str, nonce: u64, current: u64) -> bool {
+        nonce <= current + 1 + self.config.max_lookahead
+            && self.len < self.config.max_entries
+            && self.client_len(client) < self.config.max_per_client
+    }
+
+    fn push(&mut self, pending: Pending) {
+        let nonce = pending.verified.inputs().nonce;
+        self.by_nonce.entry(nonce).or_default().push_back(pending);
+        self.len += 1;
+    }
+
+    fn take(&mut self, nonce: u64) -> Option<VecDeque<Pending>> {
+        let queue = self.by_nonce.remove(&nonce)?;
+        self.len -= queue.len();
+        Some(queue)
+    }
+
+    fn drop_pending(&mut self, pending: Pending, reason: DropReason) {
+        self.dropped.push(DroppedSubmission {
+            nonce: pending.verified.inputs().nonce,
+            client: pending.client,
+            submission_id: pending.id,
+            reason,
+        });
+    }
+
+    /// Drop expired entries and anything the state has already moved past.
+    fn purge(&mut self, current_nonce: u64) {
+        let ttl = self.config.ttl;
+        let now = Instant::now();
+
+        let later = self.by_nonce.split_off(&(current_nonce + 1));
+        for pending in std::mem::replace(&mut self.by_nonce, later).into_values().flatten() {
+            self.drop_pending(pending, DropReason::Superseded);
+        }
+
+        let mut expired = Vec::new();
+        self.by_nonce.retain(|_, queue| {
+            let (fresh, old) = queue
+                .drain(..)
+                .partition(|p| now.duration_since(p.queued_at) < ttl);
+            *queue = fresh;
+            expired.extend(old);
+            !queue.is_empty()
+        });
+        for pending in expired {
+            self.drop_pending(pending, DropReason::Expired);
+        }
+        self.len = self.by_nonce.values().map(|q| q.len()).sum();
+    }
+}
+
+impl VerifierEngine {
+    pub fn with_mempool(mut self, config: MempoolConfig) -> Self {
+        self.mempool = Some(Mempool::new(config));
+        self
+    }
+
+    pub fn mempool(&self) -> Option<&Mempool> {
+        self.mempool.as_ref()
+    }
+
+    /// Queued submissions dropped since the last call, oldest first.
+    ///
+    /// Entries are dropped while [`VerifierEngine::admit`] runs; callers
+    /// take them afterwards to report them.
+    pub fn take_dropped(&mut self) -> Vec<DroppedSubmission> {
+        self.mempool
+            .as_mut()
+            .map(|pool| std::mem::take(&mut pool.dropped))
+            .unwrap_or_default()
+    }
+
+    /// Pre-check that tolerates future nonces when they can be queued.
+    ///
+    /// Without a mempool this is [`VerifierEngine::check_state`].
+    pub fn precheck(&self, client: &str, public_inputs: &PublicInputs) -> Result<(), ProtocolError> {
+        let current = self.state().nonce;
+
+        if let Some(pool) = &self.mempool {
+            if public_inputs.nonce > current + 1 {
+                return if pool.can_queue(client, public_inputs.nonce, current) {
+                    Ok(())
+                } else {
+                    Err(ProtocolError::InvalidNonce)
+                };
+            }
+        }
+
+        self.check_state(public_inputs)
+    }
+
+    /// Apply a verified submission, or queue it if its nonce is ahead.
+    ///
+    /// Once a submission is applied, queued successors are applied in nonce
+    /// order for as long as they chain onto the new state. `id` identifies
+    /// the submission in the [`AppliedTransition`] or [`DroppedSubmission`]
+    /// it ends up in, since several can be queued for one nonce.
+    pub fn admit(
+        &mut self,
+        client: &str,
+        id: Hash,
+        verified: VerifiedInputs,
+        commitment: Commitment,
+    ) -> Result<Admission, ProtocolError> {
+        let current = self.state().nonce;
+        let nonce = verified.inputs().nonce;
+
+        if let Some(pool) = &mut self.mempool {
+            pool.purge(current);
+
+            if nonce > current + 1 {
+                if !pool.can_queue(client, nonce, current) {
+                    return Err(ProtocolError::InvalidNonce);
+                }
+                pool.push(Pending {
+                    client: client.to_string(),
+                    id,
+                    verified,
+                    commitment,
+                    queued_at: Instant::now(),
+                });
+                return Ok(Admission::Queued);
+            }
+        }
+
+        let before = self.state().clone();
+        self.apply(verified, commitment)?;
+
+        let mut applied = vec![AppliedTransition {
+            client: client.to_string(),
+            submission_id: id,
+            before,
+            after: self.state().clone(),
+        }];
+        self.drain_mempool(&mut applied);
+
+        Ok(Admission::Accepted(applied))
+    }
+
+    fn drain_mempool(&mut self, applied: &mut Vec<AppliedTransition>) {
+        loop {
+            let current = self.state().nonce;
+            let Some(pool) = self.mempool.as_mut() else {
+                return;
+            };
+            pool.purge(current);
+            let Some(candidates) = pool.take(current + 1) else {
+                return;
+            };
+
+            // competing submissions for the same nonce: the first one that
+            // chains onto the current root wins, the rest are dropped
+            let mut progressed = false;
+            let mut stale = Vec::new();
+            let mut candidates = candidates.into_iter();
+            for pending in candidates.by_ref() {
+                let before = self.state().clone();
+                // `apply` consumes the proof, so keep what a drop report needs
+                let (client, submission_id, nonce) =
+                    (pending.client.clone(), pending.id, pending.verified.inputs().nonce);
+                if self.apply(pending.verified, pending.commitment).is_ok() {
+                    applied.push(AppliedTransition {
+                        client,
+                        submission_id,
+                        before,
+                        after: self.state().clone(),
+                    });
+                    progressed = true;
+                    break;
+                }
+                stale.push(DroppedSubmission {
+                    client,
+                    submission_id,
+                    nonce,
+                    reason: DropReason::Stale,
+                });
+            }
+
+            let pool = self.mempool.as_mut().expect("drained from this mempool");
+            pool.dropped.extend(stale);
+            for pending in candidates {
+                pool.drop_pending(pending, DropReason::Superseded);
+            }
+
+            if !progressed {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use zkcg_common::{
+    errors::ProtocolError,
+    types::{Commitment, Hash},
+    wire::{self, PublicInputsDto, SubmissionSignatureDto, SubmitProofRequest},
+};
+
+use crate::engine::{PublicInputs, VerifierEngine};
+use crate::signature::{submission_bytes, ProverSignature};
+
+/// One proof submission, as handed to [`VerifierEngine::process_batch`].
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub proof_bytes: Vec<u8>,
+    pub public_inputs: PublicInputs,
+    pub commitment: Commitment,
+    pub signature: Option<ProverSignature>,
+}
+
+impl Submission {
+    /// Stable identifier of the (proof, public inputs, commitment) tuple:
+    /// `sha256` of its [`submission_bytes`]. The signature is not covered, so
+    /// the same proof re-signed by another prover keeps its id.
+    pub fn id(&self) -> Hash {
+        Sha256::digest(submission_bytes(&self.proof_bytes, &self.public_inputs, &self.commitment)).into()
+    }
+
+    /// Decode a JSON submission; proofs over `max_proof` bytes are refused
+    /// before they are decoded.
+    pub fn from_wire(req: &SubmitProofRequest, max_proof: usize) -> Result<Self, ProtocolError> {
+        let signature = req
+            .signature
+            .as_ref()
+            .map(|sig| {
+                let (public_key, signature) = sig.decode()?;
+                Ok::<_, ProtocolError>(ProverSignature { public_key, signature })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            proof_bytes: wire::decode_proof(&req.proof, max_proof)?,
+            public_inputs: PublicInputs {
+                threshold: req.public_inputs.threshold,
+                old_state_root: req.public_inputs.old_state_root,
+                nonce: req.public_inputs.nonce,
+            },
+            commitment: Commitment(req.new_state_commitment),
+            signature,
+        })
+    }
+
+    pub fn to_wire(&self) -> SubmitProofRequest {
+        SubmitProofRequest {
+            proof: wire::encode_proof(&self.proof_bytes),
+            public_inputs: PublicInputsDto {
+                threshold: self.public_inputs.threshold,
+                old_state_root: self.public_inputs.old_state_root,
+                nonce: self.public_inputs.nonce,
+            },
+            new_state_commitment: self.commitment.0,
+            signature: self
+                .signature
+                .as_ref()
+                .map(|sig| SubmissionSignatureDto::new(&sig.public_key, &sig.signature)),
+        }
+    }
+}
+
+impl VerifierEngine {
+    /// Verify a batch of submissions in parallel, then apply them in nonce order.
+    ///
+    /// Proof verification does not depend on engine state, so every proof is
+    /// checked concurrently on the rayon pool. Application is sequential and
+    /// re-checks the state root and nonce of each submission against the state
+    /// left by its predecessor, exactly as [`VerifierEngine::process_transition`]
+    /// would.
+    ///
+    /// Results are returned in the order the submissions were given. A rejected
+    /// submission does not stop the batch, but anything chained on top of it
+    /// will fail its state check.
+    pub fn process_batch(
+        &mut self,
+        submissions: Vec<Submission>,
+    ) -> Vec<Result<(), ProtocolError>> {
+        let verifier = self.verifier();
+
+        // 1. Verify everything concurrently
+        let verified: Vec<_> = submissions
+            .into_par_iter()
+            .map(|s| {
+                let nonce = s.public_inputs.nonce;
+                let result = match &s.signature {
+                    Some(sig) => {
+                        verifier.verify_signed(&s.proof_bytes, s.public_inputs, &s.commitment, sig)
+                    }
+                    None => verifier.verify(&s.proof_bytes, s.public_inputs),
+                };
+                (nonce, result, s.commitment)
+            })
+            .collect();
+
+        // 2. Apply strictly by nonce; ties keep submission order
+        let mut order: Vec<usize> = (0..verified.len()).collect();
+        order.sort_by_key(|&i| verified[i].0);
+
+        let mut slots: Vec<Option<_>> = verified.into_iter().map(Some).collect();
+        let mut results: Vec<Result<(), ProtocolError>> = Vec::with_capacity(slots.len());
+        results.resize_with(slots.len(), || Ok(()));
+
+        for i in order {
+            let (_, verified, commitment) = slots[i].take().expect("each index visited once");
+            results[i] = verified.and_then(|v| self.apply(v, commitment));
+        }
+
+        results
+    }
+}
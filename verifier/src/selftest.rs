@@ -0,0 +1,146 @@
+use std::{fs, io, path::Path};
+
+use zkcg_common::{
+    errors::ProtocolError,
+    wire::{self, PublicInputsDto, SubmitProofRequest},
+};
+
+use crate::{backend::ProofBackend, engine::PublicInputs};
+
+/// A proof whose verdict is known in advance.
+#[derive(Debug, Clone)]
+pub struct KnownAnswer {
+    pub label: &'static str,
+    pub proof_bytes: Vec<u8>,
+    pub public_inputs: PublicInputs,
+    pub expect_valid: bool,
+}
+
+impl KnownAnswer {
+    pub fn valid(label: &'static str, proof_bytes: Vec<u8>, public_inputs: PublicInputs) -> Self {
+        Self {
+            label,
+            proof_bytes,
+            public_inputs,
+            expect_valid: true,
+        }
+    }
+
+    pub fn invalid(label: &'static str, proof_bytes: Vec<u8>, public_inputs: PublicInputs) -> Self {
+        Self {
+            label,
+            proof_bytes,
+            public_inputs,
+            expect_valid: false,
+        }
+    }
+}
+
+/// A proof the configured backend must accept, made offline against the
+/// release's params and verifying key or guest image.
+///
+/// It is stored in the [`SubmitProofRequest`] form `zkcg verify` reads and is
+/// never produced by the node itself, so a key or image id the node loads
+/// wrongly rejects it. `examples/selftest_fixture.rs` writes one.
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub proof_bytes: Vec<u8>,
+    pub public_inputs: PublicInputs,
+}
+
+impl Fixture {
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let req: SubmitProofRequest =
+            serde_json::from_str(&fs::read_to_string(path)?).map_err(|e| invalid(e.to_string()))?;
+        Self::from_wire(&req).map_err(|e| invalid(format!("{:?}", e)))
+    }
+
+    pub fn from_wire(req: &SubmitProofRequest) -> Result<Self, ProtocolError> {
+        Ok(Self {
+            proof_bytes: wire::decode_proof(&req.proof, usize::MAX)?,
+            public_inputs: PublicInputs {
+                threshold: req.public_inputs.threshold,
+                old_state_root: req.public_inputs.old_state_root,
+                nonce: req.public_inputs.nonce,
+            },
+        })
+    }
+
+    /// Wire form, with an all-zero state commitment, which no backend reads.
+    pub fn to_wire(&self) -> SubmitProofRequest {
+        SubmitProofRequest {
+            proof: wire::encode_proof(&self.proof_bytes),
+            public_inputs: PublicInputsDto {
+                threshold: self.public_inputs.threshold,
+                old_state_root: self.public_inputs.old_state_root,
+                nonce: self.public_inputs.nonce,
+            },
+            new_state_commitment: [0u8; 32],
+            signature: None,
+        }
+    }
+}
+
+/// Outcome of running a backend against its known answers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub backend: &'static str,
+    /// Number of vectors checked; zero means the backend has none.
+    pub checked: usize,
+    pub failures: Vec<String>,
+}
+
+impl SelfTestReport {
+    /// A backend that checked nothing has not passed.
+    pub fn passed(&self) -> bool {
+        self.checked > 0 && self.failures.is_empty()
+    }
+
+    pub fn skipped(&self) -> bool {
+        self.checked == 0
+    }
+}
+
+/// Verify every known answer of `backend`, plus those built around `fixture`,
+/// and record any verdict that differs.
+///
+/// A backend must accept at least one good proof *and* reject every bad one.
+/// Only a [`Fixture`] made offline can be a good proof, so without one a
+/// backend does not pass.
+pub fn run(backend: &dyn ProofBackend, fixture: Option<&Fixture>) -> SelfTestReport {
+    let mut answers = backend.known_answers();
+    if let Some(fixture) = fixture {
+        answers.extend(backend.fixture_answers(fixture));
+    }
+    let mut failures = Vec::new();
+
+    for answer in &answers {
+        let accepted = backend
+            .verify(&answer.proof_bytes, &answer.public_inputs)
+            .is_ok();
+
+        if accepted != answer.expect_valid {
+            failures.push(format!(
+                "{}: expected {}, got {}",
+                answer.label,
+                verdict(answer.expect_valid),
+                verdict(accepted),
+            ));
+        }
+    }
+
+    if !answers.iter().any(|a| a.expect_valid) {
+        failures.push("no known-good proof to check".to_string());
+    }
+
+    SelfTestReport {
+        backend: backend.name(),
+        checked: answers.len(),
+        failures,
+    }
+}
+
+fn verdict(accepted: bool) -> &'static str {
+    if accepted { "accepted" } else { "rejected" }
+}
@@ -0,0 +1,74 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use zkcg_common::{errors::ProtocolError, types::{Commitment, Hash}};
+
+use crate::engine::PublicInputs;
+
+/// Domain tag prefixed to the canonical bytes of every submission.
+pub const SUBMISSION_DOMAIN: &[u8] = b"zkcg/submission/v1";
+
+/// Domain tag prefixed to what a prover signs.
+pub const SIGNATURE_DOMAIN: &[u8] = b"zkcg/signature/v2";
+
+/// Ed25519 signature by a prover over [`signed_bytes`].
+#[derive(Debug, Clone)]
+pub struct ProverSignature {
+    pub public_key: Hash,
+    pub signature: [u8; 64],
+}
+
+/// Canonical bytes a prover signs for one submission.
+///
+/// `domain || threshold (u64 BE) || old_state_root || nonce (u64 BE) ||
+/// new_state_commitment || sha256(proof)`
+pub fn submission_bytes(
+    proof_bytes: &[u8],
+    public_inputs: &PublicInputs,
+    commitment: &Commitment,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(SUBMISSION_DOMAIN.len() + 8 + 32 + 8 + 32 + 32);
+    out.extend_from_slice(SUBMISSION_DOMAIN);
+    out.extend_from_slice(&public_inputs.threshold.to_be_bytes());
+    out.extend_from_slice(&public_inputs.old_state_root);
+    out.extend_from_slice(&public_inputs.nonce.to_be_bytes());
+    out.extend_from_slice(&commitment.0);
+    out.extend_from_slice(&Sha256::digest(proof_bytes));
+    out
+}
+
+/// Bytes a prover signs to submit on `channel`.
+///
+/// `SIGNATURE_DOMAIN || channel || submission_bytes`, so a signature for
+/// one channel cannot advance another that allowlists the same key.
+pub fn signed_bytes(
+    channel: &Hash,
+    proof_bytes: &[u8],
+    public_inputs: &PublicInputs,
+    commitment: &Commitment,
+) -> Vec<u8> {
+    let submission = submission_bytes(proof_bytes, public_inputs, commitment);
+    let mut out = Vec::with_capacity(SIGNATURE_DOMAIN.len() + 32 + submission.len());
+    out.extend_from_slice(SIGNATURE_DOMAIN);
+    out.extend_from_slice(channel);
+    out.extend_from_slice(&submission);
+    out
+}
+
+/// Check `signature` over the [`signed_bytes`] of this submission on `channel`.
+pub fn verify_submission(
+    signature: &ProverSignature,
+    channel: &Hash,
+    proof_bytes: &[u8],
+    public_inputs: &PublicInputs,
+    commitment: &Commitment,
+) -> Result<(), ProtocolError> {
+    let key = VerifyingKey::from_bytes(&signature.public_key)
+        .map_err(|_| ProtocolError::UnauthorizedProver)?;
+    let sig = Signature::from_bytes(&signature.signature);
+
+    key.verify_strict(
+        &signed_bytes(channel, proof_bytes, public_inputs, commitment),
+        &sig,
+    )
+        .map_err(|_| ProtocolError::UnauthorizedProver)
+}
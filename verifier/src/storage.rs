@@ -1,23 +1,123 @@
-use zkcg_common::state::ProtocolState;
-use std::sync::{Arc, Mutex};
-
-#[derive(Clone)]
-pub struct StateStore {
-    inner: Arc<Mutex<ProtocolState>>,
-}
-
-impl StateStore {
-    pub fn new(state: ProtocolState) -> Self {
-        Self {
-            inner: Arc::new(Mutex::new(state)),
-        }
-    }
-
-    pub fn load(&self) -> ProtocolState {
-        self.inner.lock().unwrap().clone()
-    }
-
-    pub fn save(&self, state: ProtocolState) {
-        *self.inner.lock().unwrap() = state;
-    }
-}
+use serde::Serialize;
+use zkcg_common::state::ProtocolState;
+use std::{
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+struct Saved {
+    state: ProtocolState,
+    /// Bumped by every [`StateStore::save`]
+    generation: u64,
+}
+
+/// Latest accepted protocol state, optionally mirrored to a JSON file.
+///
+/// [`StateStore::save`] only replaces the in-memory copy, so it is cheap
+/// enough to call under the engine lock and keeps saves in transition order.
+/// [`StateStore::flush`] then writes the latest saved state to disk; it
+/// blocks, so async callers run it on a blocking thread.
+#[derive(Clone)]
+pub struct StateStore {
+    inner: Arc<Mutex<Saved>>,
+    path: Option<PathBuf>,
+    /// Generation last written to `path`; held for the whole write so
+    /// flushes never overtake each other
+    written: Arc<Mutex<u64>>,
+}
+
+impl StateStore {
+    pub fn new(state: ProtocolState) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Saved { state, generation: 0 })),
+            path: None,
+            written: Arc::new(Mutex::new(0)),
+        }
+    }
+
+    /// Resume from `path` if it holds a saved state, otherwise start at `genesis`.
+    ///
+    /// `None` keeps the state in memory only.
+    pub fn open(path: Option<PathBuf>, genesis: ProtocolState) -> io::Result<Self> {
+        let state = match &path {
+            Some(p) if p.exists() => {
+                let raw = fs::read(p)?;
+                serde_json::from_slice(&raw)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+            }
+            _ => genesis,
+        };
+
+        Ok(Self {
+            path,
+            ..Self::new(state)
+        })
+    }
+
+    pub fn load(&self) -> ProtocolState {
+        self.inner.lock().unwrap().state.clone()
+    }
+
+    /// Replace the stored state in memory. Nothing is durable until the
+    /// next [`StateStore::flush`].
+    pub fn save(&self, state: ProtocolState) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.state = state;
+        inner.generation += 1;
+    }
+
+    /// Write the latest saved state to disk, unless a concurrent flush
+    /// already has. A no-op for in-memory stores.
+    ///
+    /// Once this returns `Ok`, every state saved before the call survives a
+    /// restart.
+    pub fn flush(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut written = self.written.lock().unwrap();
+        let (state, generation) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.state.clone(), inner.generation)
+        };
+        if *written >= generation {
+            return Ok(());
+        }
+
+        write_json(path, &state)?;
+        *written = generation;
+        Ok(())
+    }
+}
+
+/// Atomically replace `path` with `value` as pretty JSON: written to a
+/// temporary file, synced, then renamed over the original.
+pub fn write_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    write_json_as(path, value, false)
+}
+
+/// [`write_json`] for files holding secrets: on unix the file is readable
+/// and writable by its owner only (`0600`).
+pub fn write_private_json(path: &Path, value: &impl Serialize) -> io::Result<()> {
+    write_json_as(path, value, true)
+}
+
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_json_as(path: &Path, value: &impl Serialize, private: bool) -> io::Result<()> {
+    let raw = serde_json::to_vec_pretty(value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    let tmp = path.with_extension("tmp");
+    let mut file = fs::File::create(&tmp)?;
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::PermissionsExt;
+        // before any secret is written, and whatever mode a stale tmp had
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+    file.write_all(&raw)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
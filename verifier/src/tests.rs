@@ -1,10 +1,11 @@
-    use crate::engine::{PublicInputs, VerifierEngine};
+    use crate::engine::{check_proof, PublicInputs, VerifierEngine};
     use zkcg_common::{
         state::ProtocolState,
         types::Commitment,
         errors::ProtocolError,
     };
     use crate::backend_stub::StubBackend;
+    use crate::backend::{WithMaxProofSize, DEFAULT_MAX_PROOF_SIZE};
 
     fn dummy_commitment() -> Commitment {
         Commitment([42u8; 32])
@@ -150,3 +151,74 @@
         assert_eq!(updated.state_root, commitment.0);
     }
     
+    #[test]
+    fn detached_verification_then_apply_succeeds() {
+        let state = initial_state();
+        let mut engine = VerifierEngine::new(state.clone(), Box::new(StubBackend::default()));
+
+        let inputs = valid_inputs(&state);
+        engine.check_state(&inputs).unwrap();
+
+        let verified = engine.verifier().verify(b"valid-proof", inputs).unwrap();
+
+        engine.apply(verified, dummy_commitment()).unwrap();
+
+        assert_eq!(engine.state().nonce, 1);
+        assert_eq!(engine.state().state_root, dummy_commitment().0);
+    }
+
+    #[test]
+    fn apply_rechecks_state_after_concurrent_transition() {
+        let state = initial_state();
+        let mut engine = VerifierEngine::new(state.clone(), Box::new(StubBackend::default()));
+
+        // two submissions verified against the same snapshot
+        let verifier = engine.verifier();
+        let first = verifier.verify(b"proof-a", valid_inputs(&state)).unwrap();
+        let second = verifier.verify(b"proof-b", valid_inputs(&state)).unwrap();
+
+        engine.apply(first, dummy_commitment()).unwrap();
+
+        let err = engine.apply(second, dummy_commitment()).unwrap_err();
+        assert!(matches!(err, ProtocolError::StateMismatch));
+        assert_eq!(engine.state().nonce, 1);
+    }
+
+    #[test]
+    fn oversized_or_empty_proof_is_invalid_format() {
+        let state = initial_state();
+        let backend = StubBackend::default();
+
+        let oversized = vec![0u8; DEFAULT_MAX_PROOF_SIZE + 1];
+        let err = check_proof(&backend, &oversized, &valid_inputs(&state)).unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidFormat));
+
+        let err = check_proof(&backend, &[], &valid_inputs(&state)).unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidFormat));
+
+        let largest = vec![0u8; DEFAULT_MAX_PROOF_SIZE];
+        assert!(check_proof(&backend, &largest, &valid_inputs(&state)).is_ok());
+    }
+
+    #[test]
+    fn configured_proof_size_cap_applies() {
+        let state = initial_state();
+        let backend = WithMaxProofSize::new(Box::new(StubBackend::default()), 8);
+
+        assert!(check_proof(&backend, b"12345678", &valid_inputs(&state)).is_ok());
+        let err = check_proof(&backend, b"123456789", &valid_inputs(&state)).unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidFormat));
+    }
+
+    #[test]
+    fn inputs_verified_by_another_engine_are_not_applied() {
+        let state = initial_state();
+        let mut engine = VerifierEngine::new(state.clone(), Box::new(StubBackend::default()));
+        let other = VerifierEngine::new(state.clone(), Box::new(StubBackend::default()));
+
+        let verified = other.verifier().verify(b"valid-proof", valid_inputs(&state)).unwrap();
+
+        let err = engine.apply(verified, dummy_commitment()).unwrap_err();
+        assert!(matches!(err, ProtocolError::InvalidProof));
+        assert_eq!(engine.state().nonce, 0);
+    }
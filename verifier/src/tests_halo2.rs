@@ -16,6 +16,7 @@ use crate::{
     backend::ProofBackend,
     backend_halo2::Halo2Backend,
     engine::PublicInputs,
+    selftest::{self, Fixture},
 };
 
 /// Generate a valid Halo2 proof using fresh params
@@ -166,4 +167,25 @@ fn empty_proof_is_rejected() {
     };
 
     assert!(backend.verify(&[], &inputs).is_err());
-}
\ No newline at end of file
+}
+#[test]
+fn self_test_rejects_a_fixture_made_for_other_keys() {
+    let params: Params<G1Affine> = Params::new(9);
+    let fixture = Fixture {
+        proof_bytes: generate_valid_proof_with_params(40, 50, &params),
+        public_inputs: PublicInputs {
+            threshold: 50,
+            old_state_root: [0u8; 32],
+            nonce: 1,
+        },
+    };
+
+    let report = selftest::run(&backend(params), Some(&fixture));
+    assert_eq!(report.checked, 3);
+    assert!(report.passed(), "{:?}", report.failures);
+
+    // keys for another circuit size stand in for a mis-loaded VK
+    let report = selftest::run(&backend(Params::new(10)), Some(&fixture));
+    assert!(!report.passed());
+    assert!(report.failures[0].starts_with("score proof: expected accepted"));
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use crate::{
+    backend_stub::StubBackend,
+    engine::{PublicInputs, VerifiedInputs, VerifierEngine},
+    mempool::{Admission, DropReason, DroppedSubmission, MempoolConfig},
+};
+use zkcg_common::{
+    errors::ProtocolError,
+    state::ProtocolState,
+    types::{Commitment, Hash},
+};
+
+fn root(n: u64) -> [u8; 32] {
+    if n == 0 { [0u8; 32] } else { [n as u8; 32] }
+}
+
+fn inputs(nonce: u64) -> PublicInputs {
+    PublicInputs {
+        threshold: 10,
+        old_state_root: root(nonce - 1),
+        nonce,
+    }
+}
+
+fn verified(engine: &VerifierEngine, nonce: u64) -> VerifiedInputs {
+    engine.verifier().verify(b"proof", inputs(nonce)).unwrap()
+}
+
+fn engine(config: MempoolConfig) -> VerifierEngine {
+    VerifierEngine::new(ProtocolState::genesis(), Box::new(StubBackend)).with_mempool(config)
+}
+
+/// Stand-in submission id, distinct per client and nonce.
+fn id(client: &str, nonce: u64) -> Hash {
+    let mut id = [nonce as u8; 32];
+    id[0] = client.as_bytes()[0];
+    id
+}
+
+fn admit(engine: &mut VerifierEngine, client: &str, nonce: u64) -> Result<Admission, ProtocolError> {
+    engine.precheck(client, &inputs(nonce))?;
+    let verified = verified(engine, nonce);
+    engine.admit(client, id(client, nonce), verified, Commitment(root(nonce)))
+}
+
+#[test]
+fn future_nonce_is_queued_then_applied() {
+    let mut engine = engine(MempoolConfig::default());
+
+    assert!(matches!(admit(&mut engine, "alice", 3), Ok(Admission::Queued)));
+    assert!(matches!(admit(&mut engine, "alice", 2), Ok(Admission::Queued)));
+    assert_eq!(engine.state().nonce, 0);
+    assert_eq!(engine.mempool().unwrap().len(), 2);
+
+    let Ok(Admission::Accepted(applied)) = admit(&mut engine, "alice", 1) else {
+        panic!("nonce 1 should apply directly");
+    };
+
+    let nonces: Vec<u64> = applied.iter().map(|t| t.after.nonce).collect();
+    assert_eq!(nonces, vec![1, 2, 3]);
+    assert_eq!(engine.state().state_root, root(3));
+    assert!(engine.mempool().unwrap().is_empty());
+}
+
+#[test]
+fn without_mempool_future_nonce_is_rejected() {
+    let mut engine = VerifierEngine::new(ProtocolState::genesis(), Box::new(StubBackend));
+
+    assert!(admit(&mut engine, "alice", 2).is_err());
+    assert!(engine.mempool().is_none());
+}
+
+#[test]
+fn per_client_limit_is_enforced() {
+    let mut engine = engine(MempoolConfig {
+        max_per_client: 1,
+        ..MempoolConfig::default()
+    });
+
+    assert!(matches!(admit(&mut engine, "alice", 2), Ok(Admission::Queued)));
+    assert!(matches!(admit(&mut engine, "alice", 3), Err(ProtocolError::InvalidNonce)));
+    assert!(matches!(admit(&mut engine, "bob", 3), Ok(Admission::Queued)));
+}
+
+#[test]
+fn total_capacity_and_lookahead_are_enforced() {
+    let mut engine = engine(MempoolConfig {
+        max_entries: 1,
+        max_lookahead: 2,
+        ..MempoolConfig::default()
+    });
+
+    assert!(matches!(admit(&mut engine, "alice", 4), Err(ProtocolError::InvalidNonce)));
+    assert!(matches!(admit(&mut engine, "alice", 3), Ok(Admission::Queued)));
+    assert!(matches!(admit(&mut engine, "bob", 2), Err(ProtocolError::InvalidNonce)));
+}
+
+#[test]
+fn expired_entries_are_not_applied() {
+    let mut engine = engine(MempoolConfig {
+        ttl: Duration::ZERO,
+        ..MempoolConfig::default()
+    });
+
+    assert!(matches!(admit(&mut engine, "alice", 2), Ok(Admission::Queued)));
+
+    let Ok(Admission::Accepted(applied)) = admit(&mut engine, "alice", 1) else {
+        panic!("nonce 1 should apply directly");
+    };
+
+    assert_eq!(applied.len(), 1);
+    assert_eq!(engine.state().nonce, 1);
+    assert!(engine.mempool().unwrap().is_empty());
+    assert_eq!(engine.take_dropped(), vec![dropped("alice", 2, DropReason::Expired)]);
+    assert!(engine.take_dropped().is_empty());
+}
+
+fn dropped(client: &str, nonce: u64, reason: DropReason) -> DroppedSubmission {
+    DroppedSubmission {
+        client: client.to_string(),
+        submission_id: id(client, nonce),
+        nonce,
+        reason,
+    }
+}
+
+#[test]
+fn stale_queued_entry_is_dropped() {
+    let mut engine = engine(MempoolConfig::default());
+
+    // queued on top of a root that nonce 1 will not produce
+    let mut forked = inputs(2);
+    forked.old_state_root = [0xEE; 32];
+    engine.precheck("mallory", &forked).unwrap();
+    let forked = engine.verifier().verify(b"proof", forked).unwrap();
+    assert!(matches!(
+        engine.admit("mallory", id("mallory", 2), forked, Commitment(root(2))),
+        Ok(Admission::Queued)
+    ));
+
+    let Ok(Admission::Accepted(applied)) = admit(&mut engine, "alice", 1) else {
+        panic!("nonce 1 should apply directly");
+    };
+
+    assert_eq!(applied.len(), 1);
+    assert_eq!(engine.state().nonce, 1);
+    assert!(engine.mempool().unwrap().is_empty());
+    assert_eq!(engine.take_dropped(), vec![dropped("mallory", 2, DropReason::Stale)]);
+}
+
+#[test]
+fn competing_entries_are_superseded_by_the_one_applied() {
+    let mut engine = engine(MempoolConfig::default());
+
+    assert!(matches!(admit(&mut engine, "alice", 2), Ok(Admission::Queued)));
+    assert!(matches!(admit(&mut engine, "bob", 2), Ok(Admission::Queued)));
+    let Ok(Admission::Accepted(applied)) = admit(&mut engine, "alice", 1) else {
+        panic!("nonce 1 should apply directly");
+    };
+
+    assert_eq!(engine.state().nonce, 2);
+    assert_eq!(applied[1].submission_id, id("alice", 2));
+    assert_eq!(engine.take_dropped(), vec![dropped("bob", 2, DropReason::Superseded)]);
+}
+
+#[test]
+fn past_nonce_is_still_rejected() {
+    let mut engine = engine(MempoolConfig::default());
+    admit(&mut engine, "alice", 1).unwrap();
+
+    assert!(matches!(admit(&mut engine, "alice", 1), Err(ProtocolError::StateMismatch)));
+}
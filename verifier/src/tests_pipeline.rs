@@ -0,0 +1,113 @@
+use crate::{
+    backend::ProofBackend,
+    engine::{PublicInputs, VerifierEngine},
+    pipeline::Submission,
+};
+use zkcg_common::{
+    errors::ProtocolError,
+    state::ProtocolState,
+    types::Commitment,
+};
+
+/// Accepts every proof except the literal bytes `b"bad"`.
+struct RejectingBackend;
+
+impl ProofBackend for RejectingBackend {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        _public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        if proof_bytes == b"bad" {
+            Err(ProtocolError::InvalidProof)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+fn root(n: u8) -> [u8; 32] {
+    if n == 0 { [0u8; 32] } else { [n; 32] }
+}
+
+/// Submission moving the chain from `root(nonce - 1)` to `root(nonce)`.
+fn chained(nonce: u64, proof: &[u8]) -> Submission {
+    Submission {
+        proof_bytes: proof.to_vec(),
+        public_inputs: PublicInputs {
+            threshold: 10,
+            old_state_root: root(nonce as u8 - 1),
+            nonce,
+        },
+        commitment: Commitment(root(nonce as u8)),
+        signature: None,
+    }
+}
+
+fn engine() -> VerifierEngine {
+    VerifierEngine::new(ProtocolState::genesis(), Box::new(RejectingBackend))
+}
+
+#[test]
+fn out_of_order_batch_is_applied_by_nonce() {
+    let mut engine = engine();
+
+    let results = engine.process_batch(vec![
+        chained(3, b"p3"),
+        chained(1, b"p1"),
+        chained(2, b"p2"),
+    ]);
+
+    assert!(results.iter().all(|r| r.is_ok()));
+    assert_eq!(engine.state().nonce, 3);
+    assert_eq!(engine.state().state_root, root(3));
+}
+
+#[test]
+fn rejected_proof_breaks_the_chain_after_it() {
+    let mut engine = engine();
+
+    let results = engine.process_batch(vec![
+        chained(1, b"p1"),
+        chained(2, b"bad"),
+        chained(3, b"p3"),
+    ]);
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(ProtocolError::InvalidProof)));
+    assert!(matches!(results[2], Err(ProtocolError::StateMismatch)));
+    assert_eq!(engine.state().nonce, 1);
+}
+
+#[test]
+fn duplicate_nonce_only_applies_first() {
+    let mut engine = engine();
+
+    let mut fork = chained(1, b"fork");
+    fork.commitment = Commitment([0xAA; 32]);
+
+    let results = engine.process_batch(vec![chained(1, b"p1"), fork]);
+
+    assert!(results[0].is_ok());
+    assert!(matches!(results[1], Err(ProtocolError::StateMismatch)));
+    assert_eq!(engine.state().state_root, root(1));
+}
+
+#[test]
+fn batch_continues_from_current_state() {
+    let mut engine = engine();
+    engine.process_batch(vec![chained(1, b"p1")]);
+
+    let results = engine.process_batch(vec![chained(1, b"replay"), chained(2, b"p2")]);
+
+    assert!(matches!(results[0], Err(ProtocolError::StateMismatch)));
+    assert!(results[1].is_ok());
+    assert_eq!(engine.state().nonce, 2);
+}
+
+#[test]
+fn empty_batch_is_a_no_op() {
+    let mut engine = engine();
+    assert!(engine.process_batch(Vec::new()).is_empty());
+    assert_eq!(engine.state().nonce, 0);
+}
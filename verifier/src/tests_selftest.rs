@@ -0,0 +1,134 @@
+use crate::{
+    backend::ProofBackend,
+    backend_stub::StubBackend,
+    engine::PublicInputs,
+    selftest::{self, Fixture, KnownAnswer},
+};
+use zkcg_common::errors::ProtocolError;
+
+fn inputs() -> PublicInputs {
+    PublicInputs {
+        threshold: 10,
+        old_state_root: [0u8; 32],
+        nonce: 1,
+    }
+}
+
+/// Accepts exactly `b"good"`, and advertises vectors for it.
+struct Fussy {
+    answers: Vec<KnownAnswer>,
+}
+
+impl ProofBackend for Fussy {
+    fn verify(
+        &self,
+        proof_bytes: &[u8],
+        _public_inputs: &PublicInputs,
+    ) -> Result<(), ProtocolError> {
+        if proof_bytes == b"good" {
+            Ok(())
+        } else {
+            Err(ProtocolError::InvalidProof)
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "fussy"
+    }
+
+    fn known_answers(&self) -> Vec<KnownAnswer> {
+        self.answers.clone()
+    }
+}
+
+#[test]
+fn matching_verdicts_pass() {
+    let backend = Fussy {
+        answers: vec![
+            KnownAnswer::valid("good", b"good".to_vec(), inputs()),
+            KnownAnswer::invalid("bad", b"bad".to_vec(), inputs()),
+        ],
+    };
+
+    let report = selftest::run(&backend, None);
+    assert_eq!(report.backend, "fussy");
+    assert_eq!(report.checked, 2);
+    assert!(report.passed());
+}
+
+#[test]
+fn wrong_verdicts_are_reported() {
+    let backend = Fussy {
+        answers: vec![
+            KnownAnswer::valid("stale vk", b"other".to_vec(), inputs()),
+            KnownAnswer::invalid("accepted forgery", b"good".to_vec(), inputs()),
+        ],
+    };
+
+    let report = selftest::run(&backend, None);
+    assert!(!report.passed());
+    assert_eq!(
+        report.failures,
+        vec![
+            "stale vk: expected accepted, got rejected".to_string(),
+            "accepted forgery: expected rejected, got accepted".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn only_bad_vectors_is_not_enough() {
+    let backend = Fussy {
+        answers: vec![KnownAnswer::invalid("bad", b"bad".to_vec(), inputs())],
+    };
+
+    assert!(!selftest::run(&backend, None).passed());
+}
+
+#[test]
+fn backend_without_vectors_is_skipped_and_not_passed() {
+    let report = selftest::run(&StubBackend, None);
+    assert!(report.skipped());
+    assert!(!report.passed());
+    assert_eq!(report.failures, vec!["no known-good proof to check".to_string()]);
+}
+
+#[test]
+fn fixture_supplies_the_known_good_proof() {
+    let backend = Fussy {
+        answers: vec![KnownAnswer::invalid("bad", b"bad".to_vec(), inputs())],
+    };
+    let good = Fixture {
+        proof_bytes: b"good".to_vec(),
+        public_inputs: inputs(),
+    };
+    let report = selftest::run(&backend, Some(&good));
+    assert_eq!(report.checked, 2);
+    assert!(report.passed());
+
+    // a fixture the backend rejects, e.g. made for other keys, fails
+    let other = Fixture {
+        proof_bytes: b"other".to_vec(),
+        public_inputs: inputs(),
+    };
+    let report = selftest::run(&backend, Some(&other));
+    assert_eq!(report.failures, vec!["fixture: expected accepted, got rejected".to_string()]);
+}
+
+#[test]
+fn fixture_round_trips_through_the_wire_form() {
+    let fixture = Fixture {
+        proof_bytes: vec![1, 2, 3],
+        public_inputs: inputs(),
+    };
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("selftest.json");
+    std::fs::write(&path, serde_json::to_string(&fixture.to_wire()).unwrap()).unwrap();
+
+    let read = Fixture::read(&path).unwrap();
+    assert_eq!(read.proof_bytes, vec![1, 2, 3]);
+    assert_eq!(read.public_inputs.threshold, 10);
+
+    std::fs::write(&path, "{}").unwrap();
+    assert!(Fixture::read(&path).is_err());
+}
@@ -0,0 +1,193 @@
+use ed25519_dalek::{Signer, SigningKey};
+
+use crate::{
+    backend_stub::StubBackend,
+    engine::{ProverRemoval, PublicInputs, VerifierEngine},
+    signature::{signed_bytes, ProverSignature},
+};
+use zkcg_common::{
+    errors::ProtocolError,
+    state::ProtocolState,
+    types::{Commitment, Hash},
+};
+
+const CHANNEL: Hash = [3u8; 32];
+
+fn prover(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn inputs() -> PublicInputs {
+    PublicInputs {
+        threshold: 10,
+        old_state_root: [0u8; 32],
+        nonce: 1,
+    }
+}
+
+fn commitment() -> Commitment {
+    Commitment([42u8; 32])
+}
+
+fn sign(key: &SigningKey, proof: &[u8], inputs: &PublicInputs, commitment: &Commitment) -> ProverSignature {
+    sign_for(&CHANNEL, key, proof, inputs, commitment)
+}
+
+fn sign_for(
+    channel: &Hash,
+    key: &SigningKey,
+    proof: &[u8],
+    inputs: &PublicInputs,
+    commitment: &Commitment,
+) -> ProverSignature {
+    let sig = key.sign(&signed_bytes(channel, proof, inputs, commitment));
+    ProverSignature {
+        public_key: key.verifying_key().to_bytes(),
+        signature: sig.to_bytes(),
+    }
+}
+
+fn engine_with_provers(keys: &[&SigningKey]) -> VerifierEngine {
+    let state = ProtocolState {
+        channel: CHANNEL,
+        ..ProtocolState::genesis()
+    };
+    let mut engine = VerifierEngine::new(state, Box::new(StubBackend));
+    for key in keys {
+        assert!(engine.register_prover(key.verifying_key().to_bytes()));
+    }
+    engine
+}
+
+#[test]
+fn open_state_accepts_unsigned_submissions() {
+    let mut engine = engine_with_provers(&[]);
+    assert!(engine.process_transition(b"proof", inputs(), commitment()).is_ok());
+}
+
+#[test]
+fn allowlisted_prover_is_accepted() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    let sig = sign(&alice, b"proof", &inputs(), &commitment());
+    engine
+        .process_signed_transition(b"proof", inputs(), commitment(), &sig)
+        .unwrap();
+
+    assert_eq!(engine.state().nonce, 1);
+}
+
+#[test]
+fn unsigned_submission_is_rejected_once_allowlist_is_set() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    let err = engine
+        .process_transition(b"proof", inputs(), commitment())
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+}
+
+#[test]
+fn unknown_prover_is_rejected() {
+    let alice = prover(1);
+    let mallory = prover(2);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    let sig = sign(&mallory, b"proof", &inputs(), &commitment());
+    let err = engine
+        .process_signed_transition(b"proof", inputs(), commitment(), &sig)
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+}
+
+#[test]
+fn signature_over_different_submission_is_rejected() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    // signed a different proof
+    let sig = sign(&alice, b"other-proof", &inputs(), &commitment());
+    let err = engine
+        .process_signed_transition(b"proof", inputs(), commitment(), &sig)
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+
+    // signed a different commitment
+    let sig = sign(&alice, b"proof", &inputs(), &Commitment([7u8; 32]));
+    let err = engine
+        .process_signed_transition(b"proof", inputs(), commitment(), &sig)
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+}
+
+#[test]
+fn signature_for_another_channel_is_rejected() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    // alice is allowlisted here too, but signed for a different channel
+    let sig = sign_for(&[4u8; 32], &alice, b"proof", &inputs(), &commitment());
+    let err = engine
+        .process_signed_transition(b"proof", inputs(), commitment(), &sig)
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+    assert_eq!(engine.state().nonce, 0);
+}
+
+#[test]
+fn last_prover_cannot_be_removed() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    let key = alice.verifying_key().to_bytes();
+    assert_eq!(engine.remove_prover(&key), ProverRemoval::LastProver);
+    assert_eq!(engine.state().provers, vec![key]);
+
+    // submission stays closed to unsigned proofs
+    let err = engine
+        .process_transition(b"proof", inputs(), commitment())
+        .unwrap_err();
+    assert!(matches!(err, ProtocolError::UnauthorizedProver));
+}
+
+#[test]
+fn signed_commitment_cannot_be_swapped_at_apply() {
+    let alice = prover(1);
+    let mut engine = engine_with_provers(&[&alice]);
+
+    let sig = sign(&alice, b"proof", &inputs(), &commitment());
+    let verified = engine
+        .verifier()
+        .verify_signed(b"proof", inputs(), &commitment(), &sig)
+        .unwrap();
+
+    let err = engine.apply(verified, Commitment([7u8; 32])).unwrap_err();
+    assert!(matches!(err, ProtocolError::CommitmentMismatch));
+}
+
+#[test]
+fn removed_prover_loses_access() {
+    let alice = prover(1);
+    let bob = prover(2);
+    let mut engine = engine_with_provers(&[&alice, &bob]);
+
+    let key = alice.verifying_key().to_bytes();
+    assert_eq!(engine.remove_prover(&key), ProverRemoval::Removed);
+    assert_eq!(engine.remove_prover(&key), ProverRemoval::NotListed);
+
+    let sig = sign(&alice, b"proof", &inputs(), &commitment());
+    let verified = engine
+        .verifier()
+        .verify_signed(b"proof", inputs(), &commitment(), &sig)
+        .unwrap();
+    assert!(matches!(
+        engine.apply(verified, commitment()),
+        Err(ProtocolError::UnauthorizedProver)
+    ));
+
+    // an unsigned verification is still rejected while bob is registered
+    let unsigned = engine.verifier().verify(b"proof", inputs()).unwrap();
+    assert!(engine.apply(unsigned, commitment()).is_err());
+}
@@ -0,0 +1,50 @@
+use crate::storage::StateStore;
+use zkcg_common::state::ProtocolState;
+
+fn advanced() -> ProtocolState {
+    ProtocolState {
+        channel: [5u8; 32],
+        state_root: [7u8; 32],
+        nonce: 3,
+        epoch: 1,
+        provers: vec![[9u8; 32]],
+    }
+}
+
+#[test]
+fn missing_file_starts_at_genesis() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+    let store = StateStore::open(Some(path), ProtocolState::genesis()).unwrap();
+    assert_eq!(store.load().nonce, 0);
+}
+
+#[test]
+fn saved_state_survives_reopen() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state.json");
+
+    let store = StateStore::open(Some(path.clone()), ProtocolState::genesis()).unwrap();
+    store.save(advanced());
+
+    // nothing reaches the file before a flush
+    let reopened = StateStore::open(Some(path.clone()), ProtocolState::genesis()).unwrap();
+    assert_eq!(reopened.load().nonce, 0);
+    store.flush().unwrap();
+
+    // genesis is ignored once a state has been saved
+    let reopened = StateStore::open(Some(path.clone()), ProtocolState::genesis()).unwrap();
+    let state = reopened.load();
+    assert_eq!(state.nonce, 3);
+    assert_eq!(state.state_root, [7u8; 32]);
+    assert_eq!(state.provers, vec![[9u8; 32]]);
+    assert_eq!(state.channel, [5u8; 32]);
+}
+
+#[test]
+fn in_memory_store_writes_nothing() {
+    let store = StateStore::new(ProtocolState::genesis());
+    store.save(advanced());
+    store.flush().unwrap();
+    assert_eq!(store.load().epoch, 1);
+}
@@ -0,0 +1,15 @@
+//! Remote prover for a ZKCG verifier node.
+//!
+//! A worker registers with the node under a `worker` API key, claims the jobs
+//! queued by `/v1/prove`, proves them with its [`Prover`] while heartbeating,
+//! and reports each proof back. A worker that goes silent loses its job to
+//! another one.
+
+pub mod prover;
+pub mod worker;
+
+pub use prover::Prover;
+pub use worker::{run, WorkerConfig};
+
+#[cfg(test)]
+mod tests_worker;
@@ -0,0 +1,96 @@
+use clap::{Parser, ValueEnum};
+use std::{path::PathBuf, process::ExitCode, sync::Arc, time::Duration};
+
+use zkcg_client::Client;
+use zkcg_worker::{run, Prover, WorkerConfig};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Backend {
+    Zkvm,
+    Halo2,
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "zkcg-worker", about = "Remote prover for a ZKCG verifier node")]
+struct Args {
+    /// Base URL of the node
+    #[arg(long, env = "ZKCG_NODE")]
+    node: String,
+
+    /// API key with the `worker` role
+    #[arg(long, env = "ZKCG_API_KEY")]
+    api_key: String,
+
+    /// Backend to prove for; must match the node's
+    #[arg(long, value_enum)]
+    backend: Backend,
+
+    /// Halo2 params file; the bundled artifacts are used when unset
+    #[arg(long)]
+    halo2_params: Option<PathBuf>,
+
+    /// Name shown in the node's logs
+    #[arg(long, default_value = "worker")]
+    name: String,
+
+    /// Seconds between claims while the node has nothing queued
+    #[arg(long, default_value_t = 2)]
+    idle_poll_secs: u64,
+}
+
+fn build_prover(args: &Args) -> Result<Arc<dyn Prover>, String> {
+    match args.backend {
+        #[cfg(feature = "zk-vm")]
+        Backend::Zkvm => Ok(Arc::new(zkcg_worker::prover::ZkVmProver)),
+        #[cfg(feature = "zk-halo2")]
+        Backend::Halo2 => {
+            let backend = match &args.halo2_params {
+                Some(path) => zkcg_verifier::Halo2Backend::from_params_file(path)
+                    .map_err(|e| format!("cannot load halo2 params {}: {}", path.display(), e))?,
+                None => zkcg_verifier::Halo2Backend::bundled(),
+            };
+            Ok(Arc::new(zkcg_worker::prover::Halo2Prover::new(backend)))
+        }
+        #[allow(unreachable_patterns)]
+        backend => Err(format!("backend {:?} is not compiled into this binary", backend)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .init();
+
+    let args = Args::parse();
+    let prover = match build_prover(&args) {
+        Ok(prover) => prover,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let node = match Client::new(&args.node) {
+        Ok(node) => node.with_api_key(&args.api_key),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let config = WorkerConfig {
+        name: args.name.clone(),
+        idle_poll: Duration::from_secs(args.idle_poll_secs),
+        ..WorkerConfig::default()
+    };
+    match run(node, prover, config).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("node refused this worker: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use zkcg_client::WorkerJob;
+
+/// Generates proofs for one backend.
+pub trait Prover: Send + Sync + 'static {
+    /// Backend name as the node reports it, e.g. `zkvm`.
+    fn backend(&self) -> &'static str;
+
+    /// Prove `job`. Blocking; the error is reported to the node as the
+    /// job's failure.
+    fn prove(&self, job: &WorkerJob) -> Result<Vec<u8>, String>;
+}
+
+/// Proves with the RISC Zero guest, bound to the job's state root and nonce.
+#[cfg(feature = "zk-vm")]
+pub struct ZkVmProver;
+
+#[cfg(feature = "zk-vm")]
+impl Prover for ZkVmProver {
+    fn backend(&self) -> &'static str {
+        "zkvm"
+    }
+
+    fn prove(&self, job: &WorkerJob) -> Result<Vec<u8>, String> {
+        use zkcg_zkvm_host::ZkVmProverError;
+
+        zkcg_zkvm_host::prove(job.secret_value, job.threshold, job.old_state_root, job.nonce).map_err(
+            |err| match err {
+                ZkVmProverError::PolicyViolation => "policy violation".to_string(),
+                ZkVmProverError::ExecutionFailed => "zkvm execution failed".to_string(),
+            },
+        )
+    }
+}
+
+/// Proves the Halo2 score circuit with the node's params.
+#[cfg(feature = "zk-halo2")]
+pub struct Halo2Prover {
+    backend: zkcg_verifier::Halo2Backend,
+}
+
+#[cfg(feature = "zk-halo2")]
+impl Halo2Prover {
+    /// Params (and so the VK) must match the node's, or its checks reject
+    /// every proof.
+    pub fn new(backend: zkcg_verifier::Halo2Backend) -> Self {
+        Self { backend }
+    }
+}
+
+#[cfg(feature = "zk-halo2")]
+impl Prover for Halo2Prover {
+    fn backend(&self) -> &'static str {
+        "halo2"
+    }
+
+    fn prove(&self, job: &WorkerJob) -> Result<Vec<u8>, String> {
+        self.backend
+            .prove_score(job.secret_value, job.threshold)
+            .ok_or_else(|| "halo2 proving failed".to_string())
+    }
+}
@@ -0,0 +1,208 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::net::TcpListener;
+use zkcg_client::{Client, ClientError, WorkerJob};
+
+use crate::{run, Prover, WorkerConfig};
+
+#[derive(Default)]
+struct Seen {
+    registrations: Vec<Value>,
+    heartbeats: usize,
+    /// (worker id, job id, body)
+    reports: Vec<(String, String, Value)>,
+}
+
+/// Stand-in node handing out `jobs` to whichever worker claims first.
+#[derive(Clone, Default)]
+struct Node {
+    jobs: Arc<Mutex<VecDeque<u64>>>,
+    seen: Arc<Mutex<Seen>>,
+    /// Worker ids answered as unknown, as after a restart
+    forgotten: Arc<Mutex<Vec<String>>>,
+    /// Refuse registrations with this status
+    refuse: Option<StatusCode>,
+}
+
+async fn register(State(node): State<Node>, Json(body): Json<Value>) -> Response {
+    if let Some(status) = node.refuse {
+        return (status, "this node verifies zkvm proofs").into_response();
+    }
+    let mut seen = node.seen.lock().unwrap();
+    seen.registrations.push(body);
+    let worker_id = format!("w{}", seen.registrations.len());
+    let registration = json!({ "worker_id": worker_id, "backend": "stub", "heartbeat_interval_secs": 1 });
+    (StatusCode::CREATED, Json(registration)).into_response()
+}
+
+fn unknown(node: &Node, worker_id: &str) -> Option<Response> {
+    node.forgotten
+        .lock()
+        .unwrap()
+        .contains(&worker_id.to_string())
+        .then(|| {
+            let code = [("x-zkcg-error-code", "ERR_UNKNOWN_WORKER")];
+            (StatusCode::NOT_FOUND, code, "unknown worker").into_response()
+        })
+}
+
+async fn heartbeat(State(node): State<Node>, Path(worker_id): Path<String>) -> Response {
+    if let Some(res) = unknown(&node, &worker_id) {
+        return res;
+    }
+    node.seen.lock().unwrap().heartbeats += 1;
+    StatusCode::NO_CONTENT.into_response()
+}
+
+async fn claim(State(node): State<Node>, Path(worker_id): Path<String>) -> Response {
+    if let Some(res) = unknown(&node, &worker_id) {
+        return res;
+    }
+    let Some(secret) = node.jobs.lock().unwrap().pop_front() else {
+        return StatusCode::NO_CONTENT.into_response();
+    };
+    let root = [3u8; 32];
+    Json(json!({
+        "job_id": format!("job-{}", secret),
+        "backend": "stub",
+        "secret_value": secret,
+        "threshold": 10,
+        "old_state_root": root,
+        "nonce": 4,
+    }))
+    .into_response()
+}
+
+async fn report(
+    State(node): State<Node>,
+    Path((worker_id, job_id)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> StatusCode {
+    node.seen.lock().unwrap().reports.push((worker_id, job_id, body));
+    StatusCode::NO_CONTENT
+}
+
+async fn serve(node: Node) -> String {
+    let app = Router::new()
+        .route("/v1/workers", post(register))
+        .route("/v1/workers/:id/heartbeat", post(heartbeat))
+        .route("/v1/workers/:id/claim", post(claim))
+        .route("/v1/workers/:id/jobs/:job_id", post(report))
+        .with_state(node);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// Proves a job as the bytes of its secret and nonce after `delay`;
+/// secret 0 violates the policy.
+struct Echo {
+    delay: Duration,
+}
+
+impl Prover for Echo {
+    fn backend(&self) -> &'static str {
+        "stub"
+    }
+
+    fn prove(&self, job: &WorkerJob) -> Result<Vec<u8>, String> {
+        std::thread::sleep(self.delay);
+        if job.secret_value == 0 {
+            return Err("policy violation".to_string());
+        }
+        assert_eq!(job.old_state_root, [3; 32]);
+        Ok(vec![job.secret_value as u8, job.nonce as u8])
+    }
+}
+
+fn config() -> WorkerConfig {
+    WorkerConfig {
+        name: "test".to_string(),
+        idle_poll: Duration::from_millis(5),
+        retry_delay: Duration::from_millis(5),
+    }
+}
+
+async fn start(node: &Node, delay: Duration) -> tokio::task::JoinHandle<Result<(), ClientError>> {
+    let client = Client::new(serve(node.clone()).await).unwrap();
+    tokio::spawn(run(client, Arc::new(Echo { delay }), config()))
+}
+
+async fn wait_for_reports(node: &Node, count: usize) {
+    for _ in 0..400 {
+        if node.seen.lock().unwrap().reports.len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("worker never reported {} jobs", count);
+}
+
+#[tokio::test]
+async fn claimed_jobs_are_proved_and_reported() {
+    let node = Node::default();
+    node.jobs.lock().unwrap().extend([7, 0]);
+    let worker = start(&node, Duration::ZERO).await;
+
+    wait_for_reports(&node, 2).await;
+    worker.abort();
+
+    let seen = node.seen.lock().unwrap();
+    assert_eq!(seen.registrations, vec![json!({ "name": "test", "backends": ["stub"] })]);
+    let (worker_id, job_id, body) = &seen.reports[0];
+    assert_eq!((worker_id.as_str(), job_id.as_str()), ("w1", "job-7"));
+    assert_eq!(body, &json!({ "proof": STANDARD.encode([7u8, 4]) }));
+    assert_eq!(seen.reports[1].2, json!({ "error": "policy violation" }));
+}
+
+#[tokio::test]
+async fn long_proofs_are_kept_alive_with_heartbeats() {
+    let node = Node::default();
+    node.jobs.lock().unwrap().push_back(1);
+    let worker = start(&node, Duration::from_millis(1500)).await;
+
+    wait_for_reports(&node, 1).await;
+    worker.abort();
+
+    assert!(node.seen.lock().unwrap().heartbeats >= 1);
+}
+
+#[tokio::test]
+async fn forgotten_worker_registers_again() {
+    let node = Node::default();
+    node.forgotten.lock().unwrap().push("w1".to_string());
+    node.jobs.lock().unwrap().push_back(9);
+    let worker = start(&node, Duration::ZERO).await;
+
+    wait_for_reports(&node, 1).await;
+    worker.abort();
+
+    let seen = node.seen.lock().unwrap();
+    assert_eq!(seen.registrations.len(), 2);
+    assert_eq!(seen.reports[0].0, "w2");
+}
+
+#[tokio::test]
+async fn refused_registration_stops_the_worker() {
+    let node = Node {
+        refuse: Some(StatusCode::UNPROCESSABLE_ENTITY),
+        ..Node::default()
+    };
+    let worker = start(&node, Duration::ZERO).await;
+
+    let err = worker.await.unwrap().unwrap_err();
+    assert!(matches!(err, ClientError::Status { status: 422, .. }));
+}
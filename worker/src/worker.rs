@@ -0,0 +1,132 @@
+use std::{sync::Arc, time::Duration};
+
+use zkcg_client::{Client, ClientError, WorkerRegistration};
+
+use crate::prover::Prover;
+
+#[derive(Debug, Clone)]
+pub struct WorkerConfig {
+    /// Shown in the node's logs
+    pub name: String,
+    /// Wait between claims while the node has nothing queued
+    pub idle_poll: Duration,
+    /// Wait before trying again after a failed request
+    pub retry_delay: Duration,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            name: "worker".to_string(),
+            idle_poll: Duration::from_secs(2),
+            retry_delay: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Take jobs from `node` and prove them with `prover`, one at a time.
+///
+/// Runs until the node refuses the worker outright (bad key, wrong role or
+/// a backend it does not verify); anything else is logged and retried, and
+/// a node that forgot the worker is registered with again.
+pub async fn run(node: Client, prover: Arc<dyn Prover>, config: WorkerConfig) -> Result<(), ClientError> {
+    let mut registration = register(&node, prover.as_ref(), &config).await?;
+    loop {
+        match work_once(&node, &prover, &registration).await {
+            Ok(true) => {}
+            Ok(false) => tokio::time::sleep(config.idle_poll).await,
+            Err(err) if err.is_unknown_worker() => {
+                tracing::warn!(worker = %registration.worker_id, "node no longer knows this worker; registering again");
+                registration = register(&node, prover.as_ref(), &config).await?;
+            }
+            Err(err) if is_fatal(&err) => return Err(err),
+            Err(err) => {
+                tracing::warn!(error = %err, "request to node failed");
+                tokio::time::sleep(config.retry_delay).await;
+            }
+        }
+    }
+}
+
+async fn register(
+    node: &Client,
+    prover: &dyn Prover,
+    config: &WorkerConfig,
+) -> Result<WorkerRegistration, ClientError> {
+    loop {
+        match node.register_worker(&config.name, &[prover.backend()]).await {
+            Ok(registration) => {
+                tracing::info!(
+                    worker = %registration.worker_id,
+                    backend = %registration.backend,
+                    heartbeat_secs = registration.heartbeat_interval_secs,
+                    "registered with node"
+                );
+                return Ok(registration);
+            }
+            Err(err) if is_fatal(&err) => return Err(err),
+            Err(err) => {
+                tracing::warn!(error = %err, "registration failed");
+                tokio::time::sleep(config.retry_delay).await;
+            }
+        }
+    }
+}
+
+/// Claim, prove and report one job. `false` when nothing was queued.
+async fn work_once(
+    node: &Client,
+    prover: &Arc<dyn Prover>,
+    registration: &WorkerRegistration,
+) -> Result<bool, ClientError> {
+    let worker_id = &registration.worker_id;
+    let Some(job) = node.claim_job(worker_id).await? else {
+        return Ok(false);
+    };
+    tracing::info!(job = %job.job_id, nonce = job.nonce, threshold = job.threshold, "job claimed");
+
+    let proving = tokio::task::spawn_blocking({
+        let prover = prover.clone();
+        let job = job.clone();
+        move || prover.prove(&job)
+    });
+    tokio::pin!(proving);
+
+    // the node hands the job to another worker if these stop
+    let interval = Duration::from_secs(registration.heartbeat_interval_secs.max(1));
+    let outcome = loop {
+        tokio::select! {
+            res = &mut proving => break res.unwrap_or_else(|_| Err("prover panicked".to_string())),
+            _ = tokio::time::sleep(interval) => match node.worker_heartbeat(worker_id).await {
+                Err(err) if err.is_unknown_worker() => return Err(err),
+                Err(err) => tracing::warn!(error = %err, "heartbeat failed"),
+                Ok(()) => {}
+            },
+        }
+    };
+
+    if let Err(reason) = &outcome {
+        tracing::warn!(job = %job.job_id, %reason, "proving failed");
+    }
+    let reported = node
+        .complete_job(worker_id, &job.job_id, outcome.as_deref().map_err(String::as_str))
+        .await;
+    match reported {
+        Ok(()) => tracing::info!(job = %job.job_id, "job reported"),
+        // handed to another worker after missed heartbeats
+        Err(ClientError::Status { status: 409, message }) => {
+            tracing::warn!(job = %job.job_id, %message, "node did not take the result");
+        }
+        // the proof did not verify; the job has failed
+        Err(ClientError::Rejected(err)) => {
+            tracing::warn!(job = %job.job_id, error = %err, "node rejected the proof");
+        }
+        Err(err) => return Err(err),
+    }
+    Ok(true)
+}
+
+/// Errors retrying cannot fix.
+fn is_fatal(err: &ClientError) -> bool {
+    matches!(err, ClientError::Status { status: 401 | 403 | 422, .. })
+}